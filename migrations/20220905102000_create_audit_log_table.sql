@@ -0,0 +1,15 @@
+create table audit_log
+(
+    id         bigserial primary key,
+    user_id    bigint      not null references users (user_id),
+    route      text        not null,
+    method     text        not null,
+    request_id text        not null,
+    entity_ids text[]      not null,
+    diff       jsonb       not null,
+    created_at timestamptz not null default now()
+);
+
+create index audit_log_user_id_idx on audit_log (user_id);
+create index audit_log_created_at_idx on audit_log (created_at);
+create index audit_log_entity_ids_idx on audit_log using gin (entity_ids);
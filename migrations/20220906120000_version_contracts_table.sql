@@ -0,0 +1,7 @@
+alter table contracts
+    add column version     bigserial primary key,
+    add column created_by  bigint references users (user_id),
+    add column created_at  timestamptz not null default now(),
+    add column rollback_of bigint references contracts (version);
+
+create index contracts_created_at_idx on contracts (created_at);
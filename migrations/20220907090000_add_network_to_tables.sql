@@ -0,0 +1,45 @@
+-- the rows stored before networks were introduced belong to the network of the deployment,
+-- which is set on the database before it's migrated, e.g.
+-- `alter database indexer_db set app.network = 'testnet'`, the migration of stored rows fails
+-- without it
+do $$
+begin
+    if nullif(current_setting('app.network', true), '') is null and (
+        exists (select from nft_tokens)
+        or exists (select from sales)
+        or exists (select from asks)
+        or exists (select from bids)
+        or exists (select from contracts)
+    ) then
+        raise exception 'The `app.network` setting must name the network of the stored rows.';
+    end if;
+end
+$$;
+
+alter table nft_tokens add column network text;
+update nft_tokens set network = current_setting('app.network', true);
+alter table nft_tokens alter column network set not null;
+alter table nft_tokens drop constraint nft_tokens_token_id_key;
+alter table nft_tokens add constraint nft_tokens_network_token_id_key unique (network, token_id);
+
+alter table sales add column network text;
+update sales set network = current_setting('app.network', true);
+alter table sales alter column network set not null;
+create index sales_network_idx on sales (network);
+
+alter table asks add column network text;
+update asks set network = current_setting('app.network', true);
+alter table asks alter column network set not null;
+alter table asks drop constraint asks_pkey;
+alter table asks add primary key (network, id);
+
+alter table bids add column network text;
+update bids set network = current_setting('app.network', true);
+alter table bids alter column network set not null;
+alter table bids drop constraint bids_pkey;
+alter table bids add primary key (network, id);
+
+alter table contracts add column network text;
+update contracts set network = current_setting('app.network', true);
+alter table contracts alter column network set not null;
+create index contracts_network_version_idx on contracts (network, version);
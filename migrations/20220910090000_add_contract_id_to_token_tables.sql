@@ -0,0 +1,56 @@
+-- the rows stored before the contracts were introduced belong to the active nft contract of their network,
+-- the rows of a network without contracts get the `unconfigured` fallback contract id, which is reachable
+-- once listed in the network's `networks.nft_contracts` settings or updated to the real contract id
+alter table nft_tokens add column contract_id text;
+alter table sales add column contract_id text;
+alter table asks add column contract_id text;
+alter table bids add column contract_id text;
+
+with active as (
+    select distinct on (network) network, contracts_config ->> 'nft_contract_id' as contract_id
+    from contracts
+    order by network, version desc
+)
+update nft_tokens t set contract_id = coalesce(
+    (select active.contract_id from active where active.network = t.network),
+    'unconfigured'
+);
+with active as (
+    select distinct on (network) network, contracts_config ->> 'nft_contract_id' as contract_id
+    from contracts
+    order by network, version desc
+)
+update sales t set contract_id = coalesce(
+    (select active.contract_id from active where active.network = t.network),
+    'unconfigured'
+);
+with active as (
+    select distinct on (network) network, contracts_config ->> 'nft_contract_id' as contract_id
+    from contracts
+    order by network, version desc
+)
+update asks t set contract_id = coalesce(
+    (select active.contract_id from active where active.network = t.network),
+    'unconfigured'
+);
+with active as (
+    select distinct on (network) network, contracts_config ->> 'nft_contract_id' as contract_id
+    from contracts
+    order by network, version desc
+)
+update bids t set contract_id = coalesce(
+    (select active.contract_id from active where active.network = t.network),
+    'unconfigured'
+);
+
+alter table nft_tokens alter column contract_id set not null;
+alter table sales alter column contract_id set not null;
+alter table asks alter column contract_id set not null;
+alter table bids alter column contract_id set not null;
+
+alter table nft_tokens drop constraint nft_tokens_network_token_id_key;
+alter table nft_tokens add constraint nft_tokens_network_contract_id_token_id_key
+    unique (network, contract_id, token_id);
+create index sales_network_contract_id_token_id_idx on sales (network, contract_id, token_id);
+create index asks_network_contract_id_token_id_idx on asks (network, contract_id, token_id);
+create index bids_network_contract_id_token_id_idx on bids (network, contract_id, token_id);
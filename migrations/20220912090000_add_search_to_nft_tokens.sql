@@ -0,0 +1,18 @@
+create extension if not exists pg_trgm;
+
+-- the trait values of the model are searched without its kind, which is a facet
+alter table nft_tokens add column search_document tsvector generated always as (
+    setweight(to_tsvector('simple', coalesce(title, '')), 'A')
+    || setweight(to_tsvector('simple', coalesce(description, '')), 'B')
+    || setweight(to_tsvector('simple', owner_id), 'B')
+    || setweight(jsonb_to_tsvector('simple', model - 'kind', '["string"]'), 'C')
+) stored;
+
+-- the fallback for the typos, which the full-text search misses
+alter table nft_tokens add column search_text text generated always as (
+    lower(coalesce(title, '') || ' ' || coalesce(description, '') || ' ' || owner_id || ' '
+        || (model - 'kind')::text)
+) stored;
+
+create index nft_tokens_search_document_idx on nft_tokens using gin (search_document);
+create index nft_tokens_search_text_idx on nft_tokens using gin (search_text gin_trgm_ops);
@@ -0,0 +1,52 @@
+-- the rarity among the tokens of the same network, contract and kind, higher scores are rarer
+-- and the rank 1 is the rarest by the trait-normalized score
+alter table nft_tokens
+    add column rarity_statistical_score double precision,
+    add column rarity_trait_normalized_score double precision,
+    add column rarity_rank bigint;
+
+create index nft_tokens_network_contract_id_kind_idx
+    on nft_tokens (network, contract_id, (model ->> 'kind'));
+
+with traits as (
+    select n.id, n.network, n.contract_id, n.model ->> 'kind' as kind, t.key, t.value
+    -- the values are compared as jsonb so that the json nulls are counted rather than dropped
+    -- by the joins
+    from nft_tokens n, jsonb_each(n.model - 'kind') as t
+),
+totals as (
+    select network, contract_id, model ->> 'kind' as kind, count(*)::float8 as total
+    from nft_tokens
+    group by 1, 2, 3
+),
+value_counts as (
+    select network, contract_id, kind, key, value, count(*)::float8 as count
+    from traits
+    group by 1, 2, 3, 4, 5
+),
+trait_sizes as (
+    select network, contract_id, kind, key, count(*)::float8 as size
+    from value_counts
+    group by 1, 2, 3, 4
+),
+scores as (
+    select traits.id, traits.network, traits.contract_id, traits.kind,
+        sum(-ln(value_counts.count / totals.total)) as statistical,
+        sum(totals.total / value_counts.count / trait_sizes.size) as trait_normalized
+    from traits
+        join value_counts using (network, contract_id, kind, key, value)
+        join trait_sizes using (network, contract_id, kind, key)
+        join totals using (network, contract_id, kind)
+    group by 1, 2, 3, 4
+),
+ranks as (
+    select id, statistical, trait_normalized,
+        rank() over (partition by network, contract_id, kind order by trait_normalized desc) as rank
+    from scores
+)
+update nft_tokens n
+set rarity_statistical_score = ranks.statistical,
+    rarity_trait_normalized_score = ranks.trait_normalized,
+    rarity_rank = ranks.rank
+from ranks
+where n.id = ranks.id;
@@ -0,0 +1,14 @@
+-- the indexes of the list filters, the token ids are filtered with or without their contract
+create index nft_tokens_network_owner_id_idx on nft_tokens (network, owner_id);
+create index nft_tokens_network_token_id_idx on nft_tokens (network, token_id);
+create index nft_tokens_network_kind_idx on nft_tokens (network, (model ->> 'kind'));
+-- the order of `sort=rarity`, the unranked tokens last
+create index nft_tokens_network_rarity_rank_id_idx on nft_tokens (network, rarity_rank, id);
+
+-- replaced by the ones below, which lead with the network too
+drop index sales_network_idx;
+create index sales_network_token_id_idx on sales (network, token_id);
+create index sales_network_date_idx on sales (network, date);
+
+create index asks_network_token_id_idx on asks (network, token_id);
+create index bids_network_token_id_idx on bids (network, token_id);
@@ -0,0 +1,4 @@
+-- the version of a row, bumped by every update and served as the strong etag of its resource
+alter table nft_tokens add column row_version bigint not null default 1;
+alter table asks add column row_version bigint not null default 1;
+alter table bids add column row_version bigint not null default 1;
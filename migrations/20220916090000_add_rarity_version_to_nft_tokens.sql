@@ -0,0 +1,3 @@
+-- the version of the rarity of a token, bumped by the rescoring caused by the writes of the other tokens of
+-- its kind, which tags the token's representation along with its row version but isn't compared by `If-Match`
+alter table nft_tokens add column rarity_version bigint not null default 0;
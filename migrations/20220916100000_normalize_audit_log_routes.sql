@@ -0,0 +1,3 @@
+-- the entries of the versioned and network scoped aliases are stored as their root route, e.g. `/sales`
+update audit_log set route = regexp_replace(route, '^/(v[0-9]+/)?(networks/\{network\}/)?', '/')
+where route ~ '^/(v[0-9]+/|networks/)';
@@ -0,0 +1,16 @@
+-- the entries stored before the network was recorded were made on the network of the
+-- deployment, see the `app.network` setting of `20220907090000_add_network_to_tables.sql`
+do $$
+begin
+    if nullif(current_setting('app.network', true), '') is null
+        and exists (select from audit_log)
+    then
+        raise exception 'The `app.network` setting must name the network of the stored entries.';
+    end if;
+end
+$$;
+
+alter table audit_log add column network text;
+update audit_log set network = current_setting('app.network', true);
+alter table audit_log alter column network set not null;
+create index audit_log_network_idx on audit_log (network);
@@ -0,0 +1,6 @@
+SELECT contract_id, id, token_id, account_id, approval_id, price
+FROM asks
+WHERE network = $1
+    AND ($2::text IS null OR contract_id = $2)
+    AND ($3::text IS null OR token_id = $3)
+ORDER BY id LIMIT $4 OFFSET $5
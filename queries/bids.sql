@@ -0,0 +1,6 @@
+SELECT contract_id, id, token_id, account_id, expire_at, create_at, price
+FROM bids
+WHERE network = $1
+    AND ($2::text IS null OR contract_id = $2)
+    AND ($3::text IS null OR token_id = $3)
+ORDER BY id LIMIT $4 OFFSET $5
@@ -0,0 +1,5 @@
+SELECT t.key AS "name!", t.value AS "value!", count(*) AS "count!"
+FROM nft_tokens, jsonb_each_text(model - 'kind') AS t
+WHERE network = $1 AND ($2::text IS null OR contract_id = $2) AND model->>'kind' = $3
+GROUP BY 1, 2
+ORDER BY 1, 3 DESC, 2
@@ -0,0 +1,63 @@
+-- the page is taken by the branch of the sort, each ordered as an index so that the generic plan, which
+-- is planned before the sort is known, still reads the page from the index
+WITH filtered AS NOT MATERIALIZED (
+    SELECT n.*
+    FROM nft_tokens AS n
+    WHERE n.network = $1
+        AND ($2::text IS null OR n.contract_id = $2)
+        AND ($3::text IS null OR n.token_id = $3)
+        AND ($4::text IS null OR n.owner_id = $4)
+        AND ($5::text IS null OR n.model->>'kind' = $5)
+        AND ($12::bool IS null OR $12 = EXISTS (
+            SELECT 1 FROM asks
+            WHERE network = n.network AND contract_id = n.contract_id AND token_id = n.token_id
+        ))
+        AND ($13::bool IS null OR $13 = EXISTS (
+            SELECT 1 FROM bids
+            WHERE network = n.network AND contract_id = n.contract_id AND token_id = n.token_id
+                AND (expire_at IS null OR expire_at > now())
+        ))
+),
+page AS (
+    (
+        SELECT * FROM filtered
+        WHERE $8::text = 'rarity'
+        ORDER BY rarity_rank, id
+        LIMIT $6 OFFSET $7
+    )
+    UNION ALL
+    (
+        SELECT * FROM filtered
+        WHERE $8::text IS DISTINCT FROM 'rarity'
+        ORDER BY id
+        LIMIT $6 OFFSET $7
+    )
+)
+SELECT n.contract_id AS "contract_id!", n.token_id AS "token_id!", n.owner_id AS "owner_id!", n.media AS "media!",
+    n.model AS "model!: Json<ModelKind>", n.copies, n.description, n.expires_at, n.issued_at, n.title, n.media_hash,
+    n.rarity_statistical_score, n.rarity_trait_normalized_score, n.rarity_rank,
+    n.row_version AS "row_version!", n.rarity_version AS "rarity_version!",
+    ask.id AS "ask_id?", ask.account_id AS "ask_account_id?", ask.approval_id AS "ask_approval_id?", ask.price AS "ask_price?",
+    bid.id AS "bid_id?", bid.account_id AS "bid_account_id?", bid.expire_at AS "bid_expire_at?", bid.create_at AS "bid_create_at?", bid.price AS "bid_price?",
+    sale.id AS "sale_id?", sale.prev_owner AS "sale_prev_owner?", sale.curr_owner AS "sale_curr_owner?", sale.price AS "sale_price?", sale.date AS "sale_date?"
+FROM page AS n
+LEFT JOIN LATERAL (
+    SELECT id, account_id, approval_id, price
+    FROM asks
+    WHERE $9 AND network = n.network AND contract_id = n.contract_id AND token_id = n.token_id
+    ORDER BY price, id LIMIT 1
+) AS ask ON true
+LEFT JOIN LATERAL (
+    SELECT id, account_id, expire_at, create_at, price
+    FROM bids
+    WHERE $10 AND network = n.network AND contract_id = n.contract_id AND token_id = n.token_id
+        AND (expire_at IS null OR expire_at > now())
+    ORDER BY price DESC, id LIMIT 1
+) AS bid ON true
+LEFT JOIN LATERAL (
+    SELECT id, prev_owner, curr_owner, price, date
+    FROM sales
+    WHERE $11 AND network = n.network AND contract_id = n.contract_id AND token_id = n.token_id
+    ORDER BY date DESC, id DESC LIMIT 1
+) AS sale ON true
+ORDER BY CASE WHEN $8 = 'rarity' THEN n.rarity_rank END NULLS LAST, n.id
@@ -0,0 +1,6 @@
+SELECT id, contract_id, prev_owner, curr_owner, token_id, price, date
+FROM sales
+WHERE network = $1
+    AND ($2::text IS null OR contract_id = $2)
+    AND ($3::text IS null OR token_id = $3)
+ORDER BY id LIMIT $4 OFFSET $5
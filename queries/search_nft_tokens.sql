@@ -0,0 +1,14 @@
+SELECT contract_id, token_id, owner_id, media, model as "model: Json<ModelKind>", copies, description, expires_at, issued_at, title, media_hash,
+    rarity_statistical_score, rarity_trait_normalized_score, rarity_rank, row_version, rarity_version
+FROM nft_tokens
+WHERE network = $1
+    AND ($4::text IS null OR contract_id = $4)
+    AND ($5::text IS null OR model->>'kind' = $5)
+    AND (search_document @@ to_tsquery('simple', $2) OR $3 <% search_text)
+ORDER BY
+    CASE WHEN search_document @@ to_tsquery('simple', $2)
+        THEN 1 + ts_rank(search_document, to_tsquery('simple', $2))
+        ELSE word_similarity($3, search_text)
+    END DESC,
+    id
+LIMIT $6 OFFSET $7
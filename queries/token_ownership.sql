@@ -0,0 +1,6 @@
+SELECT token_id, COALESCE(bool_or(owner_id = $2), false) AS "owned!"
+FROM nft_tokens
+WHERE network = $1
+    AND ($3::text IS null OR contract_id = $3)
+    AND token_id = ANY($4)
+GROUP BY token_id
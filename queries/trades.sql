@@ -0,0 +1,4 @@
+SELECT id, contract_id, prev_owner, curr_owner, token_id, price, date
+FROM sales
+WHERE network = $1 AND ($2::text IS null OR contract_id = $2) AND date >= $3
+ORDER BY date OFFSET $4 LIMIT $5
@@ -18,4 +18,10 @@ echo >&2 "Postgres is up and running on port ${DB_PORT}!"
 
 export DATABASE_URL=postgres://${DB_USER}:${DB_PASSWORD}@${DB_HOST}:${DB_PORT}/${DB_NAME}
 sqlx database create
+# the network of the rows stored before the tables were scoped by network, the migrations
+# fail without it when there are such rows, e.g. `DB_NETWORK=testnet`
+if [[ -n "${DB_NETWORK}" ]]; then
+  psql --host "${DB_HOST}" --username "${DB_USER}" --port "${DB_PORT}" --dbname "${DB_NAME}" \
+    --command "ALTER DATABASE \"${DB_NAME}\" SET app.network = '${DB_NETWORK}';"
+fi
 sqlx migrate run
@@ -0,0 +1,124 @@
+use std::time::SystemTime;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue, HttpDate, LINK};
+use actix_web::web;
+use actix_web_lab::middleware::Next;
+
+use crate::config::{ApiVersionSettings, DeprecationSettings};
+
+/// The version key of the unversioned root aliases.
+pub const LEGACY: &str = "legacy";
+
+/// The API version of a request path, e.g. `v1` for `/v1/sales`, or [`LEGACY`] for the
+/// unversioned root aliases.
+pub fn version(path: &str) -> &str {
+    let segment = path
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or_default();
+
+    match segment.strip_prefix('v') {
+        Some(number) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => segment,
+        _ => LEGACY,
+    }
+}
+
+/// Strips the version prefix of a path or a matched pattern, e.g. `v1/sales` becomes `sales`.
+pub fn strip_version(path: &str) -> &str {
+    let path = path.trim_start_matches('/');
+    match version(path) {
+        LEGACY => path,
+        version => path[version.len()..].trim_start_matches('/'),
+    }
+}
+
+/// The root route of a matched pattern, e.g. `v1/networks/{network}/sales` becomes `sales`,
+/// so that the versioned and network scoped routes share the budgets, the cached responses
+/// and the audit entries of the root's ones.
+pub fn route_name(pattern: &str) -> &str {
+    let route = strip_version(pattern);
+    route.strip_prefix("networks/{network}/").unwrap_or(route)
+}
+
+/// Announces the deprecation of the requested API version with the `Deprecation`, `Sunset`
+/// and `Link` headers.
+pub async fn deprecation(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let deprecation = req
+        .app_data::<web::Data<ApiVersionSettings>>()
+        .and_then(|settings| settings.deprecation(version(req.path())).cloned());
+
+    let mut response = next.call(req).await?;
+    if let Some(deprecation) = deprecation {
+        for (name, value) in headers(&deprecation) {
+            response.headers_mut().append(name, value);
+        }
+    }
+
+    Ok(response)
+}
+
+fn headers(deprecation: &DeprecationSettings) -> Vec<(HeaderName, HeaderValue)> {
+    let http_date = |moment: chrono::DateTime<chrono::Utc>| {
+        HeaderValue::from_str(&HttpDate::from(SystemTime::from(moment)).to_string())
+            .expect("HTTP date is always a valid header value")
+    };
+
+    let mut headers = vec![(
+        HeaderName::from_static("deprecation"),
+        deprecation
+            .since
+            .map(http_date)
+            .unwrap_or_else(|| HeaderValue::from_static("true")),
+    )];
+    if let Some(sunset) = deprecation.sunset {
+        headers.push((HeaderName::from_static("sunset"), http_date(sunset)));
+    }
+    if let Some(link) = deprecation
+        .link
+        .as_ref()
+        .and_then(|link| HeaderValue::from_str(&format!("<{link}>; rel=\"deprecation\"")).ok())
+    {
+        headers.push((LINK, link));
+    }
+
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_is_taken_from_the_first_segment() {
+        assert_eq!(version("/v1/sales"), "v1");
+        assert_eq!(version("/v12/networks/testnet/sales"), "v12");
+        assert_eq!(version("/sales"), LEGACY);
+        assert_eq!(version("/v/sales"), LEGACY);
+        assert_eq!(version("/vx/sales"), LEGACY);
+        assert_eq!(version("/"), LEGACY);
+    }
+
+    #[test]
+    fn version_prefix_is_stripped() {
+        assert_eq!(strip_version("/v1/sales"), "sales");
+        assert_eq!(
+            strip_version("/v2/networks/{network}/sales"),
+            "networks/{network}/sales"
+        );
+        assert_eq!(strip_version("/sales"), "sales");
+    }
+
+    #[test]
+    fn scoped_routes_are_named_by_their_root_route() {
+        for pattern in ["/sales", "/v1/sales", "/networks/{network}/sales"] {
+            assert_eq!(route_name(pattern), "sales");
+        }
+        assert_eq!(route_name("/v1/networks/{network}/sales"), "sales");
+    }
+}
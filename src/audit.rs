@@ -0,0 +1,124 @@
+use std::future::{ready, Ready};
+
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{Postgres, Transaction};
+use tracing_actix_web::RequestId;
+use utoipa::ToSchema;
+
+use crate::api_version::route_name;
+use crate::auth::middleware::UserId;
+use crate::errors::JsonError;
+use crate::network::Network;
+
+/// A stored write made through the authenticated routes.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub user_id: i64,
+    pub network: String,
+    pub route: String,
+    pub method: String,
+    pub request_id: String,
+    pub entity_ids: Vec<String>,
+    /// The `before` and `after` states of the changed entities.
+    #[schema(value_type = Object)]
+    pub diff: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Who changed what, extracted from an authenticated request.
+#[derive(Debug, Clone)]
+pub struct AuditContext {
+    pub user_id: i64,
+    pub network: String,
+    pub route: String,
+    pub method: String,
+    pub request_id: String,
+}
+
+impl AuditContext {
+    fn extract(req: &HttpRequest) -> Result<Self, anyhow::Error> {
+        let extensions = req.extensions();
+        let user_id = extensions
+            .get::<UserId>()
+            .context("The request wasn't authenticated.")?;
+        let request_id = extensions
+            .get::<RequestId>()
+            .context("The request doesn't have an id.")?;
+        let network = Network::extract(req)?;
+
+        Ok(Self {
+            user_id: user_id.0,
+            network: network.to_string(),
+            // the versioned and network scoped aliases are stored as the root route
+            route: format!(
+                "/{}",
+                route_name(
+                    &req.match_pattern()
+                        .unwrap_or_else(|| req.path().to_string())
+                )
+            ),
+            method: req.method().to_string(),
+            request_id: request_id.to_string(),
+        })
+    }
+
+    #[tracing::instrument(name = "Store audit entry to database", skip(self, diff, tx))]
+    pub async fn record(
+        &self,
+        entity_ids: &[String],
+        diff: Value,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> Result<(), anyhow::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (user_id, network, route, method, request_id, entity_ids, diff)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            self.user_id,
+            self.network,
+            self.route,
+            self.method,
+            self.request_id,
+            entity_ids,
+            diff,
+        )
+        .execute(tx)
+        .await
+        .context("Failed to store the audit entry.")?;
+
+        Ok(())
+    }
+}
+
+impl FromRequest for AuditContext {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Self::extract(req).map_err(|e| {
+            actix_web::error::InternalError::from_response(
+                e.to_string(),
+                HttpResponse::InternalServerError().json(JsonError::unexpected(e)),
+            )
+            .into()
+        }))
+    }
+}
+
+pub fn created<T: Serialize>(after: &T) -> Value {
+    json!({ "before": null, "after": after })
+}
+
+pub fn updated<B: Serialize, A: Serialize>(before: &B, after: &A) -> Value {
+    json!({ "before": before, "after": after })
+}
+
+pub fn deleted<T: Serialize>(before: &T) -> Value {
+    json!({ "before": before, "after": null })
+}
@@ -1,30 +1,76 @@
+use std::fmt::{Display, Formatter};
+
 use crate::auth::password::{basic_auth, validate_credentials};
-use crate::errors::JsonError;
+use crate::errors::{AuthError, JsonError};
 use actix_web::body::MessageBody;
 use actix_web::dev::{ServiceRequest, ServiceResponse};
-use actix_web::HttpResponse;
+use actix_web::{HttpMessage, HttpResponse};
 use actix_web_lab::middleware::Next;
 use anyhow::Context;
 use sqlx::PgPool;
 
-pub async fn auth(
-    req: ServiceRequest,
-    next: Next<impl MessageBody>,
-) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
-    let creds = basic_auth(req.headers())?;
-    let db_pool = req
-        .app_data::<actix_web::web::Data<PgPool>>()
+/// Id of the authenticated user, stored in the request extensions by [`auth`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserId(pub i64);
+
+impl Display for UserId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+fn get_db_pool(req: &ServiceRequest) -> Result<&actix_web::web::Data<PgPool>, actix_web::Error> {
+    req.app_data::<actix_web::web::Data<PgPool>>()
         .context("Failed to get database pool from application data.")
         .map_err(|e| {
             actix_web::error::InternalError::from_response(
                 e.to_string(),
-                HttpResponse::InternalServerError().json(JsonError::new(e)),
+                HttpResponse::InternalServerError().json(JsonError::unexpected(e)),
             )
-        })?;
+            .into()
+        })
+}
+
+pub async fn auth(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let creds = basic_auth(req.headers())?;
+    let db_pool = get_db_pool(&req)?;
 
     tracing::Span::current().record("username", &tracing::field::display(&creds.username));
     let user_id = validate_credentials(creds, db_pool).await?;
     tracing::Span::current().record("user_id", &tracing::field::display(&user_id));
+    req.extensions_mut().insert(UserId(user_id));
+
+    next.call(req).await
+}
+
+/// Lets through only admins, must be wrapped by [`auth`].
+pub async fn admin(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let user_id = req
+        .extensions()
+        .get::<UserId>()
+        .copied()
+        .context("The request wasn't authenticated.")
+        .map_err(AuthError::UnexpectedError)?;
+    let db_pool = get_db_pool(&req)?;
+
+    let is_admin = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE user_id = $1 AND is_admin) as "is_admin!""#,
+        user_id.0
+    )
+    .fetch_one(db_pool.get_ref())
+    .await
+    .context("Failed to perform a query to retrieve user's role.")
+    .map_err(AuthError::UnexpectedError)?;
+
+    if !is_admin {
+        return Err(AuthError::Forbidden(anyhow::anyhow!("User {user_id} isn't an admin.")).into());
+    }
 
     next.call(req).await
 }
@@ -0,0 +1,10 @@
+pub use backend::*;
+pub use conditional::*;
+pub use memory::*;
+pub use response_cache::*;
+
+mod backend;
+mod conditional;
+mod memory;
+pub mod middleware;
+mod response_cache;
@@ -0,0 +1,120 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use actix_web::http::header::{EntityTag, IfNoneMatch, CONTENT_TYPE, ETAG};
+use actix_web::web::Bytes;
+use actix_web::HttpResponse;
+use sha2::{Digest, Sha256};
+
+/// The future of a cache backend's operation.
+pub type CacheFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, anyhow::Error>> + Send + 'a>>;
+
+/// The storage of the cached responses, e.g. the process memory or a shared Redis.
+///
+/// The keys start with the network and the route name of the response, see
+/// [`ResponseCache::key`](crate::cache::ResponseCache::key), which lets [`Self::remove_prefix`]
+/// drop a route or a whole network.
+pub trait CacheBackend: Send + Sync {
+    /// The response stored under the key, unless it expired.
+    fn get<'a>(&'a self, key: &'a str) -> CacheFuture<'a, Option<CachedResponse>>;
+
+    /// Stores the response under the key for the time to live.
+    fn set(&self, key: String, response: CachedResponse, ttl: Duration) -> CacheFuture<'_, ()>;
+
+    /// Drops the responses of the keys starting with the prefix.
+    fn remove_prefix<'a>(&'a self, prefix: &'a str) -> CacheFuture<'a, ()>;
+}
+
+/// A successful response with its entity tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedResponse {
+    pub content_type: Option<String>,
+    pub body: Bytes,
+    /// The opaque tag of the body, without the quotes, equal for the instances sharing a backend
+    /// since it's a fixed digest of the body.
+    pub etag: String,
+}
+
+impl CachedResponse {
+    pub fn new(content_type: Option<String>, body: Bytes) -> Self {
+        // the first half of the sha256 digest, hex-encoded
+        let digest = Sha256::digest(&body);
+        let etag = digest[..16]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        Self {
+            content_type,
+            body,
+            etag,
+        }
+    }
+
+    /// The cached response, or `304 Not Modified` if the client already has its tag.
+    pub fn respond(&self, if_none_match: Option<&IfNoneMatch>) -> HttpResponse {
+        let etag = EntityTag::new_strong(self.etag.clone());
+        let not_modified = match if_none_match {
+            Some(IfNoneMatch::Any) => true,
+            Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+            None => false,
+        };
+        if not_modified {
+            return HttpResponse::NotModified()
+                .insert_header((ETAG, etag))
+                .finish();
+        }
+
+        let mut response = HttpResponse::Ok();
+        response.insert_header((ETAG, etag));
+        if let Some(content_type) = &self.content_type {
+            response.insert_header((CONTENT_TYPE, content_type.as_str()));
+        }
+        response.body(self.body.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached() -> CachedResponse {
+        CachedResponse::new(
+            Some("application/json".to_string()),
+            Bytes::from_static(b"{}"),
+        )
+    }
+
+    #[test]
+    fn equal_bodies_have_equal_tags() {
+        let other = CachedResponse::new(None, Bytes::from_static(b"{}"));
+        assert_eq!(cached().etag, other.etag);
+        let changed = CachedResponse::new(None, Bytes::from_static(b"[]"));
+        assert_ne!(cached().etag, changed.etag);
+    }
+
+    #[test]
+    fn tags_are_stable_across_builds() {
+        assert_eq!(cached().etag, "44136fa355b3678a1146ad16f7e8649e");
+    }
+
+    #[test]
+    fn matching_tags_are_not_modified() {
+        let cached = cached();
+        let tag = EntityTag::new_weak(cached.etag.clone());
+        let test_cases = [
+            (None, 200),
+            (Some(IfNoneMatch::Any), 304),
+            (Some(IfNoneMatch::Items(vec![tag])), 304),
+            (
+                Some(IfNoneMatch::Items(vec![EntityTag::new_strong("a".into())])),
+                200,
+            ),
+        ];
+        for (if_none_match, status) in test_cases {
+            let actual = cached.respond(if_none_match.as_ref()).status();
+            assert_eq!(actual, status, "Unexpected status for {if_none_match:?}");
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use actix_web::http::header::{EntityTag, IfMatch, IfNoneMatch, ETAG};
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+/// The version of a stored row, bumped by every write of the row, the strong entity tag of
+/// the resource it's served as and the version compared by the `If-Match` of a write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowVersion(pub i64);
+
+impl RowVersion {
+    pub fn etag(self) -> EntityTag {
+        EntityTag::new_strong(self.0.to_string())
+    }
+
+    /// The version of a representation of the row which also shows state derived from other
+    /// rows, bumped without a write of the row.
+    pub fn with_derived(self, derived: i64) -> RepresentationVersion {
+        RepresentationVersion { row: self, derived }
+    }
+
+    /// Whether the strong tag names this version, alone or as the row part of the tag of a
+    /// [`RepresentationVersion`].
+    fn is_tagged_by(self, tag: &EntityTag) -> bool {
+        tag.strong_eq(&self.etag())
+            || (!tag.weak
+                && tag.tag().split_once('.').map(|(row, _)| row) == Some(&self.0.to_string()))
+    }
+
+    /// Whether the client already has this version, i.e. the response is `304 Not Modified`.
+    pub fn is_cached_by(self, if_none_match: Option<&IfNoneMatch>) -> bool {
+        is_cached_by(&self.etag(), if_none_match)
+    }
+
+    pub fn not_modified(self) -> HttpResponse {
+        not_modified(self.etag())
+    }
+
+    /// The resource tagged by this version, unless the client already has it.
+    pub fn respond<T: Serialize>(
+        self,
+        if_none_match: Option<&IfNoneMatch>,
+        resource: &T,
+    ) -> HttpResponse {
+        respond(self.etag(), if_none_match, resource)
+    }
+}
+
+/// The version of a representation which also shows state derived from other rows, e.g. the
+/// rarity of a nft token scored among the tokens of its kind. Its entity tag is
+/// `"{row}.{derived}"`, or the row's one until the derived state changes, so that the caches
+/// revalidate after a change of either while the `If-Match` of a write only compares the row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepresentationVersion {
+    row: RowVersion,
+    derived: i64,
+}
+
+impl RepresentationVersion {
+    pub fn etag(self) -> EntityTag {
+        match self.derived {
+            0 => self.row.etag(),
+            derived => EntityTag::new_strong(format!("{}.{derived}", self.row.0)),
+        }
+    }
+
+    /// The resource tagged by this version, unless the client already has it.
+    pub fn respond<T: Serialize>(
+        self,
+        if_none_match: Option<&IfNoneMatch>,
+        resource: &T,
+    ) -> HttpResponse {
+        respond(self.etag(), if_none_match, resource)
+    }
+}
+
+fn is_cached_by(etag: &EntityTag, if_none_match: Option<&IfNoneMatch>) -> bool {
+    match if_none_match {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        None => false,
+    }
+}
+
+fn not_modified(etag: EntityTag) -> HttpResponse {
+    HttpResponse::NotModified()
+        .insert_header((ETAG, etag))
+        .finish()
+}
+
+fn respond<T: Serialize>(
+    etag: EntityTag,
+    if_none_match: Option<&IfNoneMatch>,
+    resource: &T,
+) -> HttpResponse {
+    if is_cached_by(&etag, if_none_match) {
+        return not_modified(etag);
+    }
+
+    HttpResponse::Ok()
+        .insert_header((ETAG, etag))
+        .json(resource)
+}
+
+/// Whether the `If-Match` precondition of a write holds for the current version of the
+/// resource, `None` if it doesn't exist. A request without the header always proceeds.
+pub fn if_match_holds(if_match: Option<&IfMatch>, current: Option<RowVersion>) -> bool {
+    match (if_match, current) {
+        (None, _) => true,
+        (Some(IfMatch::Items(tags)), _) if tags.is_empty() => true,
+        (Some(_), None) => false,
+        (Some(IfMatch::Any), Some(_)) => true,
+        (Some(IfMatch::Items(tags)), Some(current)) => {
+            tags.iter().any(|tag| current.is_tagged_by(tag))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[EntityTag]) -> IfMatch {
+        IfMatch::Items(tags.to_vec())
+    }
+
+    #[test]
+    fn if_match_requires_the_current_strong_tag() {
+        let current = RowVersion(2);
+        let test_cases = [
+            (None, Some(current), true),
+            (Some(tags(&[])), None, true),
+            (Some(IfMatch::Any), Some(current), true),
+            (Some(IfMatch::Any), None, false),
+            (
+                Some(tags(&[RowVersion(1).etag(), current.etag()])),
+                Some(current),
+                true,
+            ),
+            (Some(tags(&[RowVersion(1).etag()])), Some(current), false),
+            (
+                Some(tags(&[EntityTag::new_weak("2".into())])),
+                Some(current),
+                false,
+            ),
+            (Some(tags(&[current.etag()])), None, false),
+        ];
+        for (if_match, current, expected) in test_cases {
+            let actual = if_match_holds(if_match.as_ref(), current);
+            assert_eq!(
+                actual, expected,
+                "Unexpected result of {if_match:?} for {current:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn if_match_compares_the_row_part_of_representation_tags() {
+        let current = RowVersion(2);
+        let test_cases = [
+            (current.with_derived(3).etag(), true),
+            (current.with_derived(0).etag(), true),
+            (RowVersion(1).with_derived(2).etag(), false),
+            (EntityTag::new_strong("20.1".into()), false),
+            (EntityTag::new_weak("2.3".into()), false),
+        ];
+        for (tag, expected) in test_cases {
+            let actual = if_match_holds(Some(&tags(std::slice::from_ref(&tag))), Some(current));
+            assert_eq!(actual, expected, "Unexpected result of {tag:?}");
+        }
+    }
+
+    #[test]
+    fn representation_tags_change_with_the_derived_state() {
+        let row = RowVersion(2);
+        assert_eq!(row.with_derived(0).etag(), row.etag());
+        assert_eq!(
+            row.with_derived(1).etag(),
+            EntityTag::new_strong("2.1".into())
+        );
+    }
+
+    #[test]
+    fn if_none_match_accepts_weak_tags() {
+        let version = RowVersion(3);
+        let weak = IfNoneMatch::Items(vec![EntityTag::new_weak("3".into())]);
+        assert!(version.is_cached_by(Some(&weak)));
+        assert!(version.is_cached_by(Some(&IfNoneMatch::Any)));
+        assert!(!version.is_cached_by(Some(&IfNoneMatch::Items(vec![]))));
+        assert!(!version.is_cached_by(None));
+    }
+}
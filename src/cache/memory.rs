@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::future::ready;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::cache::{CacheBackend, CacheFuture, CachedResponse};
+
+/// The cache of a single process, the responses are lost on restart.
+pub struct MemoryBackend {
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (Instant, CachedResponse)>>,
+}
+
+impl MemoryBackend {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl CacheBackend for MemoryBackend {
+    fn get<'a>(&'a self, key: &'a str) -> CacheFuture<'a, Option<CachedResponse>> {
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let response = entries
+            .get(key)
+            .filter(|(expires_at, _)| *expires_at > now)
+            .map(|(_, response)| response.clone());
+
+        Box::pin(ready(Ok(response)))
+    }
+
+    fn set(&self, key: String, response: CachedResponse, ttl: Duration) -> CacheFuture<'_, ()> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= self.max_entries {
+            entries.retain(|_, (expires_at, _)| *expires_at > now);
+        }
+        if entries.len() < self.max_entries || entries.contains_key(&key) {
+            entries.insert(key, (now + ttl, response));
+        }
+
+        Box::pin(ready(Ok(())))
+    }
+
+    fn remove_prefix<'a>(&'a self, prefix: &'a str) -> CacheFuture<'a, ()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|key, _| !key.starts_with(prefix));
+
+        Box::pin(ready(Ok(())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::web::Bytes;
+
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn response(body: &'static str) -> CachedResponse {
+        CachedResponse::new(None, Bytes::from_static(body.as_bytes()))
+    }
+
+    async fn get(backend: &MemoryBackend, key: &str) -> Option<CachedResponse> {
+        backend.get(key).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn responses_expire() {
+        let backend = MemoryBackend::new(10);
+        backend.set("a".into(), response("a"), TTL).await.unwrap();
+        backend
+            .set("b".into(), response("b"), Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(get(&backend, "a").await, Some(response("a")));
+        assert_eq!(get(&backend, "b").await, None);
+    }
+
+    #[tokio::test]
+    async fn responses_are_removed_by_prefix() {
+        let backend = MemoryBackend::new(10);
+        for key in [
+            "mainnet/paid?a",
+            "mainnet/paid?b",
+            "mainnet/contracts?",
+            "testnet/paid?a",
+        ] {
+            backend.set(key.into(), response(key), TTL).await.unwrap();
+        }
+
+        backend.remove_prefix("mainnet/paid?").await.unwrap();
+
+        assert_eq!(get(&backend, "mainnet/paid?a").await, None);
+        assert_eq!(get(&backend, "mainnet/paid?b").await, None);
+        assert!(get(&backend, "mainnet/contracts?").await.is_some());
+        assert!(get(&backend, "testnet/paid?a").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn full_cache_only_replaces_its_responses() {
+        let backend = MemoryBackend::new(1);
+        backend.set("a".into(), response("a"), TTL).await.unwrap();
+        backend.set("b".into(), response("b"), TTL).await.unwrap();
+        backend.set("a".into(), response("c"), TTL).await.unwrap();
+
+        assert_eq!(get(&backend, "a").await, Some(response("c")));
+        assert_eq!(get(&backend, "b").await, None);
+    }
+}
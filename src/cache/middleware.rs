@@ -0,0 +1,173 @@
+use actix_web::body::{to_bytes, BodySize, BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{EntityTag, Header, IfNoneMatch, CONTENT_TYPE, ETAG};
+use actix_web::http::StatusCode;
+use actix_web::{error, web};
+use actix_web_lab::middleware::Next;
+
+use crate::api_version::route_name;
+use crate::cache::{CachedResponse, ResponseCache};
+use crate::network::Network;
+
+/// Serves the cached response of a read route, or caches the successful response of the
+/// handler, and answers a request whose `If-None-Match` has the response's `ETag` with
+/// `304 Not Modified`.
+pub async fn cache(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let cache = match req.app_data::<web::Data<ResponseCache>>() {
+        Some(cache) if cache.enabled() => cache.clone(),
+        _ => return Ok(next.call(req).await?.map_into_boxed_body()),
+    };
+    let route = req
+        .match_pattern()
+        .map(|pattern| route_name(&pattern).to_string())
+        .unwrap_or_default();
+    // the handler rejects the unknown networks and the malformed queries
+    let (network, key) = match (req.extract::<Network>().await, resource(&req)) {
+        (Ok(network), Some(resource)) => {
+            let key = ResponseCache::key(&network, &route, &resource);
+            (network, key)
+        }
+        _ => return Ok(next.call(req).await?.map_into_boxed_body()),
+    };
+    let if_none_match = IfNoneMatch::parse(req.request()).ok();
+
+    if let Some(cached) = cache.get(&key).await {
+        return Ok(req.into_response(cached.respond(if_none_match.as_ref())));
+    }
+
+    // taken before the handler reads, a write invalidating the route meanwhile leaves the
+    // response uncached
+    let generation = cache.generation(&network, &route);
+    let response = next.call(req).await?;
+    // the exported files are streamed rather than buffered
+    if response.status() != StatusCode::OK || response.response().body().size() == BodySize::Stream
+    {
+        return Ok(response.map_into_boxed_body());
+    }
+    let (req, response) = response.into_parts();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    // the handlers of the versioned resources tag them by their row version
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok()?.parse::<EntityTag>().ok());
+    let body = to_bytes(response.into_body()).await.map_err(|e| {
+        let e: Box<dyn std::error::Error> = e.into();
+        error::ErrorInternalServerError(e.to_string())
+    })?;
+    let mut cached = CachedResponse::new(content_type, body);
+    if let Some(etag) = etag {
+        cached.etag = etag.tag().to_string();
+    }
+    cache
+        .set_since(generation, &network, &route, key, cached.clone())
+        .await;
+
+    Ok(ServiceResponse::new(
+        req,
+        cached.respond(if_none_match.as_ref()),
+    ))
+}
+
+/// The path parameters, but the network, and the query parameters of the request, sorted
+/// so that the order of the query doesn't matter.
+fn resource(req: &ServiceRequest) -> Option<String> {
+    let query = web::Query::<Vec<(String, String)>>::from_query(req.query_string()).ok()?;
+    let mut params: Vec<_> = req
+        .match_info()
+        .iter()
+        .filter(|(name, _)| *name != "network")
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .chain(query.into_inner())
+        .collect();
+    params.sort();
+
+    serde_json::to_string(&params).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use actix_web::{test, App, HttpRequest, HttpResponse};
+    use actix_web_lab::middleware::from_fn;
+    use futures_util::future::join;
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::config::{CacheSettings, NetworkSettings};
+
+    /// The database of the handler, whose slow reads wait for their release.
+    #[derive(Default)]
+    struct Database {
+        value: AtomicU64,
+        read: Notify,
+        released: Notify,
+    }
+
+    async fn read(req: HttpRequest, db: web::Data<Database>) -> HttpResponse {
+        let value = db.value.load(Ordering::SeqCst);
+        if req.headers().contains_key("x-slow") {
+            db.read.notify_one();
+            db.released.notified().await;
+        }
+        HttpResponse::Ok().body(value.to_string())
+    }
+
+    fn network_settings() -> NetworkSettings {
+        NetworkSettings {
+            default: "mainnet".to_string(),
+            header: "x-network".to_string(),
+            ids: vec!["mainnet".to_string()],
+            nft_contracts: Default::default(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn reads_racing_an_invalidation_are_not_cached() {
+        let cache = web::Data::new(ResponseCache::new(CacheSettings {
+            enabled: true,
+            max_entries: 10,
+            default_ttl_seconds: 60,
+            routes: Default::default(),
+        }));
+        let db = web::Data::new(Database::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(cache.clone())
+                .app_data(db.clone())
+                .app_data(web::Data::new(network_settings()))
+                .route("/paid", web::get().to(read).wrap(from_fn(super::cache))),
+        )
+        .await;
+        let network = Network::extract(
+            &test::TestRequest::default()
+                .app_data(web::Data::new(network_settings()))
+                .to_http_request(),
+        )
+        .unwrap();
+
+        let slow_read = test::TestRequest::get()
+            .uri("/paid")
+            .insert_header(("x-slow", "1"))
+            .to_request();
+        let write = async {
+            db.read.notified().await;
+            db.value.store(1, Ordering::SeqCst);
+            cache.invalidate(&network, Some("paid")).await;
+            db.released.notify_one();
+        };
+        let (stale, ()) = join(test::call_and_read_body(&app, slow_read), write).await;
+        assert_eq!(stale, "0");
+
+        let req = test::TestRequest::get().uri("/paid").to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "1");
+    }
+}
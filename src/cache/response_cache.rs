@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::cache::{CacheBackend, CachedResponse, MemoryBackend};
+use crate::config::CacheSettings;
+use crate::network::Network;
+
+/// The cached responses of the hot read routes, dropped by the writes of their data.
+pub struct ResponseCache {
+    settings: CacheSettings,
+    backend: Box<dyn CacheBackend>,
+    /// The number of invalidations of each prefix of the process, see [`Self::generation`].
+    generations: Mutex<HashMap<String, u64>>,
+}
+
+impl ResponseCache {
+    /// The cache kept in the process memory.
+    pub fn new(settings: CacheSettings) -> Self {
+        let backend = MemoryBackend::new(settings.max_entries);
+        Self::with_backend(settings, backend)
+    }
+
+    pub fn with_backend(settings: CacheSettings, backend: impl CacheBackend + 'static) -> Self {
+        Self {
+            settings,
+            backend: Box::new(backend),
+            generations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    pub fn ttl(&self, route: &str) -> Duration {
+        self.settings.ttl(route)
+    }
+
+    /// The key of a response of the route, e.g. `mainnet/paid?[["days","7"]]`, where the
+    /// resource identifies the response within its route.
+    pub fn key(network: &Network, route: &str, resource: &str) -> String {
+        format!("{network}/{route}?{resource}")
+    }
+
+    /// The cached response, a failing backend is treated as a miss.
+    pub async fn get(&self, key: &str) -> Option<CachedResponse> {
+        match self.backend.get(key).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!(error = ?e, "Failed to get the cached response.");
+                None
+            }
+        }
+    }
+
+    pub async fn set(&self, key: String, response: CachedResponse, ttl: Duration) {
+        if let Err(e) = self.backend.set(key, response, ttl).await {
+            tracing::warn!(error = ?e, "Failed to cache the response.");
+        }
+    }
+
+    /// The generation of the route of the network, bumped by the invalidations of the route
+    /// and of the whole network. It's taken before the response is read, so that a response
+    /// read before a write isn't cached after the write's invalidation, see [`Self::set_since`].
+    pub fn generation(&self, network: &Network, route: &str) -> u64 {
+        let generations = self.generations.lock().unwrap_or_else(|e| e.into_inner());
+        [Self::key(network, route, ""), format!("{network}/")]
+            .iter()
+            .filter_map(|prefix| generations.get(prefix))
+            .sum()
+    }
+
+    /// Caches the response of the route read since the generation, unless the route was
+    /// invalidated meanwhile. An invalidation racing the store is caught by checking again
+    /// after it, the response is then dropped rather than served until it expires.
+    pub async fn set_since(
+        &self,
+        generation: u64,
+        network: &Network,
+        route: &str,
+        key: String,
+        response: CachedResponse,
+    ) {
+        if self.generation(network, route) != generation {
+            return;
+        }
+        self.set(key.clone(), response, self.ttl(route)).await;
+        if self.generation(network, route) != generation {
+            // the keys end with their resource, so no other key starts with this one
+            if let Err(e) = self.backend.remove_prefix(&key).await {
+                tracing::error!(error = ?e, "Failed to drop the stale cached response.");
+            }
+        }
+    }
+
+    /// Drops the cached responses of the route of the network, or of all its routes, e.g.
+    /// when the active contracts change.
+    pub async fn invalidate(&self, network: &Network, route: Option<&str>) {
+        if !self.enabled() {
+            return;
+        }
+
+        let prefix = match route {
+            Some(route) => Self::key(network, route, ""),
+            None => format!("{network}/"),
+        };
+        // bumped first, so that the responses read before are either dropped below or not
+        // stored by `set_since`
+        *self
+            .generations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(prefix.clone())
+            .or_default() += 1;
+        if let Err(e) = self.backend.remove_prefix(&prefix).await {
+            tracing::error!(error = ?e, "Failed to invalidate the cached responses.");
+        }
+    }
+}
@@ -1,7 +1,15 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use sqlx::postgres::PgConnectOptions;
 
+use crate::domain::LimitBounds;
+
 /// Configuration for the server.
 #[derive(Deserialize, Clone)]
 pub struct ApplicationSettings {
@@ -18,6 +26,27 @@ pub struct Settings {
     pub database: DatabaseSettings,
     /// The application settings.
     pub application: ApplicationSettings,
+    /// The rate limiter settings.
+    pub rate_limit: RateLimitSettings,
+    /// The response cache settings.
+    pub cache: CacheSettings,
+    /// The page size settings of the list endpoints.
+    pub pagination: PaginationSettings,
+    /// The networks served by this deployment.
+    pub networks: NetworkSettings,
+    /// The deprecation of the served API versions.
+    #[serde(default)]
+    pub api_versions: ApiVersionSettings,
+    /// The accepted token ids of the contracts.
+    pub token_ids: TokenIdSettings,
+    /// The NEP-177 metadata of the contracts.
+    pub contract_metadata: ContractMetadataSettings,
+    /// The resolution of the token media to http urls.
+    pub media: MediaSettings,
+    /// The previews of the tokens composed of their trait assets.
+    pub preview: PreviewSettings,
+    /// The streaming of the rows of the list endpoints.
+    pub streaming: StreamingSettings,
 }
 
 #[derive(Deserialize, Clone)]
@@ -49,6 +78,307 @@ impl DatabaseSettings {
     }
 }
 
+/// Configuration for the rate limiter of the public read endpoints.
+#[derive(Deserialize, Clone)]
+pub struct RateLimitSettings {
+    /// Whether the requests are throttled at all.
+    pub enabled: bool,
+    /// Addresses of the reverse proxies allowed to set the client ip headers.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Headers carrying the client ip, checked in order for trusted proxies only.
+    #[serde(default)]
+    pub client_ip_headers: Vec<String>,
+    /// The header carrying the client's API key.
+    pub api_key_header: String,
+    /// API keys which are throttled by their own buckets instead of the client ip's one.
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// The budget for the routes without their own entry in `routes`.
+    pub default_budget: Budget,
+    /// The budgets keyed by the route name, e.g. `nft_tokens`.
+    #[serde(default)]
+    pub routes: HashMap<String, Budget>,
+}
+
+impl RateLimitSettings {
+    pub fn budget(&self, route: &str) -> Budget {
+        self.routes
+            .get(route)
+            .copied()
+            .unwrap_or(self.default_budget)
+    }
+}
+
+/// Configuration for the response cache of the hot read endpoints.
+#[derive(Deserialize, Clone)]
+pub struct CacheSettings {
+    /// Whether the responses are cached at all.
+    pub enabled: bool,
+    /// Beyond this number of cached responses the expired ones are dropped, then the new ones
+    /// aren't cached until some expire.
+    pub max_entries: usize,
+    /// The time to live of the routes without their own entry in `routes`.
+    pub default_ttl_seconds: u64,
+    /// The times to live keyed by the route name, e.g. `paid`.
+    #[serde(default)]
+    pub routes: HashMap<String, u64>,
+}
+
+impl CacheSettings {
+    pub fn ttl(&self, route: &str) -> Duration {
+        let seconds = self
+            .routes
+            .get(route)
+            .copied()
+            .unwrap_or(self.default_ttl_seconds);
+
+        Duration::from_secs(seconds)
+    }
+}
+
+/// Token bucket parameters, both positive.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "BudgetParameters")]
+pub struct Budget {
+    /// The maximum number of requests in a burst.
+    pub capacity: u32,
+    /// The number of requests restored per second.
+    pub refill_per_second: f64,
+}
+
+/// The budget as written in the settings, before it's validated.
+#[derive(Deserialize)]
+struct BudgetParameters {
+    capacity: u32,
+    refill_per_second: f64,
+}
+
+impl TryFrom<BudgetParameters> for Budget {
+    type Error = String;
+
+    fn try_from(budget: BudgetParameters) -> Result<Self, Self::Error> {
+        if budget.capacity == 0 {
+            return Err("The `capacity` of a budget must be positive.".to_string());
+        }
+        if budget.refill_per_second.is_nan() || budget.refill_per_second <= 0.0 {
+            return Err(format!(
+                "The `refill_per_second` of a budget must be positive, got `{}`.",
+                budget.refill_per_second
+            ));
+        }
+
+        Ok(Self {
+            capacity: budget.capacity,
+            refill_per_second: budget.refill_per_second,
+        })
+    }
+}
+
+/// Configuration for the page sizes of the list endpoints.
+#[derive(Deserialize, Clone)]
+pub struct PaginationSettings {
+    /// What to do with a `limit` exceeding the maximum.
+    pub oversized_limit: OversizedLimit,
+    /// The limits for the resources without their own entry in `resources`.
+    pub default_limits: LimitSettings,
+    /// The limits keyed by the resource name, e.g. `nft_tokens`.
+    #[serde(default)]
+    pub resources: HashMap<String, LimitSettings>,
+}
+
+impl PaginationSettings {
+    pub fn bounds(&self, resource: &str) -> LimitBounds {
+        let limits = self
+            .resources
+            .get(resource)
+            .copied()
+            .unwrap_or(self.default_limits);
+
+        LimitBounds {
+            default: limits.default,
+            max: limits.max,
+            oversized: self.oversized_limit,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct LimitSettings {
+    /// The page size used when `limit` is omitted.
+    pub default: i64,
+    /// The largest accepted page size.
+    pub max: i64,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizedLimit {
+    /// Respond with a validation error.
+    Reject,
+    /// Silently use the maximum instead.
+    Clamp,
+}
+
+/// Configuration for the token ids, which NEP-171 leaves to the contracts.
+#[derive(Deserialize, Clone, Debug)]
+pub struct TokenIdSettings {
+    /// The format for the contracts without their own entry in `contracts`.
+    pub default: TokenIdFormat,
+    /// The formats keyed by the contract id, e.g. `nft.battlemon.near`.
+    #[serde(default)]
+    pub contracts: HashMap<String, TokenIdFormat>,
+}
+
+impl TokenIdSettings {
+    /// The format of the contract's token ids, the default one if the contract isn't known.
+    pub fn format(&self, contract_id: Option<&str>) -> TokenIdFormat {
+        contract_id
+            .and_then(|id| self.contracts.get(id))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TokenIdFormat {
+    /// The accepted characters.
+    pub charset: TokenIdCharset,
+    /// The longest accepted token id in bytes.
+    pub max_length: usize,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenIdCharset {
+    /// Any string without control characters, e.g. `weapon:123`.
+    Nep171,
+    /// Only the ASCII digits, e.g. the ids of the lemons contract.
+    Numeric,
+}
+
+/// Configuration for the NEP-177 metadata of the contracts, which isn't part of the contracts
+/// config.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ContractMetadataSettings {
+    /// The metadata of the contracts without their own entry in `contracts`.
+    pub default: ContractMetadata,
+    /// The metadata keyed by the contract id, e.g. `nft.battlemon.near`.
+    #[serde(default)]
+    pub contracts: HashMap<String, ContractMetadata>,
+}
+
+impl ContractMetadataSettings {
+    pub fn metadata(&self, contract_id: &str) -> &ContractMetadata {
+        self.contracts.get(contract_id).unwrap_or(&self.default)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ContractMetadata {
+    /// The human readable name, e.g. `Battlemon`.
+    pub name: String,
+    /// The ticker, e.g. `LEMON`.
+    pub symbol: String,
+    /// A data URL of a small image of the contract.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// The base of the relative `media` and `reference` URLs of the tokens.
+    #[serde(default)]
+    pub base_uri: Option<String>,
+}
+
+/// Configuration for the gateways of the decentralized storage media.
+#[derive(Deserialize, Clone, Debug)]
+pub struct MediaSettings {
+    /// The IPFS gateways serving `{gateway}{cid}`, e.g. `https://ipfs.io/ipfs/`, the media
+    /// hosted by the others are rewritten to the first one.
+    pub ipfs_gateways: Vec<String>,
+    /// The Arweave gateway serving `{gateway}{id}`, e.g. `https://arweave.net/`.
+    pub arweave_gateway: String,
+}
+
+/// Configuration for the previews of the tokens, which layer an asset per trait value of their
+/// model.
+#[derive(Deserialize, Clone, Debug)]
+pub struct PreviewSettings {
+    /// The directory of the assets, e.g. `{asset_dir}/lemon/cap/cap1.svg`, the SVG asset of a
+    /// value is preferred to its PNG one.
+    pub asset_dir: PathBuf,
+    /// The width of the previews in pixels.
+    pub width: u32,
+    /// The height of the previews in pixels.
+    pub height: u32,
+    /// The traits keyed by the kind in the order of their layers, the first one at the bottom,
+    /// the unlisted traits are layered above them by name.
+    #[serde(default)]
+    pub layers: HashMap<String, Vec<String>>,
+}
+
+/// Configuration for the rows of the list endpoints, which are streamed from the database
+/// rather than fetched at once.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct StreamingSettings {
+    /// The rows fetched ahead of a slow client, the query waits while they're unsent.
+    pub buffer_rows: usize,
+    /// The most rows of an exported file, e.g. `sales.csv`, the rest is left out.
+    pub max_rows: i64,
+}
+
+/// Configuration for the networks, e.g. `testnet` and `mainnet`, served by one deployment.
+#[derive(Deserialize, Clone)]
+pub struct NetworkSettings {
+    /// The network used when a request doesn't select one.
+    pub default: String,
+    /// The header selecting the network when the path doesn't.
+    pub header: String,
+    /// The ids of the served networks.
+    pub ids: Vec<String>,
+    /// The nft contracts of the networks keyed by their id, which stay valid besides the ones
+    /// named by any version of the network's contracts config.
+    #[serde(default)]
+    pub nft_contracts: HashMap<String, Vec<String>>,
+}
+
+impl NetworkSettings {
+    pub fn is_known(&self, network: &str) -> bool {
+        self.ids.iter().any(|id| id == network)
+    }
+
+    pub fn nft_contracts(&self, network: &str) -> &[String] {
+        self.nft_contracts
+            .get(network)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Configuration for the API versions served side by side, e.g. `v1`.
+#[derive(Deserialize, Clone, Default)]
+pub struct ApiVersionSettings {
+    /// The deprecated versions keyed by their path prefix, e.g. `v1`, the unversioned root
+    /// aliases are keyed by `legacy`.
+    #[serde(default)]
+    pub deprecated: HashMap<String, DeprecationSettings>,
+}
+
+impl ApiVersionSettings {
+    pub fn deprecation(&self, version: &str) -> Option<&DeprecationSettings> {
+        self.deprecated.get(version)
+    }
+}
+
+/// The headers announcing the deprecation of an API version.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct DeprecationSettings {
+    /// The moment the version was deprecated, `Deprecation: true` is sent when omitted.
+    pub since: Option<DateTime<Utc>>,
+    /// The moment the version stops being served.
+    pub sunset: Option<DateTime<Utc>>,
+    /// The documentation of the deprecation and the migration to the next version.
+    pub link: Option<String>,
+}
+
 pub fn get_config() -> anyhow::Result<Settings> {
     let config_path = std::env::current_dir()
         .context("Failed to determine the current directory")?
@@ -103,3 +433,44 @@ impl TryFrom<String> for Environment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, File, FileFormat};
+
+    use super::*;
+
+    fn budget(yaml: &str) -> Result<Budget, config::ConfigError> {
+        Config::builder()
+            .add_source(File::from_str(yaml, FileFormat::Yaml))
+            .build()?
+            .try_deserialize()
+    }
+
+    #[test]
+    fn positive_budgets_are_loaded() {
+        let actual = budget("capacity: 10\nrefill_per_second: 0.5").unwrap();
+        let expected = Budget {
+            capacity: 10,
+            refill_per_second: 0.5,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn budgets_without_requests_are_rejected() {
+        let test_cases = [
+            ("capacity: 0\nrefill_per_second: 1.0", "capacity"),
+            ("capacity: 10\nrefill_per_second: 0.0", "refill_per_second"),
+            ("capacity: 10\nrefill_per_second: -1.0", "refill_per_second"),
+            ("capacity: 10\nrefill_per_second: NaN", "refill_per_second"),
+        ];
+        for (yaml, field) in test_cases {
+            let actual = budget(yaml).expect_err(yaml).to_string();
+            assert!(
+                actual.contains(field),
+                "Unexpected error {actual} of {yaml}"
+            );
+        }
+    }
+}
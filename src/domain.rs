@@ -1,29 +1,47 @@
 use self::private::New;
+pub use account_id::*;
 pub use ask::*;
+pub use audit_log::*;
+pub use bid::*;
+pub use contract::*;
+pub use export::*;
+pub use field_error::*;
 pub use limit::*;
+pub use media::*;
+pub use nft_kind::*;
 pub use nft_token::*;
 pub use nft_token_days::*;
 pub use nft_token_user_id::*;
 pub use offset::*;
 pub use paid::*;
 pub use paid_days::*;
+pub use price::*;
 pub use sale::*;
 pub use sale_days::*;
+pub use search::*;
 pub use token_id::*;
-pub use bid::*;
 
+mod account_id;
 mod ask;
+mod audit_log;
+mod bid;
+mod contract;
+mod export;
+mod field_error;
 mod limit;
+mod media;
+mod nft_kind;
 mod nft_token;
 mod nft_token_days;
 mod nft_token_user_id;
 mod offset;
 mod paid;
 mod paid_days;
+mod price;
 mod sale;
 mod sale_days;
+mod search;
 mod token_id;
-mod bid;
 
 pub(self) mod private {
     pub enum Local {}
@@ -43,11 +61,17 @@ pub trait ParseToPositiveInt
 where
     Self: Sized + New + Default,
 {
+    /// The name of the parsed query.
+    const FIELD: &'static str;
+    /// The code of a negative value.
+    const CODE: &'static str;
     const ERROR: &'static str = "The parsed value must be positive.";
 
-    fn parse(value: Option<i64>) -> Result<Self, &'static str> {
+    fn parse(value: Option<i64>) -> Result<Self, FieldError> {
         match value {
-            Some(v) if v.is_negative() => Err(Self::ERROR),
+            Some(v) if v.is_negative() => {
+                Err(FieldError::new(Self::FIELD, Self::CODE, Self::ERROR))
+            }
             None => Ok(Self::default()),
             Some(v) => Ok(Self::new(v)),
         }
@@ -55,7 +79,7 @@ where
 }
 
 pub trait Parse<T>: Sized {
-    fn parse(value: Option<T>) -> Result<Self, String>;
+    fn parse(value: Option<T>) -> Result<Self, FieldError>;
 }
 
 pub trait IntoInner {
@@ -95,6 +119,24 @@ pub mod helpers {
     use rand::prelude::StdRng;
     use rand::SeedableRng;
 
+    use crate::config::{TokenIdCharset, TokenIdFormat, TokenIdSettings};
+
+    pub const NFT_CONTRACT_ID: &str = "nft.battlemon.near";
+
+    pub fn nft_contract_ids() -> Vec<String> {
+        vec![NFT_CONTRACT_ID.to_string()]
+    }
+
+    pub fn token_id_settings() -> TokenIdSettings {
+        TokenIdSettings {
+            default: TokenIdFormat {
+                charset: TokenIdCharset::Nep171,
+                max_length: 128,
+            },
+            contracts: Default::default(),
+        }
+    }
+
     #[derive(Debug, Clone)]
     pub struct PositiveIntegersFixture(pub Option<i64>);
 
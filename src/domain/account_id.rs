@@ -0,0 +1,76 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::domain::FieldError;
+
+/// The rules of the NEAR account ids, e.g. `alice.near` or `dev-1603749005325-6432576`.
+pub(crate) static ACCOUNT_ID_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(([a-z\d]+[\-_])*[a-z\d]+\.)*([a-z\d]+[\-_])*[a-z\d]+$"#)
+        .expect("Couldn't compile regexp expression")
+});
+
+/// A NEAR account id of a write payload, e.g. the buyer of a sale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountId(String);
+
+impl AccountId {
+    pub fn parse(field: &str, id: &str) -> Result<Self, FieldError> {
+        match id {
+            id if id.len() < 2 => Err(FieldError::new(
+                field,
+                "validation.account_id_too_short",
+                format!("Account id `{id}` is too short (min 2)."),
+            )),
+            id if id.len() > 64 => Err(FieldError::new(
+                field,
+                "validation.account_id_too_long",
+                format!("Account id `{id}` is too long (max 64)."),
+            )),
+            id if !ACCOUNT_ID_RE.is_match(id) => Err(FieldError::new(
+                field,
+                "validation.account_id_invalid_chars",
+                format!("Account id `{id}` contains wrong chars."),
+            )),
+            id => Ok(Self(id.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_account_ids_are_parsed() {
+        for id in [
+            "alice.near",
+            "fomo.alice.testnet",
+            "dev-1603749005325-6432576",
+        ] {
+            let actual = AccountId::parse("account_id", id);
+            assert_eq!(actual, Ok(AccountId(id.to_string())));
+        }
+    }
+
+    #[test]
+    fn invalid_account_ids_are_rejected() {
+        let test_cases = [
+            ("a", "validation.account_id_too_short"),
+            (&"a".repeat(65), "validation.account_id_too_long"),
+            ("Alice.near", "validation.account_id_invalid_chars"),
+            (" alice.near", "validation.account_id_invalid_chars"),
+        ];
+        for (id, code) in test_cases {
+            let actual = AccountId::parse("account_id", id).map_err(|e| (e.field, e.code));
+            assert_eq!(
+                actual,
+                Err(("account_id".to_string(), code)),
+                "`{id}` must be rejected"
+            );
+        }
+    }
+}
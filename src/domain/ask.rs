@@ -1,9 +1,15 @@
-use crate::domain::AsInner;
+use battlemon_models::market::ask::AskForRest;
+
+use crate::domain::{
+    AccountId, AsInner, ContractId, ContractRules, FieldError, Price, TokenId, ValidationErrors,
+    Validator,
+};
 
 #[derive(Debug, Clone)]
 pub struct AskFilter {
     pub limit: crate::domain::Limit,
     pub offset: crate::domain::Offset,
+    pub contract_id: ContractId,
     pub token_id: crate::domain::TokenId,
 }
 
@@ -16,7 +22,65 @@ impl AskFilter {
         self.offset.get()
     }
 
+    pub fn contract_id(&self) -> Option<&str> {
+        self.contract_id.as_inner()
+    }
+
     pub fn token_id(&self) -> Option<&str> {
         self.token_id.as_inner()
     }
 }
+
+/// An ask of a write payload, which passed the validation.
+#[derive(Debug, Clone)]
+pub struct NewAsk {
+    contract_id: String,
+    ask: AskForRest,
+}
+
+impl NewAsk {
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    pub fn into_parts(self) -> (String, AskForRest) {
+        (self.contract_id, self.ask)
+    }
+}
+
+impl TryFrom<(String, AskForRest, &ContractRules<'_>)> for NewAsk {
+    type Error = ValidationErrors;
+
+    fn try_from(
+        (contract_id, mut ask, rules): (String, AskForRest, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse_required(&contract_id, rules));
+        let format = rules.token_id_format(contract_id.as_deref());
+        if ask.id.trim().is_empty() {
+            validator.push(FieldError::new(
+                "id",
+                "validation.id_empty",
+                "The ask id is empty.",
+            ));
+        }
+        let token_id = validator.check(TokenId::parse_required(&ask.token_id, format));
+        validator.check(AccountId::parse("account_id", &ask.account_id));
+        if ask.approval_id.is_negative() {
+            validator.push(FieldError::new(
+                "approval_id",
+                "validation.approval_id_negative",
+                "The approval id must be positive.",
+            ));
+        }
+        validator.check(Price::parse("price", ask.price));
+
+        validator.finish(|| {
+            ask.token_id = token_id?;
+            Some(Self {
+                contract_id: contract_id?,
+                ask,
+            })
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::{Limit, Offset};
+
+#[derive(Debug, Clone)]
+pub struct AuditLogFilter {
+    pub limit: Limit,
+    pub offset: Offset,
+    pub user_id: Option<i64>,
+    pub network: Option<String>,
+    pub route: Option<String>,
+    pub method: Option<String>,
+    pub entity_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditLogFilter {
+    pub fn limit(&self) -> i64 {
+        self.limit.get()
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.get()
+    }
+
+    pub fn user_id(&self) -> Option<i64> {
+        self.user_id
+    }
+
+    pub fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    pub fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    pub fn entity_id(&self) -> Option<&str> {
+        self.entity_id.as_deref()
+    }
+
+    pub fn from(&self) -> Option<DateTime<Utc>> {
+        self.from
+    }
+
+    pub fn to(&self) -> Option<DateTime<Utc>> {
+        self.to
+    }
+}
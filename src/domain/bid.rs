@@ -1,9 +1,16 @@
-use crate::domain::AsInner;
+use battlemon_models::market::bid::BidForRest;
+use chrono::Utc;
+
+use crate::domain::{
+    AccountId, AsInner, ContractId, ContractRules, FieldError, Price, TokenId, ValidationErrors,
+    Validator,
+};
 
 #[derive(Debug, Clone)]
 pub struct BidFilter {
     pub limit: crate::domain::Limit,
     pub offset: crate::domain::Offset,
+    pub contract_id: ContractId,
     pub token_id: crate::domain::TokenId,
 }
 
@@ -16,7 +23,113 @@ impl BidFilter {
         self.offset.get()
     }
 
+    pub fn contract_id(&self) -> Option<&str> {
+        self.contract_id.as_inner()
+    }
+
     pub fn token_id(&self) -> Option<&str> {
         self.token_id.as_inner()
     }
 }
+
+/// A bid of a write payload, which passed the validation.
+#[derive(Debug, Clone)]
+pub struct NewBid {
+    contract_id: String,
+    bid: BidForRest,
+}
+
+impl NewBid {
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    pub fn into_parts(self) -> (String, BidForRest) {
+        (self.contract_id, self.bid)
+    }
+}
+
+impl TryFrom<(String, BidForRest, &ContractRules<'_>)> for NewBid {
+    type Error = ValidationErrors;
+
+    fn try_from(
+        (contract_id, mut bid, rules): (String, BidForRest, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse_required(&contract_id, rules));
+        let format = rules.token_id_format(contract_id.as_deref());
+        if bid.id.trim().is_empty() {
+            validator.push(FieldError::new(
+                "id",
+                "validation.id_empty",
+                "The bid id is empty.",
+            ));
+        }
+        let token_id = validator.check(TokenId::parse_required(&bid.token_id, format));
+        validator.check(AccountId::parse("account_id", &bid.account_id));
+        match bid.expire_at {
+            Some(expire_at) if expire_at <= Utc::now() => validator.push(FieldError::new(
+                "expire_at",
+                "validation.expire_at_not_future",
+                format!("The bid expired at `{expire_at}` already."),
+            )),
+            _ => {}
+        }
+        validator.check(Price::parse("price", bid.price));
+
+        validator.finish(|| {
+            bid.token_id = token_id?;
+            Some(Self {
+                contract_id: contract_id?,
+                bid,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    use crate::domain::helpers::{nft_contract_ids, token_id_settings, NFT_CONTRACT_ID};
+
+    use super::*;
+
+    fn parse(bid: BidForRest) -> Result<NewBid, ValidationErrors> {
+        let token_ids = token_id_settings();
+        let rules = ContractRules::new(nft_contract_ids(), &token_ids);
+        NewBid::try_from((NFT_CONTRACT_ID.to_string(), bid, &rules))
+    }
+
+    fn bid() -> BidForRest {
+        BidForRest {
+            id: "1".to_string(),
+            token_id: "1".to_string(),
+            account_id: "alice.near".to_string(),
+            expire_at: Some(Utc::now() + Duration::days(1)),
+            create_at: Utc::now(),
+            price: dec!(1.5),
+        }
+    }
+
+    #[test]
+    fn valid_bid_is_accepted() {
+        assert!(parse(bid()).is_ok());
+        let bid = BidForRest {
+            expire_at: None,
+            ..bid()
+        };
+        assert!(parse(bid).is_ok());
+    }
+
+    #[test]
+    fn expired_bid_is_rejected() {
+        let bid = BidForRest {
+            expire_at: Some(Utc::now() - Duration::seconds(1)),
+            ..bid()
+        };
+        let actual = parse(bid).map_err(|e| e.first().code);
+        assert_eq!(actual.err(), Some("validation.expire_at_not_future"));
+    }
+}
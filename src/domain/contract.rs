@@ -0,0 +1,231 @@
+use chrono::{DateTime, Utc};
+
+use crate::config::{TokenIdFormat, TokenIdSettings};
+use crate::domain::FieldError;
+
+/// Which version of the contracts config a request is asking for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContractsSelector {
+    /// The most recently stored version.
+    Active,
+    Version(i64),
+    /// The version that was active at the given moment.
+    At(DateTime<Utc>),
+}
+
+impl ContractsSelector {
+    pub fn parse(version: Option<i64>, at: Option<DateTime<Utc>>) -> Result<Self, FieldError> {
+        match (version, at) {
+            (Some(_), Some(_)) => Err(FieldError::new(
+                "at",
+                "validation.version_conflicts_with_at",
+                "The `version` and `at` queries can't be used together.",
+            )),
+            (Some(v), None) if v < 1 => Err(FieldError::new(
+                "version",
+                "validation.version_not_positive",
+                "The version value must be positive.",
+            )),
+            (Some(v), None) => Ok(Self::Version(v)),
+            (None, Some(at)) => Ok(Self::At(at)),
+            (None, None) => Ok(Self::Active),
+        }
+    }
+}
+
+/// The nft contracts a request may name, i.e. the ones of any version of the contracts config
+/// and the ones of the network settings, and the format of their token ids.
+#[derive(Debug, Clone)]
+pub struct ContractRules<'a> {
+    contract_ids: Vec<String>,
+    token_ids: &'a TokenIdSettings,
+}
+
+impl<'a> ContractRules<'a> {
+    pub fn new(
+        contract_ids: impl IntoIterator<Item = String>,
+        token_ids: &'a TokenIdSettings,
+    ) -> Self {
+        let mut contract_ids: Vec<_> = contract_ids.into_iter().collect();
+        contract_ids.sort();
+        contract_ids.dedup();
+
+        Self {
+            contract_ids,
+            token_ids,
+        }
+    }
+
+    /// The format of the token ids of the contract, the default one for an omitted contract.
+    pub fn token_id_format(&self, contract_id: Option<&str>) -> TokenIdFormat {
+        self.token_ids.format(contract_id)
+    }
+}
+
+/// The id of a nft contract allowed by the [`ContractRules`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractId(Option<String>);
+
+crate::domain::impl_into_inner!(ContractId);
+crate::domain::impl_as_inner!(ContractId);
+
+impl ContractId {
+    pub fn parse(contract_id: Option<&str>, rules: &ContractRules) -> Result<Self, FieldError> {
+        match contract_id {
+            None => Ok(Self(None)),
+            Some(id) if !rules.contract_ids.iter().any(|known| known == id) => {
+                Err(FieldError::new(
+                    "contract_id",
+                    "validation.contract_id_unknown",
+                    format!("The contract `{id}` isn't configured."),
+                )
+                .with_details(serde_json::json!({ "contracts": rules.contract_ids })))
+            }
+            Some(id) => Ok(Self(Some(id.to_string()))),
+        }
+    }
+
+    /// Parses the contract id of an answer keyed by the token id alone, e.g. an ownership,
+    /// which may only be omitted when the network has a single nft contract.
+    pub fn parse_unambiguous(
+        contract_id: Option<&str>,
+        rules: &ContractRules,
+    ) -> Result<Self, FieldError> {
+        match contract_id {
+            None if rules.contract_ids.len() > 1 => Err(FieldError::new(
+                "contract_id",
+                "validation.contract_id_ambiguous",
+                "The network has several nft contracts, the `contract_id` must name one.",
+            )
+            .with_details(serde_json::json!({ "contracts": rules.contract_ids }))),
+            contract_id => Self::parse(contract_id, rules),
+        }
+    }
+
+    /// Parses the contract id of a write payload, where it can't be omitted.
+    pub fn parse_required(contract_id: &str, rules: &ContractRules) -> Result<String, FieldError> {
+        Self::parse(Some(contract_id), rules)
+            .map(|id| id.0.expect("Some contract id is parsed into Some"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractsHistoryFilter {
+    pub limit: crate::domain::Limit,
+    pub offset: crate::domain::Offset,
+}
+
+impl ContractsHistoryFilter {
+    pub fn limit(&self) -> i64 {
+        self.limit.get()
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::helpers::{nft_contract_ids, token_id_settings};
+
+    use super::*;
+
+    #[test]
+    fn every_allowed_nft_contract_is_parsed() {
+        let token_ids = token_id_settings();
+        let mut contract_ids = nft_contract_ids();
+        contract_ids.push("items.battlemon.near".to_string());
+        let rules = ContractRules::new(contract_ids, &token_ids);
+        for id in ["nft.battlemon.near", "items.battlemon.near"] {
+            let actual = ContractId::parse_required(id, &rules);
+            assert_eq!(actual, Ok(id.to_string()));
+        }
+    }
+
+    #[test]
+    fn omitted_contracts_are_ambiguous_among_several() {
+        let token_ids = token_id_settings();
+        let rules = ContractRules::new(nft_contract_ids(), &token_ids);
+        assert_eq!(
+            ContractId::parse_unambiguous(None, &rules),
+            Ok(ContractId(None))
+        );
+
+        let mut contract_ids = nft_contract_ids();
+        contract_ids.push("items.battlemon.near".to_string());
+        let rules = ContractRules::new(contract_ids, &token_ids);
+        let actual = ContractId::parse_unambiguous(None, &rules).map_err(|e| e.code);
+        assert_eq!(actual, Err("validation.contract_id_ambiguous"));
+        let actual = ContractId::parse_unambiguous(Some("items.battlemon.near"), &rules);
+        assert_eq!(
+            actual,
+            Ok(ContractId(Some("items.battlemon.near".to_string())))
+        );
+    }
+
+    #[test]
+    fn unknown_contracts_are_rejected() {
+        let token_ids = token_id_settings();
+        for rules in [
+            ContractRules::new(nft_contract_ids(), &token_ids),
+            ContractRules::new(Vec::new(), &token_ids),
+        ] {
+            for id in ["items.battlemon.near", "market.battlemon.near"] {
+                let actual = ContractId::parse(Some(id), &rules).map_err(|e| e.code);
+                assert_eq!(actual, Err("validation.contract_id_unknown"));
+            }
+        }
+    }
+
+    #[test]
+    fn allowed_contracts_are_listed_once() {
+        let token_ids = token_id_settings();
+        let rules = ContractRules::new(
+            [
+                "nft.battlemon.near",
+                "items.battlemon.near",
+                "nft.battlemon.near",
+            ]
+            .map(String::from),
+            &token_ids,
+        );
+        let actual = ContractId::parse(Some("market.battlemon.near"), &rules).unwrap_err();
+        assert_eq!(
+            actual.details,
+            Some(serde_json::json!({
+                "contracts": ["items.battlemon.near", "nft.battlemon.near"]
+            }))
+        );
+    }
+
+    #[test]
+    fn none_values_select_active_version() {
+        assert_eq!(
+            ContractsSelector::parse(None, None),
+            Ok(ContractsSelector::Active)
+        );
+    }
+
+    #[test]
+    fn non_positive_version_is_rejected() {
+        for version in [0, -1] {
+            let actual = ContractsSelector::parse(Some(version), None);
+            assert!(
+                actual.is_err(),
+                "`ContractsSelector` isn't `Err`, actual value is {:?}",
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn version_and_at_together_are_rejected() {
+        let actual = ContractsSelector::parse(Some(1), Some(Utc::now()));
+        assert!(
+            actual.is_err(),
+            "`ContractsSelector` isn't `Err`, actual value is {:?}",
+            actual
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::domain::{FieldError, ValidationErrors, Validator};
+
+/// The format of the rows of a list route.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ExportFormat {
+    /// A page of rows in a JSON report.
+    #[default]
+    Json,
+    /// Every row as a line of comma separated values, after a header of the columns.
+    Csv,
+    /// Every row as a line of a JSON object.
+    Ndjson,
+}
+
+impl ExportFormat {
+    const FORMATS: [&'static str; 3] = ["json", "csv", "ndjson"];
+
+    pub fn parse(format: Option<&str>) -> Result<Self, FieldError> {
+        match format.unwrap_or("json") {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "ndjson" => Ok(Self::Ndjson),
+            format => Err(FieldError::new(
+                "format",
+                "validation.format_unknown",
+                format!("The format `{format}` is unknown."),
+            )
+            .with_details(serde_json::json!({ "formats": Self::FORMATS }))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+/// The requested format and columns of the rows of a list route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Export {
+    pub format: ExportFormat,
+    /// The exported columns in their requested order, every column of the rows by default.
+    pub columns: Vec<&'static str>,
+}
+
+impl Export {
+    /// Parses the comma separated `columns` among the columns of the route's rows.
+    pub fn parse(
+        format: Option<&str>,
+        columns: Option<&str>,
+        available: &[&'static str],
+    ) -> Result<Self, ValidationErrors> {
+        let mut validator = Validator::default();
+        let format = validator.check(ExportFormat::parse(format));
+        let columns = validator.check(parse_columns(columns, available));
+
+        validator.finish(|| {
+            Some(Self {
+                format: format?,
+                columns: columns?,
+            })
+        })
+    }
+
+    /// Whether the rows are streamed as a file rather than paged.
+    pub fn is_file(&self) -> bool {
+        self.format != ExportFormat::Json
+    }
+}
+
+fn parse_columns(
+    columns: Option<&str>,
+    available: &[&'static str],
+) -> Result<Vec<&'static str>, FieldError> {
+    let columns = match columns {
+        Some(columns) => columns,
+        None => return Ok(available.to_vec()),
+    };
+    let mut parsed: Vec<&'static str> = Vec::new();
+    let requested = columns
+        .split(',')
+        .map(str::trim)
+        .filter(|column| !column.is_empty());
+    for column in requested {
+        match available.iter().find(|c| **c == column) {
+            Some(column) if !parsed.contains(column) => parsed.push(column),
+            Some(_) => {}
+            None => {
+                return Err(FieldError::new(
+                    "columns",
+                    "validation.columns_unknown",
+                    format!("The column `{column}` is unknown."),
+                )
+                .with_details(serde_json::json!({ "columns": available })))
+            }
+        }
+    }
+    if parsed.is_empty() {
+        return Err(FieldError::new(
+            "columns",
+            "validation.columns_empty",
+            "No column is selected.",
+        ));
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [&str; 3] = ["id", "token_id", "price"];
+
+    #[test]
+    fn columns_are_parsed_in_the_requested_order() {
+        let actual = Export::parse(Some("csv"), Some("price, id,,price"), &COLUMNS).unwrap();
+        assert_eq!(actual.format, ExportFormat::Csv);
+        assert_eq!(actual.columns, ["price", "id"]);
+
+        let actual = Export::parse(None, None, &COLUMNS).unwrap();
+        assert_eq!(actual.format, ExportFormat::Json);
+        assert_eq!(actual.columns, COLUMNS);
+    }
+
+    #[test]
+    fn unknown_formats_and_columns_are_rejected() {
+        let actual = Export::parse(Some("xml"), Some("id,owner"), &COLUMNS).unwrap_err();
+        let codes: Vec<_> = actual.errors().iter().map(|e| e.code).collect();
+        assert_eq!(
+            codes,
+            ["validation.format_unknown", "validation.columns_unknown"]
+        );
+
+        let actual =
+            Export::parse(Some("ndjson"), Some(" , "), &COLUMNS).map_err(|e| e.first().code);
+        assert_eq!(actual, Err("validation.columns_empty"));
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::Value;
+
+/// A rejected input value, e.g. a negative `limit`, with the stable code clients match on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    /// The name of the input as the client sent it, e.g. `limit`.
+    pub field: String,
+    /// The stable code of the rejection, e.g. `validation.limit_negative`.
+    pub code: &'static str,
+    /// The human readable message, which may change between releases.
+    pub message: String,
+    /// Code specific details, e.g. the maximum of an oversized limit.
+    pub details: Option<Value>,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// Every rejected input of a request, so they can be reported at once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    /// The first rejected input.
+    pub fn first(&self) -> &FieldError {
+        &self.0[0]
+    }
+
+    /// Nests the rejected inputs under the `prefix`, e.g. `owner_id` of the third token of a
+    /// batch becomes `[2].owner_id`.
+    pub fn prefixed(self, prefix: &str) -> Self {
+        let errors = self.0.into_iter().map(|mut error| {
+            error.field = format!("{prefix}.{}", error.field);
+            error
+        });
+        Self(errors.collect())
+    }
+}
+
+impl IntoIterator for ValidationErrors {
+    type Item = FieldError;
+    type IntoIter = std::vec::IntoIter<FieldError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<FieldError> for ValidationErrors {
+    fn from(error: FieldError) -> Self {
+        Self(vec![error])
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let messages: Vec<_> = self.0.iter().map(|e| e.message.as_str()).collect();
+        f.write_str(&messages.join(" "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Accumulates the rejected inputs while the fields of a request are parsed, so that every
+/// one of them is reported rather than the first.
+#[derive(Debug, Default)]
+pub struct Validator(Vec<FieldError>);
+
+impl Validator {
+    /// The parsed value, or `None` with its error kept.
+    pub fn check<T>(&mut self, parsed: Result<T, FieldError>) -> Option<T> {
+        parsed.map_err(|error| self.push(error)).ok()
+    }
+
+    /// The value of a parser rejecting several inputs, e.g. a token of a batch, or `None` with
+    /// its errors kept.
+    pub fn check_all<T>(&mut self, parsed: Result<T, ValidationErrors>) -> Option<T> {
+        parsed.map_err(|errors| self.0.extend(errors)).ok()
+    }
+
+    pub fn push(&mut self, error: FieldError) {
+        self.0.push(error);
+    }
+
+    /// The value built from the checked ones, only once none was rejected. The `build` returns
+    /// `None` only if it's given an unchecked `None`, which is reported as an invalid request.
+    pub fn finish<T>(self, build: impl FnOnce() -> Option<T>) -> Result<T, ValidationErrors> {
+        if !self.0.is_empty() {
+            return Err(ValidationErrors(self.0));
+        }
+
+        build().ok_or_else(|| {
+            FieldError::new("request", "validation.invalid", "The request is invalid.").into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(field: &str) -> FieldError {
+        FieldError::new(field, "validation.test", format!("The {field} is invalid."))
+    }
+
+    fn validated(errors: &[&str]) -> Result<(), ValidationErrors> {
+        let mut validator = Validator::default();
+        let values: Vec<_> = ["limit", "offset", "days"]
+            .iter()
+            .map(|field| match errors.contains(field) {
+                true => validator.check(Err::<(), _>(error(field))),
+                false => validator.check(Ok(())),
+            })
+            .collect();
+        validator.finish(|| values.into_iter().collect::<Option<Vec<_>>>().map(drop))
+    }
+
+    #[test]
+    fn passed_parsers_are_skipped() {
+        let actual = validated(&["limit", "offset"]).unwrap_err();
+        assert_eq!(actual.errors(), [error("limit"), error("offset")]);
+        assert_eq!(actual.first(), &error("limit"));
+        assert_eq!(
+            actual.to_string(),
+            "The limit is invalid. The offset is invalid."
+        );
+    }
+
+    #[test]
+    fn prefixed_errors_are_nested() {
+        let actual = ValidationErrors::from(error("owner_id")).prefixed("[2]");
+        assert_eq!(actual.first().field, "[2].owner_id");
+    }
+
+    #[test]
+    fn only_passed_parsers_are_valid() {
+        assert_eq!(validated(&[]), Ok(()));
+    }
+
+    #[test]
+    fn nested_errors_are_kept() {
+        let mut validator = Validator::default();
+        let nested = ValidationErrors::from(error("owner_id")).prefixed("[0]");
+        assert_eq!(validator.check_all(Err::<(), _>(nested.clone())), None);
+        validator.push(error("limit"));
+
+        let actual = validator.finish(|| Some(())).unwrap_err();
+        assert_eq!(actual.errors(), [nested.first().clone(), error("limit")]);
+    }
+
+    #[test]
+    fn unchecked_missing_values_are_invalid() {
+        let actual = Validator::default().finish(|| None::<()>).unwrap_err();
+        assert_eq!(actual.first().code, "validation.invalid");
+    }
+}
@@ -1,45 +1,74 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Limit(i64);
+use crate::config::OversizedLimit;
+use crate::domain::FieldError;
 
-impl Default for Limit {
-    fn default() -> Self {
-        Self(100)
-    }
+/// Limit parsing rules of a resource.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitBounds {
+    pub default: i64,
+    pub max: i64,
+    pub oversized: OversizedLimit,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limit(i64);
+
 impl Limit {
     pub fn get(self) -> i64 {
         self.0
     }
-}
 
-impl crate::domain::New for Limit {
-    fn new(value: i64) -> Self {
-        Self(value)
+    pub fn parse(value: Option<i64>, bounds: LimitBounds) -> Result<Self, FieldError> {
+        match value {
+            None => Ok(Self(bounds.default)),
+            Some(v) if v.is_negative() => Err(FieldError::new(
+                "limit",
+                "validation.limit_negative",
+                "The limit value must be positive.",
+            )),
+            Some(0) => Err(FieldError::new(
+                "limit",
+                "validation.limit_zero",
+                "The limit value must be at least 1.",
+            )),
+            Some(v) if v > bounds.max => match bounds.oversized {
+                OversizedLimit::Clamp => Ok(Self(bounds.max)),
+                OversizedLimit::Reject => Err(FieldError::new(
+                    "limit",
+                    "validation.limit_too_large",
+                    format!(
+                        "The limit value `{v}` exceeds the maximum of `{}` for this resource.",
+                        bounds.max
+                    ),
+                )
+                .with_details(serde_json::json!({ "max": bounds.max }))),
+            },
+            Some(v) => Ok(Self(v)),
+        }
     }
 }
 
-impl crate::domain::ParseToPositiveInt for Limit {
-    const ERROR: &'static str = "The limit value must be positive.";
-}
-
 #[cfg(test)]
 mod tests {
     use crate::domain::helpers::PositiveIntegersFixture;
-    use crate::domain::private::New;
-    use crate::domain::ParseToPositiveInt;
 
     use super::*;
 
+    const BOUNDS: LimitBounds = LimitBounds {
+        default: 100,
+        max: 10000,
+        oversized: OversizedLimit::Reject,
+    };
+
     #[quickcheck_macros::quickcheck]
     fn valid_limit_are_parsed_successfully(valid_limit: PositiveIntegersFixture) -> bool {
-        Limit::parse(valid_limit.0).is_ok()
+        let limit = valid_limit.0.map(|v| v.max(1));
+        Limit::parse(limit, BOUNDS).is_ok()
     }
 
     #[test]
     fn negative_limit_is_rejected() {
         let limit = Some(-10);
-        let actual = Limit::parse(limit);
+        let actual = Limit::parse(limit, BOUNDS);
         assert!(
             actual.is_err(),
             "`Limit` isn't `Err`, actual value is {:?}",
@@ -48,29 +77,48 @@ mod tests {
     }
 
     #[test]
-    fn none_limit_equals_default() {
-        let limit = None;
-        let actual = Limit::parse(limit);
+    fn zero_limit_is_rejected() {
+        let actual = Limit::parse(Some(0), BOUNDS);
         assert!(
-            actual.is_ok(),
-            "The actual `Limit` isn't `Ok`, actual value is {:?}",
+            actual.is_err(),
+            "`Limit` isn't `Err`, actual value is {:?}",
             actual
         );
+    }
+
+    #[test]
+    fn none_limit_equals_configured_default() {
+        let limit = None;
+        let actual = Limit::parse(limit, BOUNDS);
         assert_eq!(
             actual,
-            Ok(Limit::default()),
+            Ok(Limit(100)),
             "The actual `Limit` doesn't equal default value, actual value is {:?}",
             actual
         );
     }
 
     #[test]
-    fn default_limit_is_100() {
-        let actual = Limit::default();
+    fn oversized_limit_is_rejected() {
+        let actual = Limit::parse(Some(10001), BOUNDS);
+        assert!(
+            actual.is_err(),
+            "`Limit` isn't `Err`, actual value is {:?}",
+            actual
+        );
+    }
+
+    #[test]
+    fn oversized_limit_is_clamped() {
+        let bounds = LimitBounds {
+            oversized: OversizedLimit::Clamp,
+            ..BOUNDS
+        };
+        let actual = Limit::parse(Some(10001), bounds);
         assert_eq!(
             actual,
-            Limit::new(100),
-            "The actual `Limit` doesn't contain `100i64`, actual value is {:?}",
+            Ok(Limit(10000)),
+            "The actual `Limit` isn't clamped to max, actual value is {:?}",
             actual
         );
     }
@@ -0,0 +1,137 @@
+use crate::domain::FieldError;
+
+/// The schemes of the absolute media urls, the relative ones, e.g. an IPFS CID, are resolved
+/// against the contract's `base_uri`.
+const MEDIA_SCHEMES: [&str; 4] = ["https", "http", "ipfs", "ar"];
+
+/// The longest accepted media url.
+const MAX_MEDIA_LENGTH: usize = 2048;
+
+/// The media url of a token, see NEP-177.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Media(String);
+
+impl Media {
+    pub fn parse(field: &str, media: &str) -> Result<Self, FieldError> {
+        let scheme = media.split_once("://").map(|(scheme, _)| scheme);
+        match media {
+            "" => Err(FieldError::new(
+                field,
+                "validation.media_empty",
+                "The media url is empty.",
+            )),
+            media if media.len() > MAX_MEDIA_LENGTH => Err(FieldError::new(
+                field,
+                "validation.media_too_long",
+                format!("The media url is too long (max {MAX_MEDIA_LENGTH})."),
+            )),
+            media
+                if media
+                    .chars()
+                    .any(|ch| ch.is_whitespace() || ch.is_control()) =>
+            {
+                Err(FieldError::new(
+                    field,
+                    "validation.media_invalid_chars",
+                    format!("The media url `{media}` contains whitespace or control chars."),
+                ))
+            }
+            media => match scheme {
+                Some(scheme) if !MEDIA_SCHEMES.contains(&scheme) => Err(FieldError::new(
+                    field,
+                    "validation.media_unsupported_scheme",
+                    format!("The media url scheme `{scheme}` isn't supported."),
+                )
+                .with_details(serde_json::json!({ "schemes": MEDIA_SCHEMES }))),
+                _ => Ok(Self(media.to_string())),
+            },
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The length of the sha256 digest of a media file.
+const MEDIA_HASH_LENGTH: usize = 32;
+
+/// The base64-encoded sha256 hash of the media file of a token, see NEP-177.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaHash(String);
+
+impl MediaHash {
+    pub fn parse(field: &str, media_hash: &str) -> Result<Self, FieldError> {
+        match base64::decode(media_hash) {
+            Ok(digest) if digest.len() == MEDIA_HASH_LENGTH => Ok(Self(media_hash.to_string())),
+            _ => Err(FieldError::new(
+                field,
+                "validation.media_hash_invalid",
+                format!("The media hash `{media_hash}` isn't a base64-encoded sha256 hash."),
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_and_relative_media_are_parsed() {
+        let valid_media = [
+            "https://example.com/lemon.png",
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            "u4pruydqqvj8pr7z",
+        ];
+        for media in valid_media {
+            assert!(Media::parse("media", media).is_ok(), "`{media}` is valid");
+        }
+    }
+
+    #[test]
+    fn invalid_media_are_rejected() {
+        let test_cases = [
+            ("", "validation.media_empty"),
+            (&"a".repeat(2049), "validation.media_too_long"),
+            (
+                "https://example.com/a b.png",
+                "validation.media_invalid_chars",
+            ),
+            (
+                "javascript://alert(1)",
+                "validation.media_unsupported_scheme",
+            ),
+        ];
+        for (media, code) in test_cases {
+            let actual = Media::parse("media", media).map_err(|e| e.code);
+            assert_eq!(actual, Err(code), "`{media}` must be rejected");
+        }
+    }
+
+    #[test]
+    fn only_sha256_media_hashes_are_parsed() {
+        let valid = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+        assert!(MediaHash::parse("media_hash", valid).is_ok());
+
+        let invalid_hashes = [
+            "",
+            "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=47DE",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "aGVsbG8=",
+        ];
+        for media_hash in invalid_hashes {
+            let actual = MediaHash::parse("media_hash", media_hash).map_err(|e| e.code);
+            assert_eq!(
+                actual,
+                Err("validation.media_hash_invalid"),
+                "`{media_hash}` must be rejected"
+            );
+        }
+    }
+}
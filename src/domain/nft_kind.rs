@@ -0,0 +1,47 @@
+use battlemon_models::nft::NftKind;
+
+use crate::domain::FieldError;
+
+/// The kind of the models of the nft tokens, e.g. `lemon`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NftKindName(String);
+
+impl NftKindName {
+    pub fn parse(field: &str, kind: &str) -> Result<Self, FieldError> {
+        match serde_json::from_value::<NftKind>(kind.into()) {
+            Ok(_) => Ok(Self(kind.to_string())),
+            Err(_) => Err(FieldError::new(
+                field,
+                "validation.nft_kind_unknown",
+                format!("The nft kind `{kind}` is unknown."),
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_kinds_are_parsed() {
+        let actual = NftKindName::parse("kind", "lemon").map(|k| k.as_str().to_string());
+        assert_eq!(actual, Ok("lemon".to_string()));
+    }
+
+    #[test]
+    fn unknown_kinds_are_rejected() {
+        for kind in ["", "Lemon", "orange"] {
+            let actual = NftKindName::parse("kind", kind).map_err(|e| e.code);
+            assert_eq!(
+                actual.err(),
+                Some("validation.nft_kind_unknown"),
+                "`{kind}`"
+            );
+        }
+    }
+}
@@ -1,13 +1,24 @@
-use crate::domain::{AsInner, Limit, NftTokenOwnerId, Offset, TokenId};
-use battlemon_models::nft::NftKind;
+use crate::domain::{
+    AccountId, AsInner, ContractId, ContractRules, FieldError, Limit, Media, MediaHash,
+    NftTokenOwnerId, Offset, TokenId, ValidationErrors, Validator,
+};
+use battlemon_models::nft::{NftKind, NftTokenForRest};
+use serde::Deserialize;
 
 #[derive(Debug, Clone)]
 pub struct NftTokenFilter {
     pub offset: Offset,
     pub limit: Limit,
     pub owner_id: NftTokenOwnerId,
+    pub contract_id: ContractId,
     pub token_id: TokenId,
     pub nft_kind: Option<String>,
+    pub sort: NftTokenSort,
+    pub expand: NftTokenExpand,
+    /// Only the tokens with (or without) an ask.
+    pub listed: Option<bool>,
+    /// Only the tokens with (or without) an unexpired bid.
+    pub has_bids: Option<bool>,
     // pub by_token_trait: TokenTrait,
 }
 
@@ -24,6 +35,10 @@ impl NftTokenFilter {
         self.owner_id.as_inner()
     }
 
+    pub fn contract_id(&self) -> Option<&str> {
+        self.contract_id.as_inner()
+    }
+
     pub fn token_id(&self) -> Option<&str> {
         self.token_id.as_inner()
     }
@@ -31,4 +46,162 @@ impl NftTokenFilter {
     pub fn nft_kind(&self) -> Option<&str> {
         self.nft_kind.as_deref()
     }
+
+    pub fn sort(&self) -> &'static str {
+        self.sort.as_str()
+    }
+}
+
+/// The market rows embedded in the listed nft tokens, e.g. `expand=ask,last_sale`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NftTokenExpand {
+    /// The cheapest ask of the token.
+    pub ask: bool,
+    /// The highest unexpired bid of the token.
+    pub best_bid: bool,
+    /// The latest sale of the token.
+    pub last_sale: bool,
+}
+
+impl NftTokenExpand {
+    const FIELDS: [&'static str; 3] = ["ask", "best_bid", "last_sale"];
+
+    pub fn parse(expand: Option<&str>) -> Result<Self, FieldError> {
+        let mut parsed = Self::default();
+        let fields = expand
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty());
+        for field in fields {
+            match field {
+                "ask" => parsed.ask = true,
+                "best_bid" => parsed.best_bid = true,
+                "last_sale" => parsed.last_sale = true,
+                _ => {
+                    return Err(FieldError::new(
+                        "expand",
+                        "validation.expand_unknown",
+                        format!("The expanded field `{field}` is unknown."),
+                    )
+                    .with_details(serde_json::json!({ "fields": Self::FIELDS })))
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// The order of the listed nft tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NftTokenSort {
+    /// The oldest stored first.
+    #[default]
+    Id,
+    /// The rarest first, by the rank within the kind, the unranked ones last.
+    Rarity,
+}
+
+impl NftTokenSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NftTokenSort::Id => "id",
+            NftTokenSort::Rarity => "rarity",
+        }
+    }
+}
+
+/// A nft token of a write payload, which passed the validation.
+#[derive(Debug, Clone)]
+pub struct NewNftToken {
+    contract_id: String,
+    nft_token: NftTokenForRest,
+}
+
+impl NewNftToken {
+    /// Validates every token of a batch, the rejected inputs are nested under the index of
+    /// their token, e.g. `[2].owner_id`.
+    pub fn parse_batch(
+        nft_tokens: Vec<(String, NftTokenForRest)>,
+        rules: &ContractRules,
+    ) -> Result<Vec<Self>, ValidationErrors> {
+        let mut validator = Validator::default();
+        let mut parsed = Vec::with_capacity(nft_tokens.len());
+        for (i, (contract_id, nft_token)) in nft_tokens.into_iter().enumerate() {
+            let nft_token = Self::try_from((contract_id, nft_token, rules))
+                .map_err(|e| e.prefixed(&format!("[{i}]")));
+            parsed.extend(validator.check_all(nft_token));
+        }
+
+        validator.finish(|| Some(parsed))
+    }
+
+    pub fn token_id(&self) -> &str {
+        &self.nft_token.token_id
+    }
+
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    pub fn into_parts(self) -> (String, NftTokenForRest) {
+        (self.contract_id, self.nft_token)
+    }
+}
+
+impl TryFrom<(String, NftTokenForRest, &ContractRules<'_>)> for NewNftToken {
+    type Error = ValidationErrors;
+
+    fn try_from(
+        (contract_id, mut nft_token, rules): (String, NftTokenForRest, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse_required(&contract_id, rules));
+        let format = rules.token_id_format(contract_id.as_deref());
+        validator.check(AccountId::parse("owner_id", &nft_token.owner_id));
+        let token_id = validator.check(TokenId::parse_required(&nft_token.token_id, format));
+        validator.check(Media::parse("media", &nft_token.media));
+        if let Some(hash) = nft_token.media_hash.as_deref() {
+            validator.check(MediaHash::parse("media_hash", hash));
+        }
+
+        validator.finish(|| {
+            nft_token.token_id = token_id?;
+            Some(Self {
+                contract_id: contract_id?,
+                nft_token,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expanded_fields_are_parsed() {
+        let actual = NftTokenExpand::parse(Some("last_sale, ask,,ask")).unwrap();
+        let expected = NftTokenExpand {
+            ask: true,
+            best_bid: false,
+            last_sale: true,
+        };
+        assert_eq!(actual, expected);
+        assert_eq!(NftTokenExpand::parse(None), Ok(NftTokenExpand::default()));
+    }
+
+    #[test]
+    fn unknown_expanded_fields_are_rejected() {
+        for expand in ["bids", "ask,sale", "Ask"] {
+            let actual = NftTokenExpand::parse(Some(expand)).map_err(|e| e.code);
+            assert_eq!(
+                actual,
+                Err("validation.expand_unknown"),
+                "`{expand}` must be rejected"
+            );
+        }
+    }
 }
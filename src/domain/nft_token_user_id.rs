@@ -1,13 +1,5 @@
-use once_cell::sync::Lazy;
-
-use regex::Regex;
-
-use crate::domain::Parse;
-
-static RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"^(([a-z\d]+[\-_])*[a-z\d]+\.)*([a-z\d]+[\-_])*[a-z\d]+$"#)
-        .expect("Couldn't compile regexp expression")
-});
+use crate::domain::account_id::ACCOUNT_ID_RE;
+use crate::domain::{FieldError, Parse};
 
 #[derive(Debug, Clone)]
 pub struct NftTokenOwnerId(Option<String>);
@@ -16,18 +8,30 @@ crate::domain::impl_into_inner!(NftTokenOwnerId);
 crate::domain::impl_as_inner!(NftTokenOwnerId);
 
 impl Parse<String> for NftTokenOwnerId {
-    fn parse(owner_id: Option<String>) -> Result<Self, String> {
+    fn parse(owner_id: Option<String>) -> Result<Self, FieldError> {
         Self::parse(owner_id.as_deref())
     }
 }
 
 impl Parse<&str> for NftTokenOwnerId {
-    fn parse(owner_id: Option<&str>) -> Result<Self, String> {
+    fn parse(owner_id: Option<&str>) -> Result<Self, FieldError> {
         match owner_id.map(|v| v.trim()) {
             None => Ok(NftTokenOwnerId(None)),
-            Some(id) if id.len() < 2 => Err(format!("User id `{id:?}` is too short (min 2)")),
-            Some(id) if id.len() > 64 => Err(format!("User id `{id:?}` is too long (max 64)")),
-            Some(id) if !RE.is_match(id) => Err(format!("User id `{id:?}` contains wrong chars.")),
+            Some(id) if id.len() < 2 => Err(FieldError::new(
+                "owner_id",
+                "validation.owner_id_too_short",
+                format!("User id `{id:?}` is too short (min 2)"),
+            )),
+            Some(id) if id.len() > 64 => Err(FieldError::new(
+                "owner_id",
+                "validation.owner_id_too_long",
+                format!("User id `{id:?}` is too long (max 64)"),
+            )),
+            Some(id) if !ACCOUNT_ID_RE.is_match(id) => Err(FieldError::new(
+                "owner_id",
+                "validation.owner_id_invalid_chars",
+                format!("User id `{id:?}` contains wrong chars."),
+            )),
             Some(id) => Ok(NftTokenOwnerId(Some(id.to_string()))),
         }
     }
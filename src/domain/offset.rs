@@ -8,6 +8,8 @@ impl crate::domain::New for Offset {
 }
 
 impl crate::domain::ParseToPositiveInt for Offset {
+    const FIELD: &'static str = "offset";
+    const CODE: &'static str = "validation.offset_negative";
     const ERROR: &'static str = "The offset value must be positive.";
 }
 
@@ -1,8 +1,11 @@
+use crate::domain::AsInner;
+
 #[derive(Debug)]
 pub struct PaidFilter {
     pub limit: crate::domain::Limit,
     pub offset: crate::domain::Offset,
     pub days: crate::domain::PaidDays,
+    pub contract_id: crate::domain::ContractId,
 }
 
 impl PaidFilter {
@@ -17,4 +20,8 @@ impl PaidFilter {
     pub fn days(&self) -> i64 {
         self.days.get()
     }
+
+    pub fn contract_id(&self) -> Option<&str> {
+        self.contract_id.as_inner()
+    }
 }
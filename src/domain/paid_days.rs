@@ -16,6 +16,8 @@ impl New for PaidDays {
 }
 
 impl ParseToPositiveInt for PaidDays {
+    const FIELD: &'static str = "days";
+    const CODE: &'static str = "validation.days_negative";
     const ERROR: &'static str = "The parsed value of days must be positive.";
 }
 
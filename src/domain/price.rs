@@ -0,0 +1,70 @@
+use rust_decimal::Decimal;
+
+use crate::domain::FieldError;
+
+/// A positive price in NEAR, which is divisible down to a yoctoNEAR.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Price(Decimal);
+
+impl Price {
+    /// The number of decimal places of a yoctoNEAR.
+    pub const MAX_SCALE: u32 = 24;
+
+    pub fn parse(field: &str, price: Decimal) -> Result<Self, FieldError> {
+        match price {
+            price if price <= Decimal::ZERO => Err(FieldError::new(
+                field,
+                "validation.price_not_positive",
+                format!("The price `{price}` must be positive."),
+            )),
+            price if price.normalize().scale() > Self::MAX_SCALE => Err(FieldError::new(
+                field,
+                "validation.price_scale_too_large",
+                format!(
+                    "The price `{price}` has more than `{}` decimal places.",
+                    Self::MAX_SCALE
+                ),
+            )
+            .with_details(serde_json::json!({ "max_scale": Self::MAX_SCALE }))),
+            price => Ok(Self(price)),
+        }
+    }
+
+    pub fn get(self) -> Decimal {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn positive_prices_are_parsed() {
+        for price in [dec!(1), dec!(0.000000000000000000000001), dec!(10.50)] {
+            assert_eq!(Price::parse("price", price), Ok(Price(price)));
+        }
+    }
+
+    #[test]
+    fn trailing_zeros_do_not_count_to_scale() {
+        let price = dec!(1.0000000000000000000000000);
+        assert!(Price::parse("price", price).is_ok());
+    }
+
+    #[test]
+    fn non_positive_prices_are_rejected() {
+        for price in [dec!(0), dec!(-1)] {
+            let actual = Price::parse("price", price).map_err(|e| e.code);
+            assert_eq!(actual, Err("validation.price_not_positive"));
+        }
+    }
+
+    #[test]
+    fn price_finer_than_yocto_near_is_rejected() {
+        let actual = Price::parse("price", dec!(0.0000000000000000000000001)).map_err(|e| e.code);
+        assert_eq!(actual, Err("validation.price_scale_too_large"));
+    }
+}
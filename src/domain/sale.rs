@@ -1,9 +1,15 @@
-use crate::domain::AsInner;
+use battlemon_models::market::sale::SaleForRest;
+
+use crate::domain::{
+    AccountId, AsInner, ContractId, ContractRules, FieldError, Price, TokenId, ValidationErrors,
+    Validator,
+};
 
 #[derive(Debug, Clone)]
 pub struct SaleFilter {
     pub limit: crate::domain::Limit,
     pub offset: crate::domain::Offset,
+    pub contract_id: ContractId,
     pub token_id: crate::domain::TokenId,
 }
 
@@ -16,7 +22,127 @@ impl SaleFilter {
         self.offset.get()
     }
 
+    pub fn contract_id(&self) -> Option<&str> {
+        self.contract_id.as_inner()
+    }
+
     pub fn token_id(&self) -> Option<&str> {
         self.token_id.as_inner()
     }
 }
+
+/// A sale of a write payload, which passed the validation.
+#[derive(Debug, Clone)]
+pub struct NewSale {
+    contract_id: String,
+    sale: SaleForRest,
+}
+
+impl NewSale {
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    pub fn into_parts(self) -> (String, SaleForRest) {
+        (self.contract_id, self.sale)
+    }
+}
+
+impl TryFrom<(String, SaleForRest, &ContractRules<'_>)> for NewSale {
+    type Error = ValidationErrors;
+
+    fn try_from(
+        (contract_id, mut sale, rules): (String, SaleForRest, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse_required(&contract_id, rules));
+        let format = rules.token_id_format(contract_id.as_deref());
+        let prev_owner = validator.check(AccountId::parse("prev_owner", &sale.prev_owner));
+        let curr_owner = validator.check(AccountId::parse("curr_owner", &sale.curr_owner));
+        let token_id = validator.check(TokenId::parse_required(&sale.token_id, format));
+        validator.check(Price::parse("price", sale.price));
+
+        if let (Some(prev_owner), Some(curr_owner)) = (prev_owner, curr_owner) {
+            if prev_owner == curr_owner {
+                validator.push(FieldError::new(
+                    "curr_owner",
+                    "validation.curr_owner_is_prev_owner",
+                    format!(
+                        "The token is sold by `{}` to themselves.",
+                        prev_owner.as_str()
+                    ),
+                ));
+            }
+        }
+
+        validator.finish(|| {
+            sale.token_id = token_id?;
+            Some(Self {
+                contract_id: contract_id?,
+                sale,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::domain::helpers::{nft_contract_ids, token_id_settings, NFT_CONTRACT_ID};
+
+    use super::*;
+
+    fn parse(sale: SaleForRest) -> Result<NewSale, ValidationErrors> {
+        let token_ids = token_id_settings();
+        let rules = ContractRules::new(nft_contract_ids(), &token_ids);
+        NewSale::try_from((NFT_CONTRACT_ID.to_string(), sale, &rules))
+    }
+
+    fn sale() -> SaleForRest {
+        SaleForRest {
+            prev_owner: "alice.near".to_string(),
+            curr_owner: "bob.near".to_string(),
+            token_id: "1".to_string(),
+            price: dec!(1.5),
+        }
+    }
+
+    #[test]
+    fn valid_sale_is_accepted() {
+        let actual = parse(sale()).map(NewSale::into_parts);
+        assert_eq!(actual, Ok((NFT_CONTRACT_ID.to_string(), sale())));
+    }
+
+    #[test]
+    fn sale_to_prev_owner_is_rejected() {
+        let mut sale = sale();
+        sale.curr_owner = sale.prev_owner.clone();
+        let actual = parse(sale).map_err(|e| e.first().code);
+        assert_eq!(actual.err(), Some("validation.curr_owner_is_prev_owner"));
+    }
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let sale = SaleForRest {
+            prev_owner: "Alice".to_string(),
+            curr_owner: "b".to_string(),
+            token_id: "".to_string(),
+            price: dec!(-1),
+        };
+        let token_ids = token_id_settings();
+        let rules = ContractRules::new(nft_contract_ids(), &token_ids);
+        let actual = NewSale::try_from(("items.near".to_string(), sale, &rules)).unwrap_err();
+        let fields: Vec<_> = actual.errors().iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "contract_id",
+                "prev_owner",
+                "curr_owner",
+                "token_id",
+                "price"
+            ]
+        );
+    }
+}
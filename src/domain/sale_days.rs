@@ -1,4 +1,4 @@
-use crate::domain::{New, ParseToPositiveInt};
+use crate::domain::{FieldError, New, ParseToPositiveInt};
 
 #[derive(Debug, Copy, Clone, Default)]
 pub struct SaleDays;
@@ -10,10 +10,17 @@ impl New for SaleDays {
 }
 
 impl ParseToPositiveInt for SaleDays {
-    fn parse(days: Option<i64>) -> Result<Self, &'static str> {
+    const FIELD: &'static str = "days";
+    const CODE: &'static str = "validation.days_prohibited";
+
+    fn parse(days: Option<i64>) -> Result<Self, FieldError> {
         match days {
             None => Ok(Self),
-            Some(_) => Err("query 'days' is prohibited for the `sales` route"),
+            Some(_) => Err(FieldError::new(
+                Self::FIELD,
+                Self::CODE,
+                "query 'days' is prohibited for the `sales` route",
+            )),
         }
     }
 }
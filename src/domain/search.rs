@@ -0,0 +1,119 @@
+use crate::domain::{AsInner, ContractId, FieldError, Limit, Offset};
+
+/// The longest accepted search query.
+const MAX_SEARCH_QUERY_LENGTH: usize = 100;
+
+/// The text typed into the search box, e.g. `lemon red cap`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    /// The words joined by a single space, matched by trigrams.
+    text: String,
+    /// The words as prefixes of the full-text query, e.g. `lemon:* & red:*`.
+    prefixes: String,
+}
+
+impl SearchQuery {
+    pub fn parse(query: Option<&str>) -> Result<Self, FieldError> {
+        let query = query.unwrap_or_default().trim();
+        if query.chars().count() > MAX_SEARCH_QUERY_LENGTH {
+            return Err(FieldError::new(
+                "q",
+                "validation.search_query_too_long",
+                format!("The search query is too long (max {MAX_SEARCH_QUERY_LENGTH})."),
+            )
+            .with_details(serde_json::json!({ "max_length": MAX_SEARCH_QUERY_LENGTH })));
+        }
+
+        // the full-text parser splits the words by the punctuation too, e.g. `cap_red`
+        let words: Vec<_> = query
+            .split(|ch: char| !ch.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if words.is_empty() {
+            return Err(FieldError::new(
+                "q",
+                "validation.search_query_empty",
+                "The search query has no words.",
+            ));
+        }
+
+        let prefixes = words
+            .iter()
+            .map(|word| format!("{word}:*"))
+            .collect::<Vec<_>>()
+            .join(" & ");
+        Ok(Self {
+            text: words.join(" "),
+            prefixes,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn prefixes(&self) -> &str {
+        &self.prefixes
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchFilter {
+    pub query: SearchQuery,
+    pub limit: Limit,
+    pub offset: Offset,
+    pub contract_id: ContractId,
+    pub nft_kind: Option<String>,
+}
+
+impl SearchFilter {
+    pub fn limit(&self) -> i64 {
+        self.limit.get()
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.get()
+    }
+
+    pub fn contract_id(&self) -> Option<&str> {
+        self.contract_id.as_inner()
+    }
+
+    pub fn nft_kind(&self) -> Option<&str> {
+        self.nft_kind.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_are_normalized_into_prefixes() {
+        let actual = SearchQuery::parse(Some("  Lemon  cap_RED ")).unwrap();
+        assert_eq!(actual.text(), "lemon cap red");
+        assert_eq!(actual.prefixes(), "lemon:* & cap:* & red:*");
+    }
+
+    #[test]
+    fn tsquery_operators_are_not_words() {
+        let actual = SearchQuery::parse(Some("lemon & !(cap | 'red')")).unwrap();
+        assert_eq!(actual.prefixes(), "lemon:* & cap:* & red:*");
+    }
+
+    #[test]
+    fn invalid_queries_are_rejected() {
+        let too_long = "a".repeat(101);
+        let test_cases = [
+            (None, "validation.search_query_empty"),
+            (Some("   "), "validation.search_query_empty"),
+            (Some("&|!:*"), "validation.search_query_empty"),
+            (Some(too_long.as_str()), "validation.search_query_too_long"),
+        ];
+        for (query, code) in test_cases {
+            let actual = SearchQuery::parse(query).map_err(|e| e.code);
+            assert_eq!(actual, Err(code), "`{query:?}` must be rejected");
+        }
+    }
+}
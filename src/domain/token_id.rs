@@ -1,4 +1,5 @@
-use crate::domain::Parse;
+use crate::config::{TokenIdCharset, TokenIdFormat};
+use crate::domain::FieldError;
 
 #[derive(Debug, Clone)]
 pub struct TokenId(Option<String>);
@@ -6,25 +7,56 @@ pub struct TokenId(Option<String>);
 crate::domain::impl_into_inner!(TokenId);
 crate::domain::impl_as_inner!(TokenId);
 
-impl Parse<&str> for TokenId {
-    fn parse(token_id: Option<&str>) -> Result<Self, String> {
+impl TokenId {
+    pub fn parse(token_id: Option<&str>, format: TokenIdFormat) -> Result<Self, FieldError> {
+        Self::parse_field("token_id", token_id, format)
+    }
+
+    /// Parses the token id of a write payload, where it can't be omitted.
+    pub fn parse_required(token_id: &str, format: TokenIdFormat) -> Result<String, FieldError> {
+        Self::parse(Some(token_id), format)
+            .map(|id| id.0.expect("Some token id is parsed into Some"))
+    }
+
+    /// Parses a token id sent as the `field` input, e.g. an item of a list.
+    pub fn parse_field(
+        field: &str,
+        token_id: Option<&str>,
+        format: TokenIdFormat,
+    ) -> Result<Self, FieldError> {
         match token_id.map(|v| v.trim()) {
             None => Ok(TokenId(None)),
-            Some(id) if id.is_empty() => Err("Token id is empty".to_string()),
-            Some(id) if !id.chars().all(|ch| ch.is_ascii_digit()) => {
-                Err("The token id must contain only digits".to_string())
+            Some("") => Err(FieldError::new(
+                field,
+                "validation.token_id_empty",
+                "Token id is empty",
+            )),
+            Some(id) if id.len() > format.max_length => Err(FieldError::new(
+                field,
+                "validation.token_id_too_long",
+                format!("The token id is too long (max {}).", format.max_length),
+            )
+            .with_details(serde_json::json!({ "max_length": format.max_length }))),
+            Some(id) if id.chars().any(char::is_control) => Err(FieldError::new(
+                field,
+                "validation.token_id_invalid_chars",
+                "The token id contains control chars.",
+            )),
+            Some(id)
+                if format.charset == TokenIdCharset::Numeric
+                    && !id.chars().all(|ch| ch.is_ascii_digit()) =>
+            {
+                Err(FieldError::new(
+                    field,
+                    "validation.token_id_not_numeric",
+                    "The token id must contain only digits",
+                ))
             }
             Some(id) => Ok(TokenId(Some(id.to_string()))),
         }
     }
 }
 
-impl Parse<String> for TokenId {
-    fn parse(token_id: Option<String>) -> Result<Self, String> {
-        Self::parse(token_id.as_deref())
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use crate::domain::IntoInner;
@@ -33,6 +65,15 @@ mod tests {
 
     use super::*;
 
+    const NEP171: TokenIdFormat = TokenIdFormat {
+        charset: TokenIdCharset::Nep171,
+        max_length: 16,
+    };
+    const NUMERIC: TokenIdFormat = TokenIdFormat {
+        charset: TokenIdCharset::Numeric,
+        max_length: 32,
+    };
+
     #[derive(Debug, Clone)]
     pub struct ValidTokenIdFixture(pub Option<String>);
 
@@ -46,14 +87,34 @@ mod tests {
 
     #[quickcheck_macros::quickcheck]
     fn valid_token_ids(valid_token_id: ValidTokenIdFixture) -> bool {
-        TokenId::parse(valid_token_id.0).is_ok()
+        TokenId::parse(valid_token_id.0.as_deref(), NUMERIC).is_ok()
+    }
+
+    #[test]
+    fn nep171_token_ids_are_parsed() {
+        for id in ["1", "weapon:123", "lemon-1.v2", "ключ"] {
+            let actual = TokenId::parse(Some(id), NEP171).map(IntoInner::into_inner);
+            assert_eq!(actual, Ok(Some(id.to_string())));
+        }
+    }
+
+    #[test]
+    fn nep171_token_id_is_bounded() {
+        let test_cases = [
+            ("a".repeat(17), "validation.token_id_too_long"),
+            ("weapon\n1".to_string(), "validation.token_id_invalid_chars"),
+        ];
+        for (id, code) in test_cases {
+            let actual = TokenId::parse(Some(&id), NEP171).map_err(|e| e.code);
+            assert_eq!(actual.err(), Some(code), "`{id:?}` must be rejected");
+        }
     }
 
     #[test]
-    fn user_id_contains_wrong_chars() {
+    fn numeric_token_id_contains_wrong_chars() {
         let invalid_token_ids = ["a", "1a", "a1", "123a", "12.", ".12", "+12", "-12", "1+2"];
         for id in invalid_token_ids {
-            let actual = TokenId::parse(Some(id));
+            let actual = TokenId::parse(Some(id), NUMERIC);
             assert!(
                 actual.is_err(),
                 "The actual `TokenId` isn't `Err`, actual value {:?}",
@@ -64,8 +125,7 @@ mod tests {
 
     #[test]
     fn empty_token_id_is_rejected() {
-        let id = Some(String::new());
-        let actual = TokenId::parse(id);
+        let actual = TokenId::parse(Some(""), NEP171);
         assert!(
             actual.is_err(),
             "The actual `TokenId` isn't `Err`, actual value {:?}",
@@ -75,8 +135,7 @@ mod tests {
 
     #[test]
     fn when_token_id_is_none_then_parsed_is_ok_none() {
-        let id: Option<String> = None;
-        let actual = TokenId::parse(id);
+        let actual = TokenId::parse(None, NEP171);
         assert!(
             actual.is_ok(),
             "The actual `TokenId` isn't `Ok`, actual value {:?}",
@@ -1,27 +1,44 @@
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::fmt::{self, Debug, Display, Formatter};
+use std::fmt::{self, Display, Formatter};
 
-use actix_web::http::header;
+use actix_web::http::{header, StatusCode};
 use actix_web::{HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::domain::{FieldError, ValidationErrors};
+use utoipa::openapi::{Content, Ref, RefOr, Response, ResponseBuilder};
+use utoipa::ToSchema;
 
 pub use ask::*;
+pub use audit_log::*;
 pub use auth::*;
 pub use bid::*;
+pub use collection::*;
 pub use contract::*;
 pub use is_owner::*;
+pub use network::*;
 pub use nft_tokens::*;
 pub use paid::*;
+pub use rate_limit::*;
 pub use sale::*;
+pub use search::*;
 
 mod ask;
+mod audit_log;
 mod auth;
 mod bid;
+mod collection;
 mod contract;
 mod is_owner;
+pub mod middleware;
+mod network;
 mod nft_tokens;
 mod paid;
+mod rate_limit;
 mod sale;
+mod search;
 
 fn error_chain_fmt(error: &impl Error, f: &mut Formatter<'_>) -> fmt::Result {
     writeln!(f, "{}\n", error)?;
@@ -34,25 +51,176 @@ fn error_chain_fmt(error: &impl Error, f: &mut Formatter<'_>) -> fmt::Result {
     Ok(())
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The body of every error response.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct JsonError {
+    /// The human readable message, which may change between releases.
+    error: String,
+    /// The stable machine readable code, e.g. `validation.limit_negative`.
+    code: String,
+    /// The rejected input of a validation error, e.g. `limit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+    /// The id of the failed request, which its logs are tagged with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    /// Code specific details, e.g. the maximum of an oversized limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    details: Option<Value>,
+    /// Every rejected input of a validation error, the first one is also reported above.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<JsonFieldError>,
+}
+
+/// A rejected input of a validation error.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct JsonFieldError {
+    /// The human readable message, which may change between releases.
     error: String,
+    /// The stable machine readable code, e.g. `validation.limit_negative`.
+    code: String,
+    /// The rejected input, e.g. `limit`.
+    field: String,
+    /// Code specific details, e.g. the maximum of an oversized limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    details: Option<Value>,
+}
+
+impl JsonFieldError {
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn details(&self) -> Option<&Value> {
+        self.details.as_ref()
+    }
+}
+
+impl From<&FieldError> for JsonFieldError {
+    fn from(error: &FieldError) -> Self {
+        Self {
+            error: error.message.clone(),
+            code: error.code.to_string(),
+            field: error.field.clone(),
+            details: error.details.clone(),
+        }
+    }
 }
 
 impl JsonError {
-    pub fn new<T: Display>(error: T) -> Self {
+    pub fn new<T: Display>(code: &str, error: T) -> Self {
         Self {
             error: error.to_string(),
+            code: code.to_string(),
+            field: None,
+            request_id: None,
+            details: None,
+            errors: Vec::new(),
         }
     }
+
+    pub fn unexpected<T: Display>(error: T) -> Self {
+        Self::new(UNEXPECTED_ERROR_CODE, error)
+    }
+
+    pub fn from_error<T: ApiError>(error: &T) -> Self {
+        let json_error = Self {
+            details: error.details(),
+            ..Self::new(error.code(), error)
+        };
+        match error.validation_errors() {
+            Some(errors) => Self {
+                field: Some(errors.first().field.clone()),
+                details: json_error
+                    .details
+                    .or_else(|| errors.first().details.clone()),
+                errors: errors.errors().iter().map(JsonFieldError::from).collect(),
+                ..json_error
+            },
+            None => json_error,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn details(&self) -> Option<&Value> {
+        self.details.as_ref()
+    }
+
+    pub fn errors(&self) -> &[JsonFieldError] {
+        &self.errors
+    }
 }
 
-pub fn default_error_response<T>(error: &T) -> HttpResponse
-where
-    T: Debug + Display + ResponseError,
-{
-    let json_error = JsonError::new(error);
+/// The code of the errors which aren't caused by the request.
+pub const UNEXPECTED_ERROR_CODE: &str = "internal.unexpected";
+
+/// The machine readable parts of an error response.
+pub trait ApiError: ResponseError {
+    /// The stable code clients match on, e.g. `auth.invalid_credentials`, a validation error
+    /// uses the code of its first rejected input.
+    fn code(&self) -> &'static str;
+
+    fn details(&self) -> Option<Value> {
+        None
+    }
+
+    /// Every rejected input of a validation error.
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        None
+    }
+}
+
+pub fn default_error_response<T: ApiError>(error: &T) -> HttpResponse {
+    let json_error = JsonError::from_error(error);
     HttpResponse::build(error.status_code())
         .insert_header((header::CONTENT_TYPE, "application/json"))
         .json(json_error)
 }
+
+/// Documents the statuses an error responds with, every one of them carries a [`JsonError`].
+fn json_error_responses(responses: &[(StatusCode, &str)]) -> BTreeMap<String, RefOr<Response>> {
+    responses
+        .iter()
+        .map(|(status, description)| {
+            let response = ResponseBuilder::new()
+                .description(*description)
+                .content(
+                    "application/json",
+                    Content::new(Ref::from_schema_name("JsonError")),
+                )
+                .build();
+
+            (status.as_str().to_string(), response.into())
+        })
+        .collect()
+}
@@ -1,15 +1,22 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter, Result};
 
-use crate::errors::JsonError;
+use crate::domain::ValidationErrors;
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
 
-use crate::errors::error_chain_fmt;
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 
 #[derive(thiserror::Error)]
 pub enum AskError {
     #[error("{0}")]
-    ValidationError(String),
+    ValidationError(ValidationErrors),
+    #[error("{0}")]
+    NotFound(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -23,17 +30,43 @@ impl Debug for AskError {
 impl actix_web::ResponseError for AskError {
     fn status_code(&self) -> StatusCode {
         match self {
-            AskError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            AskError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AskError::NotFound(_) => StatusCode::NOT_FOUND,
             AskError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for AskError {
+    fn code(&self) -> &'static str {
         match self {
-            AskError::ValidationError(_) => HttpResponse::BadRequest().json(JsonError::new(self)),
-            AskError::UnexpectedError(_) => {
-                HttpResponse::InternalServerError().json(JsonError::new(self))
-            }
+            AskError::ValidationError(e) => e.first().code,
+            AskError::NotFound(_) => "asks.not_found",
+            AskError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
         }
     }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            AskError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for AskError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The query or the ask is invalid.",
+            ),
+            (StatusCode::NOT_FOUND, "The ask doesn't exist."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
 }
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Result};
+
+use crate::domain::ValidationErrors;
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
+
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
+
+#[derive(thiserror::Error)]
+pub enum AuditLogError {
+    #[error("{0}")]
+    ValidationError(ValidationErrors),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl Debug for AuditLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl actix_web::ResponseError for AuditLogError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuditLogError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AuditLogError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for AuditLogError {
+    fn code(&self) -> &'static str {
+        match self {
+            AuditLogError::ValidationError(e) => e.first().code,
+            AuditLogError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            AuditLogError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for AuditLogError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (StatusCode::UNPROCESSABLE_ENTITY, "The query is invalid."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
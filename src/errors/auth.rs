@@ -1,6 +1,13 @@
+use std::collections::BTreeMap;
+
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
-use crate::errors::JsonError;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
+
+use crate::errors::{
+    default_error_response, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum AuthError {
@@ -8,6 +15,8 @@ pub enum AuthError {
     BadRequest(#[source] anyhow::Error),
     #[error("Invalid credentials")]
     InvalidCredentials(#[source] anyhow::Error),
+    #[error("Forbidden")]
+    Forbidden(#[source] anyhow::Error),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -17,19 +26,34 @@ impl actix_web::ResponseError for AuthError {
         match self {
             AuthError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AuthError::InvalidCredentials(_) => StatusCode::UNAUTHORIZED,
+            AuthError::Forbidden(_) => StatusCode::FORBIDDEN,
             AuthError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for AuthError {
+    fn code(&self) -> &'static str {
         match self {
-            AuthError::BadRequest(_) => HttpResponse::BadRequest().json(JsonError::new(self)),
-            AuthError::InvalidCredentials(_) => {
-                HttpResponse::Unauthorized().json(JsonError::new(self))
-            }
-            AuthError::UnexpectedError(_) => {
-                HttpResponse::InternalServerError().json(JsonError::new(self))
-            }
+            AuthError::BadRequest(_) => "auth.malformed_credentials",
+            AuthError::InvalidCredentials(_) => "auth.invalid_credentials",
+            AuthError::Forbidden(_) => "auth.forbidden",
+            AuthError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
         }
     }
 }
+
+impl IntoResponses for AuthError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (StatusCode::BAD_REQUEST, "Missing or malformed credentials."),
+            (StatusCode::UNAUTHORIZED, "The credentials are invalid."),
+            (StatusCode::FORBIDDEN, "Forbidden for the user."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
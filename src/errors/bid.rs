@@ -1,15 +1,22 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter, Result};
 
-use crate::errors::JsonError;
+use crate::domain::ValidationErrors;
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
 
-use crate::errors::error_chain_fmt;
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 
 #[derive(thiserror::Error)]
 pub enum BidError {
     #[error("{0}")]
-    ValidationError(String),
+    ValidationError(ValidationErrors),
+    #[error("{0}")]
+    NotFound(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -23,17 +30,43 @@ impl Debug for BidError {
 impl actix_web::ResponseError for BidError {
     fn status_code(&self) -> StatusCode {
         match self {
-            BidError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            BidError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            BidError::NotFound(_) => StatusCode::NOT_FOUND,
             BidError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for BidError {
+    fn code(&self) -> &'static str {
         match self {
-            BidError::ValidationError(_) => HttpResponse::BadRequest().json(JsonError::new(self)),
-            BidError::UnexpectedError(_) => {
-                HttpResponse::InternalServerError().json(JsonError::new(self))
-            }
+            BidError::ValidationError(e) => e.first().code,
+            BidError::NotFound(_) => "bids.not_found",
+            BidError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
         }
     }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            BidError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for BidError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The query or the bid is invalid.",
+            ),
+            (StatusCode::NOT_FOUND, "The bid doesn't exist."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
 }
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use crate::domain::ValidationErrors;
+use crate::errors::{
+    default_error_response, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
+
+#[derive(thiserror::Error)]
+pub enum CollectionError {
+    #[error("{0}")]
+    ValidationError(ValidationErrors),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CollectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::errors::error_chain_fmt(self, f)
+    }
+}
+
+impl actix_web::ResponseError for CollectionError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CollectionError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            CollectionError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for CollectionError {
+    fn code(&self) -> &'static str {
+        match self {
+            CollectionError::ValidationError(e) => e.first().code,
+            CollectionError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            CollectionError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for CollectionError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The kind or the query is invalid.",
+            ),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
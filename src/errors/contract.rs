@@ -1,13 +1,24 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter, Result};
 
-use crate::errors::JsonError;
+use crate::domain::ValidationErrors;
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
 
-use crate::errors::error_chain_fmt;
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 
 #[derive(thiserror::Error)]
 pub enum ContractError {
+    #[error("{0}")]
+    ValidationError(ValidationErrors),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    PreconditionFailed(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -21,15 +32,66 @@ impl Debug for ContractError {
 impl actix_web::ResponseError for ContractError {
     fn status_code(&self) -> StatusCode {
         match self {
+            ContractError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ContractError::NotFound(_) => StatusCode::NOT_FOUND,
+            ContractError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
             ContractError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for ContractError {
+    fn code(&self) -> &'static str {
+        match self {
+            ContractError::ValidationError(e) => e.first().code,
+            ContractError::NotFound(_) => "contracts.not_found",
+            ContractError::PreconditionFailed(_) => "contracts.precondition_failed",
+            ContractError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
         match self {
-            ContractError::UnexpectedError(_) => {
-                HttpResponse::InternalServerError().json(JsonError::new(self))
-            }
+            ContractError::ValidationError(e) => Some(e),
+            _ => None,
         }
     }
 }
+
+impl IntoResponses for ContractError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (StatusCode::UNPROCESSABLE_ENTITY, "The query is invalid."),
+            (
+                StatusCode::NOT_FOUND,
+                "The contracts ids aren't configured.",
+            ),
+            (
+                StatusCode::PRECONDITION_FAILED,
+                "The `If-Match` entity tag isn't the active version.",
+            ),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
+
+/// The responses of the [`ContractError`]s that the read-only routes can return,
+/// they never check an `If-Match` precondition.
+pub struct ContractReadError;
+
+impl IntoResponses for ContractReadError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (StatusCode::UNPROCESSABLE_ENTITY, "The query is invalid."),
+            (
+                StatusCode::NOT_FOUND,
+                "The contracts ids aren't configured.",
+            ),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
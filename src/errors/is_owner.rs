@@ -1,15 +1,20 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter, Result};
 
-use crate::errors::JsonError;
+use crate::domain::ValidationErrors;
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
 
-use crate::errors::error_chain_fmt;
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 
 #[derive(thiserror::Error)]
 pub enum IsOwnerError {
     #[error("{0}")]
-    ValidationError(String),
+    ValidationError(ValidationErrors),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -23,19 +28,40 @@ impl Debug for IsOwnerError {
 impl actix_web::ResponseError for IsOwnerError {
     fn status_code(&self) -> StatusCode {
         match self {
-            IsOwnerError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            IsOwnerError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             IsOwnerError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for IsOwnerError {
+    fn code(&self) -> &'static str {
+        match self {
+            IsOwnerError::ValidationError(e) => e.first().code,
+            IsOwnerError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
         match self {
-            IsOwnerError::ValidationError(_) => {
-                HttpResponse::BadRequest().json(JsonError::new(self))
-            }
-            IsOwnerError::UnexpectedError(_) => {
-                HttpResponse::InternalServerError().json(JsonError::new(self))
-            }
+            IsOwnerError::ValidationError(e) => Some(e),
+            _ => None,
         }
     }
 }
+
+impl IntoResponses for IsOwnerError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The token ids are invalid.",
+            ),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
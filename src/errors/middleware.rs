@@ -0,0 +1,67 @@
+use actix_web::body::{self, BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::{error, HttpMessage, HttpResponse};
+use actix_web_lab::middleware::Next;
+use serde_json::Value;
+use tracing_actix_web::RequestId;
+
+/// Tags the json error responses with the id of the request, which its logs are tagged with.
+pub async fn error_request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let request_id = req.extensions().get::<RequestId>().map(ToString::to_string);
+    let request_id = match request_id {
+        Some(request_id) => request_id,
+        None => {
+            return next
+                .call(req)
+                .await
+                .map(ServiceResponse::map_into_boxed_body)
+        }
+    };
+
+    match next.call(req).await {
+        Ok(response) => {
+            let (req, response) = response.map_into_boxed_body().into_parts();
+            let response = tag_json_error(response, request_id).await?;
+            Ok(ServiceResponse::new(req, response))
+        }
+        // the errors of the middlewares, e.g. `auth`, aren't rendered into responses yet
+        Err(e) => {
+            let response = tag_json_error(e.error_response(), request_id).await?;
+            Err(error::InternalError::from_response(e.to_string(), response).into())
+        }
+    }
+}
+
+async fn tag_json_error(
+    response: HttpResponse,
+    request_id: String,
+) -> Result<HttpResponse, actix_web::Error> {
+    if !is_json_error(&response) {
+        return Ok(response);
+    }
+
+    let (mut response, body) = response.into_parts();
+    let bytes = body::to_bytes(body)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut error)) => {
+            error.insert("request_id".to_string(), Value::String(request_id));
+            serde_json::to_vec(&error).expect("Json object is always serializable")
+        }
+        _ => bytes.to_vec(),
+    };
+    response.headers_mut().remove(header::CONTENT_LENGTH);
+
+    Ok(response.set_body(BoxBody::new(body)))
+}
+
+fn is_json_error(response: &HttpResponse) -> bool {
+    (response.status().is_client_error() || response.status().is_server_error())
+        && response.headers().get(header::CONTENT_TYPE)
+            == Some(&HeaderValue::from_static("application/json"))
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Result};
+
+use crate::domain::ValidationErrors;
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use serde_json::{json, Value};
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
+
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
+
+#[derive(thiserror::Error)]
+pub enum NetworkError {
+    #[error("{0}")]
+    ValidationError(ValidationErrors),
+    #[error("The network `{0}` isn't served.")]
+    UnknownNetwork(String),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl Debug for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl actix_web::ResponseError for NetworkError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            NetworkError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            NetworkError::UnknownNetwork(_) => StatusCode::NOT_FOUND,
+            NetworkError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for NetworkError {
+    fn code(&self) -> &'static str {
+        match self {
+            NetworkError::ValidationError(e) => e.first().code,
+            NetworkError::UnknownNetwork(_) => "network.unknown",
+            NetworkError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn details(&self) -> Option<Value> {
+        match self {
+            NetworkError::UnknownNetwork(network) => Some(json!({ "network": network })),
+            _ => None,
+        }
+    }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            NetworkError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for NetworkError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The network header is malformed.",
+            ),
+            (StatusCode::NOT_FOUND, "The network isn't served."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
@@ -1,11 +1,22 @@
-use crate::errors::JsonError;
+use std::collections::BTreeMap;
+
+use crate::domain::ValidationErrors;
+use crate::errors::{
+    default_error_response, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
 
 #[derive(thiserror::Error)]
 pub enum NftTokensError {
     #[error("{0}")]
-    ValidationError(String),
+    ValidationError(ValidationErrors),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    PreconditionFailed(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -19,19 +30,63 @@ impl std::fmt::Debug for NftTokensError {
 impl actix_web::ResponseError for NftTokensError {
     fn status_code(&self) -> StatusCode {
         match self {
-            NftTokensError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            NftTokensError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            NftTokensError::NotFound(_) => StatusCode::NOT_FOUND,
+            NftTokensError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
             NftTokensError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for NftTokensError {
+    fn code(&self) -> &'static str {
         match self {
-            NftTokensError::ValidationError(_) => {
-                HttpResponse::BadRequest().json(JsonError::new(self))
-            }
-            NftTokensError::UnexpectedError(_) => {
-                HttpResponse::InternalServerError().json(JsonError::new(self))
-            }
+            NftTokensError::ValidationError(e) => e.first().code,
+            NftTokensError::NotFound(_) => "nft_tokens.not_found",
+            NftTokensError::PreconditionFailed(_) => "nft_tokens.precondition_failed",
+            NftTokensError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
         }
     }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            NftTokensError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for NftTokensError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The query or the tokens are invalid.",
+            ),
+            (
+                StatusCode::PRECONDITION_FAILED,
+                "The `If-Match` entity tag isn't the token's version.",
+            ),
+            (StatusCode::NOT_FOUND, "The nft token doesn't exist."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
+
+/// The responses of the [`NftTokensError`]s that the read-only routes can return,
+/// they never check an `If-Match` precondition.
+pub struct NftTokensReadError;
+
+impl IntoResponses for NftTokensReadError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (StatusCode::UNPROCESSABLE_ENTITY, "The query is invalid."),
+            (StatusCode::NOT_FOUND, "The nft token doesn't exist."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
 }
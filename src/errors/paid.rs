@@ -1,13 +1,19 @@
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug, Formatter};
 
 use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
 
-use crate::errors::{self, error_chain_fmt};
+use crate::domain::ValidationErrors;
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 
 #[derive(thiserror::Error)]
 pub enum PaidError {
     #[error("{0}")]
-    ValidationError(String),
+    ValidationError(ValidationErrors),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -21,12 +27,37 @@ impl Debug for PaidError {
 impl ResponseError for PaidError {
     fn status_code(&self) -> StatusCode {
         match self {
-            PaidError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            PaidError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             PaidError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
-        errors::default_error_response(self)
+        default_error_response(self)
+    }
+}
+
+impl ApiError for PaidError {
+    fn code(&self) -> &'static str {
+        match self {
+            PaidError::ValidationError(e) => e.first().code,
+            PaidError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            PaidError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for PaidError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (StatusCode::UNPROCESSABLE_ENTITY, "The query is invalid."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
     }
 }
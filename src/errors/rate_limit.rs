@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Result};
+
+use crate::errors::JsonError;
+use crate::rate_limit::Quota;
+use actix_web::http::{header, StatusCode};
+use actix_web::HttpResponse;
+use serde_json::{json, Value};
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
+
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
+
+#[derive(thiserror::Error)]
+pub enum RateLimitError {
+    #[error("Too many requests, retry in {} seconds.", .0.retry_after)]
+    TooManyRequests(Quota),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl Debug for RateLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl actix_web::ResponseError for RateLimitError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RateLimitError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            RateLimitError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            RateLimitError::TooManyRequests(quota) => {
+                let mut response = HttpResponse::TooManyRequests();
+                for h in quota.headers() {
+                    response.insert_header(h);
+                }
+                response
+                    .insert_header((header::RETRY_AFTER, quota.retry_after))
+                    .json(JsonError::from_error(self))
+            }
+            RateLimitError::UnexpectedError(_) => default_error_response(self),
+        }
+    }
+}
+
+impl ApiError for RateLimitError {
+    fn code(&self) -> &'static str {
+        match self {
+            RateLimitError::TooManyRequests(_) => "rate_limit.exceeded",
+            RateLimitError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn details(&self) -> Option<Value> {
+        match self {
+            RateLimitError::TooManyRequests(quota) => {
+                Some(json!({ "retry_after": quota.retry_after }))
+            }
+            RateLimitError::UnexpectedError(_) => None,
+        }
+    }
+}
+
+impl IntoResponses for RateLimitError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                "The request budget is exhausted.",
+            ),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
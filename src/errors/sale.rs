@@ -1,15 +1,22 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter, Result};
 
-use crate::errors::JsonError;
+use crate::domain::ValidationErrors;
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
 
-use crate::errors::error_chain_fmt;
+use crate::errors::{
+    default_error_response, error_chain_fmt, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
 
 #[derive(thiserror::Error)]
 pub enum SaleError {
     #[error("{0}")]
-    ValidationError(String),
+    ValidationError(ValidationErrors),
+    #[error("{0}")]
+    NotFound(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -23,17 +30,43 @@ impl Debug for SaleError {
 impl actix_web::ResponseError for SaleError {
     fn status_code(&self) -> StatusCode {
         match self {
-            SaleError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            SaleError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            SaleError::NotFound(_) => StatusCode::NOT_FOUND,
             SaleError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for SaleError {
+    fn code(&self) -> &'static str {
         match self {
-            SaleError::ValidationError(_) => HttpResponse::BadRequest().json(JsonError::new(self)),
-            SaleError::UnexpectedError(_) => {
-                HttpResponse::InternalServerError().json(JsonError::new(self))
-            }
+            SaleError::ValidationError(e) => e.first().code,
+            SaleError::NotFound(_) => "sales.not_found",
+            SaleError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
         }
     }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            SaleError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for SaleError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The query or the sale is invalid.",
+            ),
+            (StatusCode::NOT_FOUND, "The sale doesn't exist."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
 }
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use crate::domain::ValidationErrors;
+use crate::errors::{
+    default_error_response, json_error_responses, ApiError, UNEXPECTED_ERROR_CODE,
+};
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use utoipa::openapi::{RefOr, Response};
+use utoipa::IntoResponses;
+
+#[derive(thiserror::Error)]
+pub enum SearchError {
+    #[error("{0}")]
+    ValidationError(ValidationErrors),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::errors::error_chain_fmt(self, f)
+    }
+}
+
+impl actix_web::ResponseError for SearchError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SearchError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            SearchError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        default_error_response(self)
+    }
+}
+
+impl ApiError for SearchError {
+    fn code(&self) -> &'static str {
+        match self {
+            SearchError::ValidationError(e) => e.first().code,
+            SearchError::UnexpectedError(_) => UNEXPECTED_ERROR_CODE,
+        }
+    }
+
+    fn validation_errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            SearchError::ValidationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponses for SearchError {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        json_error_responses(&[
+            (StatusCode::UNPROCESSABLE_ENTITY, "The query is invalid."),
+            (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error."),
+        ])
+    }
+}
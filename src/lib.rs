@@ -1,6 +1,14 @@
+pub mod api_version;
+pub mod audit;
+pub mod cache;
 pub mod config;
 pub mod domain;
 pub mod errors;
+pub mod media;
+pub mod network;
+pub mod openapi;
+pub mod preview;
+pub mod rate_limit;
 pub mod routes;
 pub mod startup;
 pub mod telemetry;
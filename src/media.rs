@@ -0,0 +1,194 @@
+use crate::config::{ContractMetadataSettings, MediaSettings};
+use crate::routes::{ContractScoped, NftTokenRow};
+
+/// Resolves the media of the tokens, as emitted by the contracts, to absolute http urls.
+#[derive(Debug, Clone)]
+pub struct MediaResolver {
+    ipfs_gateways: Vec<String>,
+    arweave_gateway: String,
+    contract_metadata: ContractMetadataSettings,
+}
+
+impl MediaResolver {
+    pub fn new(media: MediaSettings, contract_metadata: ContractMetadataSettings) -> Self {
+        Self {
+            ipfs_gateways: media
+                .ipfs_gateways
+                .iter()
+                .map(String::as_str)
+                .map(with_slash)
+                .collect(),
+            arweave_gateway: with_slash(&media.arweave_gateway),
+            contract_metadata,
+        }
+    }
+
+    /// Sets the resolved `media_url` of the tokens.
+    pub fn resolve_all(&self, nft_tokens: &mut [ContractScoped<NftTokenRow>]) {
+        for nft_token in nft_tokens {
+            self.resolve_token(nft_token);
+        }
+    }
+
+    pub fn resolve_token(&self, nft_token: &mut ContractScoped<NftTokenRow>) {
+        nft_token.item.media_url =
+            self.resolve(&nft_token.contract_id, &nft_token.item.nft_token.media);
+    }
+
+    /// The http url of the media, missing if it's relative and neither the contract has a
+    /// `base_uri` nor the media starts with an IPFS CID.
+    ///
+    /// The `ipfs://` and `ar://` urls and the urls of the known IPFS gateways are rewritten to
+    /// the preferred gateway, the other http urls are kept.
+    pub fn resolve(&self, contract_id: &str, media: &str) -> Option<String> {
+        if media.contains("://") || media.starts_with("data:") {
+            return self.resolve_absolute(media);
+        }
+        let base_uri = self
+            .contract_metadata
+            .metadata(contract_id)
+            .base_uri
+            .as_deref();
+        match base_uri {
+            Some(base_uri) => self.resolve_absolute(&format!(
+                "{}{}",
+                with_slash(base_uri),
+                media.trim_start_matches('/')
+            )),
+            None if is_cid(media.split('/').next().unwrap_or_default()) => self.ipfs_url(media),
+            None => None,
+        }
+    }
+
+    fn resolve_absolute(&self, url: &str) -> Option<String> {
+        if let Some(path) = url.strip_prefix("ipfs://") {
+            return self.ipfs_url(path.strip_prefix("ipfs/").unwrap_or(path));
+        }
+        if let Some(id) = url.strip_prefix("ar://") {
+            return Some(format!("{}{id}", self.arweave_gateway));
+        }
+        let hosted = self
+            .ipfs_gateways
+            .iter()
+            .skip(1)
+            .find_map(|gateway| url.strip_prefix(gateway.as_str()));
+        match hosted {
+            Some(path) => self.ipfs_url(path),
+            None if url.starts_with("https://") || url.starts_with("http://") => {
+                Some(url.to_string())
+            }
+            None if url.starts_with("data:") => Some(url.to_string()),
+            None => None,
+        }
+    }
+
+    fn ipfs_url(&self, path: &str) -> Option<String> {
+        self.ipfs_gateways
+            .first()
+            .map(|gateway| format!("{gateway}{path}"))
+    }
+}
+
+fn with_slash(url: &str) -> String {
+    format!("{}/", url.trim_end_matches('/'))
+}
+
+/// Whether the path segment is a CIDv0, e.g. `Qm...`, or a base32 CIDv1, e.g. `bafy...`.
+fn is_cid(segment: &str) -> bool {
+    let v0 = segment.len() == 46
+        && segment.starts_with("Qm")
+        && segment.chars().all(|ch| ch.is_ascii_alphanumeric());
+    let v1 = segment.len() >= 59
+        && segment.starts_with('b')
+        && segment
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ('2'..='7').contains(&ch));
+
+    v0 || v1
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::config::ContractMetadata;
+
+    use super::*;
+
+    const CID: &str = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+    fn resolver(base_uri: Option<&str>) -> MediaResolver {
+        let metadata = ContractMetadata {
+            name: "Battlemon".to_string(),
+            symbol: "LEMON".to_string(),
+            icon: None,
+            base_uri: None,
+        };
+        let media = MediaSettings {
+            ipfs_gateways: vec![
+                "https://ipfs.io/ipfs".to_string(),
+                "https://cloudflare-ipfs.com/ipfs/".to_string(),
+            ],
+            arweave_gateway: "https://arweave.net/".to_string(),
+        };
+        let contracts = HashMap::from([(
+            "nft.battlemon.near".to_string(),
+            ContractMetadata {
+                base_uri: base_uri.map(str::to_string),
+                ..metadata.clone()
+            },
+        )]);
+
+        MediaResolver::new(
+            media,
+            ContractMetadataSettings {
+                default: metadata,
+                contracts,
+            },
+        )
+    }
+
+    #[test]
+    fn absolute_media_are_rewritten_to_the_preferred_gateway() {
+        let resolver = resolver(None);
+        let ipfs_url = format!("https://ipfs.io/ipfs/{CID}/1.png");
+        let test_cases = [
+            (format!("ipfs://{CID}/1.png"), ipfs_url.as_str()),
+            (format!("ipfs://ipfs/{CID}/1.png"), ipfs_url.as_str()),
+            (
+                format!("https://cloudflare-ipfs.com/ipfs/{CID}/1.png"),
+                ipfs_url.as_str(),
+            ),
+            ("ar://abc".to_string(), "https://arweave.net/abc"),
+            (
+                "https://example.com/1.png".to_string(),
+                "https://example.com/1.png",
+            ),
+        ];
+        for (media, expected) in test_cases {
+            let actual = resolver.resolve("nft.battlemon.near", &media);
+            assert_eq!(actual.as_deref(), Some(expected), "`{media}`");
+        }
+    }
+
+    #[test]
+    fn relative_media_are_resolved_against_the_base_uri() {
+        let resolver = resolver(Some(
+            "ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/",
+        ));
+        assert_eq!(
+            resolver.resolve("nft.battlemon.near", "/1.png").as_deref(),
+            Some("https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1.png")
+        );
+        assert_eq!(
+            resolver.resolve("items.battlemon.near", CID).as_deref(),
+            Some(format!("https://ipfs.io/ipfs/{CID}").as_str()),
+            "The bare CID isn't resolved without a base uri"
+        );
+        assert_eq!(resolver.resolve("items.battlemon.near", "1.png"), None);
+        assert_eq!(
+            resolver.resolve("items.battlemon.near", "ftp://a/1.png"),
+            None
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt::{self, Display, Formatter};
+use std::future::{ready, Ready};
+
+use actix_web::dev::Payload;
+use actix_web::{web, FromRequest, HttpRequest};
+use anyhow::Context;
+
+use crate::config::NetworkSettings;
+use crate::domain::FieldError;
+use crate::errors::NetworkError;
+
+/// The network a request operates on, taken from the `networks/{network}` path prefix,
+/// then from the configured header, falling back to the default network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Network {
+    id: String,
+    nft_contracts: Vec<String>,
+}
+
+impl Network {
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// The nft contracts configured for the network in the settings.
+    pub fn nft_contracts(&self) -> &[String] {
+        &self.nft_contracts
+    }
+
+    pub(crate) fn extract(req: &HttpRequest) -> Result<Self, NetworkError> {
+        let settings = req
+            .app_data::<web::Data<NetworkSettings>>()
+            .context("Failed to get network settings from application data.")?;
+
+        let network = match req.match_info().get("network") {
+            Some(network) => network.to_string(),
+            None => match req.headers().get(settings.header.as_str()) {
+                Some(value) => value
+                    .to_str()
+                    .map_err(|_| {
+                        NetworkError::ValidationError(
+                            FieldError::new(
+                                settings.header.as_str(),
+                                "validation.network_header_malformed",
+                                format!("The `{}` header must be a valid string.", settings.header),
+                            )
+                            .into(),
+                        )
+                    })?
+                    .to_string(),
+                None => settings.default.clone(),
+            },
+        };
+
+        if !settings.is_known(&network) {
+            return Err(NetworkError::UnknownNetwork(network));
+        }
+
+        Ok(Self {
+            nft_contracts: settings.nft_contracts(&network).to_vec(),
+            id: network,
+        })
+    }
+}
+
+impl Display for Network {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl FromRequest for Network {
+    type Error = NetworkError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Self::extract(req))
+    }
+}
@@ -0,0 +1,355 @@
+use utoipa::openapi::path::{ParameterBuilder, ParameterIn};
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::openapi::{ObjectBuilder, Required, SchemaType};
+use utoipa::{Modify, OpenApi};
+
+use crate::audit::AuditEntry;
+use crate::errors::{JsonError, JsonFieldError};
+use crate::preview::PreviewFormat;
+use crate::routes::{
+    self, AsksReport, AuditLogReport, BidsReport, CollectionTraits, ContractAsk,
+    ContractAskPayload, ContractBid, ContractBidPayload, ContractNftToken, ContractNftTokenPayload,
+    ContractSale, ContractSalePayload, ContractsHistoryReport, ContractsRollback, ContractsVersion,
+    IsOwnerReport, KindFacet, NftContractMetadata, NftTokenRow, NftTokensReport, Ownership, Rarity,
+    SalesReport, SearchFacets, SearchReport, TokenMetadata, TokenOwnership, TraitDistribution,
+    TraitFacet, TraitValueCount,
+};
+
+/// The paths which don't depend on the network.
+const NETWORK_INDEPENDENT_PATHS: [&str; 2] = ["/health_check", "/audit_log"];
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Battlemon REST API",
+        description = "Every network dependent path is also served under the \
+        `/networks/{network}` prefix, which takes precedence over the `X-Network` header. \
+        The unversioned root aliases of the `/v1` paths are deprecated, their responses carry \
+        the `Deprecation` and `Sunset` headers."
+    ),
+    servers((url = "/v1", description = "The current version of the API.")),
+    paths(
+        routes::health_check,
+        routes::get_contracts,
+        routes::insert_contracts,
+        routes::get_contracts_history,
+        routes::rollback_contracts,
+        routes::get_contract_metadata,
+        routes::paid,
+        routes::get_sales,
+        routes::get_sale,
+        routes::insert_sale,
+        routes::get_nft_tokens,
+        routes::get_nft_token,
+        routes::get_nft_token_metadata,
+        routes::get_nft_token_preview,
+        routes::insert_nft_token,
+        routes::update_nft_token,
+        routes::search,
+        routes::get_collection_traits,
+        routes::get_asks,
+        routes::get_ask,
+        routes::insert_ask,
+        routes::delete_ask,
+        routes::get_bids,
+        routes::get_bid,
+        routes::insert_bid,
+        routes::delete_bid,
+        routes::get_audit_log,
+        routes::is_owner,
+        routes::is_token_owner,
+    ),
+    components(schemas(
+        JsonError,
+        JsonFieldError,
+        models::AskForDb,
+        models::AskForRest,
+        models::BidForDb,
+        models::BidForRest,
+        models::ContractConfig,
+        models::NftTokenForRest,
+        models::Paid,
+        models::SaleForDb,
+        models::SaleForRest,
+        AsksReport,
+        AuditEntry,
+        AuditLogReport,
+        BidsReport,
+        CollectionTraits,
+        ContractAsk,
+        ContractAskPayload,
+        ContractBid,
+        ContractBidPayload,
+        ContractNftToken,
+        ContractNftTokenPayload,
+        ContractSale,
+        ContractSalePayload,
+        ContractsHistoryReport,
+        ContractsRollback,
+        ContractsVersion,
+        IsOwnerReport,
+        NftContractMetadata,
+        NftTokenRow,
+        PreviewFormat,
+        NftTokensReport,
+        Ownership,
+        Rarity,
+        SalesReport,
+        KindFacet,
+        SearchFacets,
+        SearchReport,
+        TraitDistribution,
+        TokenMetadata,
+        TokenOwnership,
+        TraitFacet,
+        TraitValueCount,
+    )),
+    modifiers(&BasicAuth, &NetworkHeader)
+)]
+pub struct ApiDoc;
+
+struct BasicAuth;
+
+impl Modify for BasicAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "basic_auth",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Basic)),
+        );
+    }
+}
+
+struct NetworkHeader;
+
+impl Modify for NetworkHeader {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let header = ParameterBuilder::new()
+            .name("X-Network")
+            .parameter_in(ParameterIn::Header)
+            .required(Required::False)
+            .description(Some(
+                "The network to operate on, e.g. `testnet`, the default network is used when omitted.",
+            ))
+            .schema(Some(ObjectBuilder::new().schema_type(SchemaType::String)))
+            .build();
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if NETWORK_INDEPENDENT_PATHS.contains(&path.as_str()) {
+                continue;
+            }
+            item.parameters
+                .get_or_insert_with(Vec::new)
+                .push(header.clone());
+        }
+    }
+}
+
+/// Schemas of the `battlemon_models` types, which don't describe themselves, mirrored by local
+/// types checked to serialize as the models do.
+pub mod models {
+    use chrono::{DateTime, Utc};
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+    use utoipa::ToSchema;
+
+    /// See `battlemon_models::market::ask::AskForDb`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct AskForDb {
+        pub id: String,
+        pub token_id: String,
+        pub account_id: String,
+        pub approval_id: i64,
+        pub price: Decimal,
+    }
+
+    /// See `battlemon_models::market::ask::AskForRest`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct AskForRest {
+        pub id: String,
+        pub token_id: String,
+        pub account_id: String,
+        pub approval_id: i64,
+        pub price: Decimal,
+    }
+
+    /// See `battlemon_models::market::bid::BidForDb`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct BidForDb {
+        pub id: String,
+        pub token_id: String,
+        pub account_id: String,
+        /// The bid never expires if it's null.
+        pub expire_at: Option<DateTime<Utc>>,
+        pub create_at: DateTime<Utc>,
+        pub price: Decimal,
+    }
+
+    /// See `battlemon_models::market::bid::BidForRest`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct BidForRest {
+        pub id: String,
+        pub token_id: String,
+        pub account_id: String,
+        /// The bid never expires if it's null.
+        pub expire_at: Option<DateTime<Utc>>,
+        pub create_at: DateTime<Utc>,
+        pub price: Decimal,
+    }
+
+    /// See `battlemon_models::config::ContractConfig`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct ContractConfig {
+        pub nft_contract_id: String,
+        pub market_contract_id: String,
+    }
+
+    /// See `battlemon_models::nft::NftTokenForRest`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct NftTokenForRest {
+        pub owner_id: String,
+        pub token_id: String,
+        pub title: Option<String>,
+        pub description: Option<String>,
+        pub media: String,
+        pub media_hash: Option<String>,
+        pub copies: Option<String>,
+        pub issued_at: Option<String>,
+        pub expires_at: Option<String>,
+        /// The traits of the token tagged by the `kind` of its model, e.g. `lemon`.
+        #[schema(value_type = Object)]
+        pub model: serde_json::Value,
+    }
+
+    /// See `battlemon_models::market::paid::Paid`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct Paid {
+        pub history: Vec<SaleForDb>,
+        pub total_trade_volume: Decimal,
+        pub trades_number: usize,
+        pub top_trade: Decimal,
+    }
+
+    /// See `battlemon_models::market::sale::SaleForDb`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct SaleForDb {
+        pub id: i64,
+        pub prev_owner: String,
+        pub curr_owner: String,
+        pub token_id: String,
+        pub price: Decimal,
+        pub date: DateTime<Utc>,
+    }
+
+    /// See `battlemon_models::market::sale::SaleForRest`.
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct SaleForRest {
+        pub prev_owner: String,
+        pub curr_owner: String,
+        pub token_id: String,
+        pub price: Decimal,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use battlemon_models::config;
+        use battlemon_models::market::{ask, bid, paid, sale};
+        use battlemon_models::nft::{self, FromTraitWeights, Lemon, ModelKind};
+        use serde::de::DeserializeOwned;
+        use serde_json::Value;
+        use sqlx::types::Json;
+
+        use super::*;
+
+        /// The model is read by its mirror and written back as it was.
+        fn assert_mirrored<M: DeserializeOwned + Serialize>(model: impl Serialize) {
+            let expected = serde_json::to_value(model).unwrap();
+            let mirror: M = serde_json::from_value(expected.clone())
+                .unwrap_or_else(|e| panic!("The mirror doesn't read {expected}: {e}"));
+            let actual: Value = serde_json::to_value(mirror).unwrap();
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn mirrors_serialize_as_the_models() {
+            let price = Decimal::new(1050, 2);
+            let date: DateTime<Utc> = "2022-09-01T12:00:00Z".parse().unwrap();
+            let ask = ask::AskForDb {
+                id: "ask-1".to_string(),
+                token_id: "1".to_string(),
+                account_id: "alice.near".to_string(),
+                approval_id: 1,
+                price,
+            };
+            let bid = bid::BidForDb {
+                id: "bid-1".to_string(),
+                token_id: "1".to_string(),
+                account_id: "bob.near".to_string(),
+                expire_at: Some(date),
+                create_at: date,
+                price,
+            };
+            let sale = sale::SaleForDb {
+                id: 1,
+                prev_owner: "alice.near".to_string(),
+                curr_owner: "bob.near".to_string(),
+                token_id: "1".to_string(),
+                price,
+                date,
+            };
+            let model = Lemon::from_trait_weights(&"".to_string(), &[1, 1, 1, 1, 1]);
+            let nft_token = nft::NftTokenForRest {
+                owner_id: "alice.near".to_string(),
+                token_id: "1".to_string(),
+                title: Some("Lemon".to_string()),
+                description: None,
+                media: "lemon.png".to_string(),
+                media_hash: None,
+                copies: Some("1".to_string()),
+                issued_at: None,
+                expires_at: None,
+                model: Json(ModelKind::Lemon(model)),
+            };
+
+            assert_mirrored::<AskForRest>(ask::AskForRest {
+                id: ask.id.clone(),
+                token_id: ask.token_id.clone(),
+                account_id: ask.account_id.clone(),
+                approval_id: ask.approval_id,
+                price,
+            });
+            assert_mirrored::<AskForDb>(ask);
+            assert_mirrored::<BidForRest>(bid::BidForRest {
+                id: bid.id.clone(),
+                token_id: bid.token_id.clone(),
+                account_id: bid.account_id.clone(),
+                expire_at: None,
+                create_at: date,
+                price,
+            });
+            assert_mirrored::<BidForDb>(bid);
+            assert_mirrored::<SaleForRest>(sale::SaleForRest {
+                prev_owner: sale.prev_owner.clone(),
+                curr_owner: sale.curr_owner.clone(),
+                token_id: sale.token_id.clone(),
+                price,
+            });
+            assert_mirrored::<Paid>(paid::Paid::new(vec![sale.clone()], price, 1, price));
+            assert_mirrored::<SaleForDb>(sale);
+            assert_mirrored::<ContractConfig>(config::ContractConfig {
+                nft_contract_id: "nft.battlemon.near".to_string(),
+                market_contract_id: "market.battlemon.near".to_string(),
+            });
+            assert_mirrored::<NftTokenForRest>(nft_token);
+        }
+    }
+}
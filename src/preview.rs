@@ -0,0 +1,256 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use crate::config::PreviewSettings;
+
+/// The asset formats of a trait value, the first one found is layered.
+const ASSET_FORMATS: [(&str, &str); 2] = [("svg", "image/svg+xml"), ("png", "image/png")];
+
+/// The image format of a preview.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewFormat {
+    Svg,
+    /// The rasterized SVG preview.
+    Png,
+}
+
+impl PreviewFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PreviewFormat::Svg => "svg",
+            PreviewFormat::Png => "png",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            PreviewFormat::Svg => "image/svg+xml",
+            PreviewFormat::Png => "image/png",
+        }
+    }
+}
+
+/// Composes the previews of the tokens from the assets of their trait values.
+#[derive(Debug, Clone)]
+pub struct PreviewRenderer {
+    settings: PreviewSettings,
+}
+
+impl PreviewRenderer {
+    pub fn new(settings: PreviewSettings) -> Self {
+        Self { settings }
+    }
+
+    /// The hex-encoded sha256 hash of the model, equal models have equal previews.
+    pub fn model_hash(model: &Value) -> String {
+        // the objects of `serde_json` are ordered by key
+        let digest = Sha256::digest(model.to_string().as_bytes());
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// The preview of the model, missing if none of its trait values has an asset.
+    pub fn render(
+        &self,
+        model: &Value,
+        format: PreviewFormat,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let svg = match self.compose_svg(model)? {
+            Some(svg) => svg,
+            None => return Ok(None),
+        };
+
+        match format {
+            PreviewFormat::Svg => Ok(Some(svg.into_bytes())),
+            PreviewFormat::Png => self.rasterize(&svg).map(Some),
+        }
+    }
+
+    /// The SVG embedding the assets of the layers, the first one at the bottom.
+    pub fn compose_svg(&self, model: &Value) -> Result<Option<String>, anyhow::Error> {
+        let (width, height) = (self.settings.width, self.settings.height);
+        let mut images = Vec::new();
+        for (name, value) in self.layers(model) {
+            if let Some((content_type, asset)) = self.asset(model, &name, &value)? {
+                images.push(format!(
+                    r#"<image width="{width}" height="{height}" xlink:href="data:{content_type};base64,{}"/>"#,
+                    base64::encode(asset)
+                ));
+            }
+        }
+        if images.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{width}" height="{height}" viewBox="0 0 {width} {height}">{}</svg>"#,
+            images.concat()
+        )))
+    }
+
+    /// The trait names and values of the model in the order of their layers.
+    fn layers(&self, model: &Value) -> Vec<(String, String)> {
+        let kind = model["kind"].as_str().unwrap_or_default();
+        let order = self.settings.layers.get(kind);
+        let position = |name: &str| {
+            order
+                .and_then(|order| order.iter().position(|n| n == name))
+                .unwrap_or(usize::MAX)
+        };
+        let mut layers: Vec<_> = model
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| name.as_str() != "kind")
+            .filter_map(|(name, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(n) => n.to_string(),
+                    Value::Bool(b) => b.to_string(),
+                    _ => return None,
+                };
+                Some((name.clone(), value))
+            })
+            .collect();
+        layers.sort_by(|(a, _), (b, _)| (position(a), a).cmp(&(position(b), b)));
+
+        layers
+    }
+
+    /// The content type and the content of the asset of the trait value.
+    fn asset(
+        &self,
+        model: &Value,
+        name: &str,
+        value: &str,
+    ) -> Result<Option<(&'static str, Vec<u8>)>, anyhow::Error> {
+        let kind = model["kind"].as_str().unwrap_or_default();
+        if ![kind, name, value].iter().all(|part| is_path_segment(part)) {
+            return Ok(None);
+        }
+
+        for (extension, content_type) in ASSET_FORMATS {
+            let path: PathBuf = [
+                self.settings.asset_dir.as_path(),
+                kind.as_ref(),
+                name.as_ref(),
+                format!("{value}.{extension}").as_ref(),
+            ]
+            .iter()
+            .collect();
+            match std::fs::read(&path) {
+                Ok(asset) => return Ok(Some((content_type, asset))),
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read the asset {path:?}."))
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn rasterize(&self, svg: &str) -> Result<Vec<u8>, anyhow::Error> {
+        let options = usvg::Options::default();
+        let tree = usvg::Tree::from_str(svg, &options.to_ref())
+            .context("Failed to parse the composed preview.")?;
+        let mut pixmap = tiny_skia::Pixmap::new(self.settings.width, self.settings.height)
+            .context("The preview size is empty.")?;
+        resvg::render(
+            &tree,
+            usvg::FitTo::Original,
+            tiny_skia::Transform::default(),
+            pixmap.as_mut(),
+        )
+        .context("Failed to rasterize the preview.")?;
+
+        pixmap
+            .encode_png()
+            .context("Failed to encode the preview as PNG.")
+    }
+}
+
+/// Whether the model's part names a single file, not e.g. `..` or `a/b`.
+fn is_path_segment(part: &str) -> bool {
+    !part.is_empty()
+        && !part.starts_with('.')
+        && !part.contains(|ch: char| ch == '/' || ch == '\\' || ch.is_control())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn renderer(asset_dir: PathBuf) -> PreviewRenderer {
+        PreviewRenderer::new(PreviewSettings {
+            asset_dir,
+            width: 8,
+            height: 8,
+            layers: HashMap::from([(
+                "lemon".to_string(),
+                vec!["head".to_string(), "cap".to_string()],
+            )]),
+        })
+    }
+
+    #[test]
+    fn listed_traits_are_layered_first() {
+        let model = json!({ "kind": "lemon", "cap": "red", "eyes": "green", "head": "round" });
+
+        let actual = renderer(PathBuf::new()).layers(&model);
+        let names: Vec<_> = actual.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["head", "cap", "eyes"]);
+    }
+
+    #[test]
+    fn model_hash_ignores_the_key_order() {
+        let first: Value = serde_json::from_str(r#"{"kind":"lemon","cap":"red"}"#).unwrap();
+        let second: Value = serde_json::from_str(r#"{"cap":"red","kind":"lemon"}"#).unwrap();
+        assert_eq!(
+            PreviewRenderer::model_hash(&first),
+            PreviewRenderer::model_hash(&second)
+        );
+        assert_eq!(PreviewRenderer::model_hash(&first).len(), 64);
+    }
+
+    #[test]
+    fn svg_assets_are_rasterized() {
+        let asset_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let cap_dir = asset_dir.join("lemon").join("cap");
+        std::fs::create_dir_all(&cap_dir).unwrap();
+        let square = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><rect width="8" height="8" fill="red"/></svg>"#;
+        std::fs::write(cap_dir.join("red.svg"), square).unwrap();
+        let model = json!({ "kind": "lemon", "cap": "red" });
+
+        let png = renderer(asset_dir)
+            .render(&model, PreviewFormat::Png)
+            .unwrap()
+            .expect("The preview is missing");
+        let pixmap = tiny_skia::Pixmap::decode_png(&png).unwrap();
+        let pixel = pixmap.pixel(4, 4).unwrap();
+        assert_eq!(
+            (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()),
+            (255, 0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn traits_escaping_the_asset_dir_are_skipped() {
+        for part in ["", "..", ".hidden", "a/b", "a\\b"] {
+            assert!(!is_path_segment(part), "`{part}` is a path segment");
+        }
+        let model = json!({ "kind": "lemon", "cap": "../../etc/passwd" });
+        let actual = renderer(PathBuf::from("/")).compose_svg(&model).unwrap();
+        assert_eq!(actual, None);
+    }
+}
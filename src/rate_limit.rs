@@ -0,0 +1,6 @@
+pub use bucket::*;
+pub use limiter::*;
+
+mod bucket;
+mod limiter;
+pub mod middleware;
@@ -0,0 +1,147 @@
+use std::time::Instant;
+
+use crate::config::Budget;
+
+/// State of the rate limit after a request has been counted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    /// The bucket capacity.
+    pub limit: u32,
+    /// The number of requests left in the bucket.
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset: u64,
+    /// Seconds until the next request will be allowed.
+    pub retry_after: u64,
+}
+
+impl Quota {
+    pub fn headers(&self) -> [(&'static str, String); 3] {
+        [
+            ("ratelimit-limit", self.limit.to_string()),
+            ("ratelimit-remaining", self.remaining.to_string()),
+            ("ratelimit-reset", self.reset.to_string()),
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn full(budget: Budget, now: Instant) -> Self {
+        Self {
+            tokens: budget.capacity.into(),
+            updated_at: now,
+        }
+    }
+
+    pub fn try_acquire(&mut self, budget: Budget, now: Instant) -> Result<Quota, Quota> {
+        self.refill(budget, now);
+        let acquired = self.tokens >= 1.0;
+        if acquired {
+            self.tokens -= 1.0;
+        }
+
+        let quota = self.quota(budget);
+        if acquired {
+            Ok(quota)
+        } else {
+            Err(quota)
+        }
+    }
+
+    pub fn is_full(&mut self, budget: Budget, now: Instant) -> bool {
+        self.refill(budget, now);
+        self.tokens >= budget.capacity.into()
+    }
+
+    fn refill(&mut self, budget: Budget, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        let capacity = f64::from(budget.capacity);
+        self.tokens = (self.tokens + elapsed * budget.refill_per_second).min(capacity);
+        self.updated_at = now;
+    }
+
+    fn quota(&self, budget: Budget) -> Quota {
+        let seconds_until = |tokens: f64| {
+            let missing = (tokens - self.tokens).max(0.0);
+            (missing / budget.refill_per_second).ceil() as u64
+        };
+
+        Quota {
+            limit: budget.capacity,
+            remaining: self.tokens.floor() as u32,
+            reset: seconds_until(budget.capacity.into()),
+            retry_after: seconds_until(1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const BUDGET: Budget = Budget {
+        capacity: 3,
+        refill_per_second: 1.0,
+    };
+
+    #[test]
+    fn full_bucket_allows_requests_up_to_capacity() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(BUDGET, now);
+        for remaining in (0..3).rev() {
+            let actual = bucket.try_acquire(BUDGET, now);
+            assert!(
+                matches!(actual, Ok(Quota { remaining: r, .. }) if r == remaining),
+                "The request isn't allowed with {} remaining, actual value is {:?}",
+                remaining,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn empty_bucket_rejects_request() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(BUDGET, now);
+        (0..3).for_each(|_| {
+            let _ = bucket.try_acquire(BUDGET, now);
+        });
+        let actual = bucket.try_acquire(BUDGET, now);
+        assert_eq!(
+            actual,
+            Err(Quota {
+                limit: 3,
+                remaining: 0,
+                reset: 3,
+                retry_after: 1,
+            }),
+            "The request isn't rejected, actual value is {:?}",
+            actual
+        );
+    }
+
+    #[test]
+    fn bucket_is_refilled_over_time() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(BUDGET, now);
+        (0..3).for_each(|_| {
+            let _ = bucket.try_acquire(BUDGET, now);
+        });
+        let later = now + Duration::from_secs(2);
+        let actual = bucket.try_acquire(BUDGET, later);
+        assert!(
+            matches!(actual, Ok(Quota { remaining: 1, .. })),
+            "The bucket isn't refilled, actual value is {:?}",
+            actual
+        );
+        assert!(bucket.is_full(BUDGET, later + Duration::from_secs(10)));
+    }
+}
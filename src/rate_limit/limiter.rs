@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use actix_web::dev::ServiceRequest;
+
+use crate::config::RateLimitSettings;
+use crate::rate_limit::{Quota, TokenBucket};
+
+/// Beyond this number of tracked clients the full buckets are dropped.
+const MAX_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    ApiKey(String),
+    Ip(IpAddr),
+    Unknown,
+}
+
+pub struct RateLimiter {
+    settings: RateLimitSettings,
+    buckets: Mutex<HashMap<(String, ClientKey), TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(settings: RateLimitSettings) -> Self {
+        Self {
+            settings,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    /// Counts the request against the route's budget of the client.
+    pub fn check(&self, route: &str, client: ClientKey) -> Result<Quota, Quota> {
+        let budget = self.settings.budget(route);
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|(route, _), bucket| !bucket.is_full(self.settings.budget(route), now));
+        }
+
+        buckets
+            .entry((route.to_string(), client))
+            .or_insert_with(|| TokenBucket::full(budget, now))
+            .try_acquire(budget, now)
+    }
+
+    pub fn client_key(&self, req: &ServiceRequest) -> ClientKey {
+        let api_key = req
+            .headers()
+            .get(&self.settings.api_key_header)
+            .and_then(|v| v.to_str().ok())
+            .filter(|key| self.settings.api_keys.iter().any(|k| k == key));
+
+        match (api_key, req.peer_addr()) {
+            (Some(key), _) => ClientKey::ApiKey(key.to_string()),
+            (None, Some(peer)) => ClientKey::Ip(self.client_ip(req, peer.ip())),
+            (None, None) => ClientKey::Unknown,
+        }
+    }
+
+    fn client_ip(&self, req: &ServiceRequest, peer: IpAddr) -> IpAddr {
+        if !self.is_trusted(&peer) {
+            return peer;
+        }
+
+        self.settings
+            .client_ip_headers
+            .iter()
+            .filter_map(|header| req.headers().get(header)?.to_str().ok())
+            .find_map(|value| self.forwarded_ip(value))
+            .unwrap_or(peer)
+    }
+
+    /// Takes the rightmost address that doesn't belong to a trusted proxy,
+    /// because the leftmost ones can be spoofed by the client.
+    fn forwarded_ip(&self, value: &str) -> Option<IpAddr> {
+        value
+            .split(',')
+            .rev()
+            .filter_map(|ip| ip.trim().parse().ok())
+            .find(|ip| !self.is_trusted(ip))
+    }
+
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.settings.trusted_proxies.contains(ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use crate::config::Budget;
+
+    use super::*;
+
+    fn settings() -> RateLimitSettings {
+        RateLimitSettings {
+            enabled: true,
+            trusted_proxies: vec!["10.0.0.1".parse().unwrap()],
+            client_ip_headers: vec!["X-Forwarded-For".to_string()],
+            api_key_header: "X-Api-Key".to_string(),
+            api_keys: vec!["indexer".to_string()],
+            default_budget: Budget {
+                capacity: 1,
+                refill_per_second: 1.0,
+            },
+            routes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn forwarded_header_from_untrusted_peer_is_ignored() {
+        let limiter = RateLimiter::new(settings());
+        let req = TestRequest::default()
+            .peer_addr("192.168.0.5:1234".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "1.1.1.1"))
+            .to_srv_request();
+        let actual = limiter.client_key(&req);
+        assert_eq!(actual, ClientKey::Ip("192.168.0.5".parse().unwrap()));
+    }
+
+    #[test]
+    fn forwarded_header_from_trusted_peer_is_used() {
+        let limiter = RateLimiter::new(settings());
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:1234".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "6.6.6.6, 1.1.1.1, 10.0.0.1"))
+            .to_srv_request();
+        let actual = limiter.client_key(&req);
+        assert_eq!(actual, ClientKey::Ip("1.1.1.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn only_known_api_keys_are_used() {
+        let limiter = RateLimiter::new(settings());
+        let peer = "192.168.0.5:1234".parse().unwrap();
+        let known = TestRequest::default()
+            .peer_addr(peer)
+            .insert_header(("X-Api-Key", "indexer"))
+            .to_srv_request();
+        let unknown = TestRequest::default()
+            .peer_addr(peer)
+            .insert_header(("X-Api-Key", "random"))
+            .to_srv_request();
+        assert_eq!(
+            limiter.client_key(&known),
+            ClientKey::ApiKey("indexer".to_string())
+        );
+        assert_eq!(limiter.client_key(&unknown), ClientKey::Ip(peer.ip()));
+    }
+
+    #[test]
+    fn clients_are_throttled_separately() {
+        let limiter = RateLimiter::new(settings());
+        let alice = ClientKey::Ip("1.1.1.1".parse().unwrap());
+        let bob = ClientKey::Ip("2.2.2.2".parse().unwrap());
+        assert!(limiter.check("sales", alice.clone()).is_ok());
+        assert!(limiter.check("sales", alice.clone()).is_err());
+        assert!(limiter.check("nft_tokens", alice).is_ok());
+        assert!(limiter.check("sales", bob).is_ok());
+    }
+}
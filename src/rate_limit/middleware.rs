@@ -0,0 +1,43 @@
+use crate::api_version::route_name;
+use crate::errors::RateLimitError;
+use crate::rate_limit::RateLimiter;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::web;
+use actix_web_lab::middleware::Next;
+use anyhow::Context;
+
+pub async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let limiter = req
+        .app_data::<web::Data<RateLimiter>>()
+        .context("Failed to get rate limiter from application data.")
+        .map_err(RateLimitError::UnexpectedError)?
+        .clone();
+
+    if !limiter.enabled() {
+        return next.call(req).await;
+    }
+
+    let route = req
+        .match_pattern()
+        .map(|pattern| route_name(&pattern).to_string())
+        .unwrap_or_default();
+    let client = limiter.client_key(&req);
+    let quota = limiter
+        .check(&route, client)
+        .map_err(RateLimitError::TooManyRequests)?;
+
+    let mut response = next.call(req).await?;
+    for (name, value) in quota.headers() {
+        response.headers_mut().insert(
+            HeaderName::from_static(name),
+            HeaderValue::from_str(&value).expect("Quota header value is always valid"),
+        );
+    }
+
+    Ok(response)
+}
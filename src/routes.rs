@@ -1,54 +1,119 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::audit::AuditEntry;
+use crate::openapi::models::{
+    AskForDb, AskForRest, BidForDb, BidForRest, NftTokenForRest, SaleForDb, SaleForRest,
+};
 
 pub use asks::*;
+pub use audit_log::*;
 pub use bids::*;
+pub use collections::*;
 pub use contracts::*;
 pub use health_check::*;
 pub use is_owner::*;
+pub use metadata::*;
 pub use nft_tokens::*;
+pub use openapi::*;
 pub use paid::*;
+pub use preview::*;
 pub use sale::*;
+pub use search::*;
+pub use streaming::*;
 
 mod asks;
+mod audit_log;
 mod bids;
+mod collections;
 mod contracts;
 mod health_check;
 mod is_owner;
+mod metadata;
 mod nft_tokens;
+mod openapi;
 mod paid;
+mod preview;
 mod sale;
+mod search;
+mod streaming;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationQuery {
+    /// Only the rows of the nft contract, one of the active contracts config.
+    pub contract_id: Option<String>,
+    /// Only the rows of the token.
     pub token_id: Option<String>,
+    /// The number of days to look back, accepted by `paid` only.
     pub days: Option<i64>,
+    /// The page size, bounded by the resource's maximum.
     pub limit: Option<i64>,
+    /// The number of rows to skip.
     pub offset: Option<i64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A token related row or payload with the nft contract of its token.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[aliases(
+    ContractAsk = ContractScoped<AskForDb>,
+    ContractAskPayload = ContractScoped<AskForRest>,
+    ContractBid = ContractScoped<BidForDb>,
+    ContractBidPayload = ContractScoped<BidForRest>,
+    ContractNftToken = ContractScoped<NftTokenRow>,
+    ContractNftTokenPayload = ContractScoped<NftTokenForRest>,
+    ContractSale = ContractScoped<SaleForDb>,
+    ContractSalePayload = ContractScoped<SaleForRest>
+)]
+pub struct ContractScoped<T> {
+    /// The nft contract of the token, e.g. `nft.battlemon.near`.
+    pub contract_id: String,
+    #[serde(flatten)]
+    pub item: T,
+}
+
+impl<T> ContractScoped<T> {
+    pub fn new(contract_id: impl Into<String>, item: T) -> Self {
+        Self {
+            contract_id: contract_id.into(),
+            item,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[aliases(
+    AsksReport = RowsJsonReport<ContractAsk>,
+    AuditLogReport = RowsJsonReport<AuditEntry>,
+    BidsReport = RowsJsonReport<ContractBid>,
+    ContractsHistoryReport = RowsJsonReport<ContractsVersion>,
+    NftTokensReport = RowsJsonReport<ContractNftToken>,
+    SalesReport = RowsJsonReport<ContractSale>
+)]
 pub struct RowsJsonReport<T> {
     pub rows: Vec<T>,
+    /// Whether it's the last page.
     pub end: bool,
+    /// The effective page size.
+    pub limit: i64,
 }
 
 impl<'de, T> RowsJsonReport<T>
 where
     T: Serialize + Deserialize<'de>,
 {
-    fn new(rows: Vec<T>, end: bool) -> Self {
-        Self { rows, end }
+    fn new(rows: Vec<T>, end: bool, limit: i64) -> Self {
+        Self { rows, end, limit }
     }
 
     pub fn from_rows(mut rows: Vec<T>, limit: i64) -> Self {
-        let limit = limit as usize;
-        let (rows, end) = if rows.is_empty() || rows.len() <= limit {
+        let (rows, end) = if rows.is_empty() || rows.len() <= limit as usize {
             (rows, true)
         } else {
             rows.pop();
             (rows, false)
         };
 
-        Self::new(rows, end)
+        Self::new(rows, end, limit)
     }
 }
@@ -1,72 +1,244 @@
-use crate::domain::{AskFilter, Limit, Offset, Parse, ParseToPositiveInt, TokenId};
-use crate::errors::AskError;
-use crate::routes::{PaginationQuery, RowsJsonReport};
+use crate::audit::{self, AuditContext};
+use crate::cache::RowVersion;
+use crate::config::{PaginationSettings, StreamingSettings, TokenIdSettings};
+use crate::domain::{
+    AskFilter, ContractId, ContractRules, Limit, LimitBounds, NewAsk, Offset, ParseToPositiveInt,
+    TokenId, ValidationErrors, Validator,
+};
+use crate::errors::{AskError, AuthError, NetworkError, RateLimitError};
+use crate::network::Network;
+use crate::routes::{
+    get_contract_rules, send_rows, ContractScoped, ExportQuery, PaginationQuery, RowStream,
+};
+use actix_web::http::header::IfNoneMatch;
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
 use battlemon_models::market::ask::{AskForDb, AskForRest};
+use futures_util::stream::{BoxStream, StreamExt, TryStreamExt};
 use sqlx::{PgPool, Postgres, Transaction};
 
-impl TryFrom<PaginationQuery> for AskFilter {
-    type Error = String;
+/// The columns of the exported asks.
+pub const ASK_COLUMNS: [&str; 6] = [
+    "contract_id",
+    "id",
+    "token_id",
+    "account_id",
+    "approval_id",
+    "price",
+];
 
-    fn try_from(query: PaginationQuery) -> Result<Self, Self::Error> {
-        let token_id = TokenId::parse(query.token_id)?;
-        let limit = Limit::parse(query.limit)?;
-        let offset = Offset::parse(query.offset)?;
+impl TryFrom<(PaginationQuery, LimitBounds, &ContractRules<'_>)> for AskFilter {
+    type Error = ValidationErrors;
 
-        Ok(Self {
-            limit,
-            offset,
-            token_id,
+    fn try_from(
+        (query, bounds, rules): (PaginationQuery, LimitBounds, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse(query.contract_id.as_deref(), rules));
+        let format = rules.token_id_format(query.contract_id.as_deref());
+        let token_id = validator.check(TokenId::parse(query.token_id.as_deref(), format));
+        let limit = validator.check(Limit::parse(query.limit, bounds));
+        let offset = validator.check(Offset::parse(query.offset));
+
+        validator.finish(|| {
+            Some(Self {
+                limit: limit?,
+                offset: offset?,
+                contract_id: contract_id?,
+                token_id: token_id?,
+            })
         })
     }
 }
 
-#[tracing::instrument(name = "Handle asks request", skip(filter, pool))]
+#[utoipa::path(
+    get,
+    path = "/asks",
+    tag = "asks",
+    params(PaginationQuery, ExportQuery),
+    responses(
+        (status = 200, description = "A page of asks, or every ask as a file.", content(
+            ("application/json" = AsksReport),
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+        )),
+        NetworkError,
+        RateLimitError,
+        AskError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle asks request",
+    skip(filter, pool, pagination, token_ids, streaming)
+)]
 pub async fn get_asks(
     web::Query(filter): web::Query<PaginationQuery>,
+    web::Query(export): web::Query<ExportQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    streaming: web::Data<StreamingSettings>,
+) -> Result<HttpResponse, AskError> {
+    let export = export
+        .parse(&ASK_COLUMNS)
+        .map_err(AskError::ValidationError)?;
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let filter: AskFilter = (filter, pagination.bounds("asks"), &rules)
+        .try_into()
+        .map_err(AskError::ValidationError)?;
+    let page = filter.limit();
+    // the row after the last one of a page or a file tells that it's truncated
+    let limit = match export.is_file() {
+        true => streaming.max_rows + 1,
+        false => page + 1,
+    };
+    let rows = RowStream::spawn(&streaming, move |sender| async move {
+        send_rows(asks_db(&filter, limit, &network, &pool), sender).await
+    })
+    .await
+    .context("Failed to get the ask's data from the database.")?;
+    if export.is_file() {
+        return Ok(rows.into_file(export, "asks", streaming.max_rows));
+    }
+
+    Ok(rows.into_report(page))
+}
+
+/// The asks after the filter's offset, at most `limit` of them.
+pub fn asks_db<'a>(
+    filter: &'a AskFilter,
+    limit: i64,
+    network: &'a Network,
+    pool: &'a PgPool,
+) -> BoxStream<'a, Result<ContractScoped<AskForDb>, sqlx::Error>> {
+    sqlx::query_file!(
+        "queries/asks.sql",
+        network.as_str(),
+        filter.contract_id(),
+        filter.token_id(),
+        limit,
+        filter.offset()
+    )
+    .fetch(pool)
+    .map_ok(|r| {
+        let ask = AskForDb {
+            id: r.id,
+            token_id: r.token_id,
+            account_id: r.account_id,
+            approval_id: r.approval_id,
+            price: r.price,
+        };
+        ContractScoped::new(r.contract_id, ask)
+    })
+    .boxed()
+}
+
+#[utoipa::path(
+    get,
+    path = "/asks/{id}",
+    tag = "asks",
+    params(
+        ("id" = String, Path, description = "The id of the ask."),
+        ("If-None-Match" = Option<String>, Header, description = "The entity tags of the cached versions."),
+    ),
+    responses(
+        (status = 200, description = "The ask.", body = ContractAsk),
+        (status = 304, description = "The ask matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        AskError,
+    )
+)]
+#[tracing::instrument(name = "Handle ask request", skip(pool))]
+pub async fn get_ask(
+    id: web::Path<String>,
+    network: Network,
     pool: web::Data<PgPool>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
 ) -> Result<HttpResponse, AskError> {
-    let filter = filter.try_into().map_err(AskError::ValidationError)?;
-    let asks = get_asks_db(&filter, &pool)
+    let (ask, version) = get_ask_db(&id, &network, &pool)
         .await
-        .context("Failed to get the ask's data from the database.")?;
+        .context("Failed to get the ask's data from the database.")?
+        .ok_or_else(|| {
+            AskError::NotFound(format!(
+                "The ask `{id}` doesn't exist in the `{network}` network."
+            ))
+        })?;
 
-    Ok(HttpResponse::Ok().json(RowsJsonReport::from_rows(asks, filter.limit())))
+    Ok(version.respond(if_none_match.as_deref(), &ask))
 }
 
-#[tracing::instrument(name = "Query asks from database", skip(filter, pool))]
-pub async fn get_asks_db(
-    filter: &AskFilter,
+#[tracing::instrument(name = "Query ask from database", skip(pool))]
+pub async fn get_ask_db(
+    id: &str,
+    network: &Network,
     pool: &PgPool,
-) -> Result<Vec<AskForDb>, anyhow::Error> {
-    let rows = sqlx::query_as!(
-        AskForDb,
+) -> Result<Option<(ContractScoped<AskForDb>, RowVersion)>, anyhow::Error> {
+    let row = sqlx::query!(
         r#"
-        SELECT id, token_id,  account_id, approval_id, price
+        SELECT contract_id, id, token_id, account_id, approval_id, price, row_version
         FROM asks
-        WHERE ($1::text IS null OR token_id = $1)
-        ORDER BY id LIMIT $2 OFFSET $3;
+        WHERE network = $1 AND id = $2
         "#,
-        filter.token_id(),
-        filter.limit() + 1,
-        filter.offset()
+        network.as_str(),
+        id,
     )
-    .fetch_all(pool)
+    .fetch_optional(pool)
     .await?;
 
-    Ok(rows)
+    Ok(row.map(|r| {
+        let ask = AskForDb {
+            id: r.id,
+            token_id: r.token_id,
+            account_id: r.account_id,
+            approval_id: r.approval_id,
+            price: r.price,
+        };
+        (
+            ContractScoped::new(r.contract_id, ask),
+            RowVersion(r.row_version),
+        )
+    }))
 }
 
-#[tracing::instrument(name = "Insert ask", skip(ask, pool))]
+#[utoipa::path(
+    post,
+    path = "/asks",
+    tag = "asks",
+    request_body = ContractAskPayload,
+    responses(
+        (status = 201, description = "The ask is stored."),
+        NetworkError,
+        AuthError,
+        AskError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Insert ask", skip(ask, pool, token_ids))]
 pub async fn insert_ask(
-    web::Json(ask): web::Json<AskForRest>,
+    web::Json(ask): web::Json<ContractScoped<AskForRest>>,
+    network: Network,
     pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
+    audit: AuditContext,
 ) -> Result<HttpResponse, AskError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let ask =
+        NewAsk::try_from((ask.contract_id, ask.item, &rules)).map_err(AskError::ValidationError)?;
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    insert_ask_db(ask, &mut tx)
+    let inserted = insert_ask_db(ask, &network, &mut tx)
         .await
         .context("Failed to insert the ask data into the database.")?;
+    if let Some(ask) = inserted {
+        audit
+            .record(
+                std::slice::from_ref(&ask.item.id),
+                audit::created(&ask),
+                &mut tx,
+            )
+            .await?;
+    }
     tx.commit()
         .await
         .context("Failed to commit SQL transaction to store a new subscriber.")?;
@@ -75,36 +247,70 @@ pub async fn insert_ask(
 
 #[tracing::instrument(name = "Store ask to database", skip(tx))]
 pub async fn insert_ask_db(
-    ask: AskForRest,
+    ask: NewAsk,
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
-    sqlx::query!(
+) -> Result<Option<ContractScoped<AskForDb>>, anyhow::Error> {
+    let (contract_id, ask) = ask.into_parts();
+    let row = sqlx::query!(
         r#"
-        INSERT INTO asks (id, token_id, account_id, approval_id, price)
-        VALUES ($1, $2, $3, $4, $5)
-        ON CONFLICT (id) DO NOTHING
+        INSERT INTO asks (network, contract_id, id, token_id, account_id, approval_id, price)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (network, id) DO NOTHING
+        RETURNING contract_id, id, token_id, account_id, approval_id, price
         "#,
+        network.as_str(),
+        contract_id,
         ask.id,
         ask.token_id,
         ask.account_id,
         ask.approval_id,
         ask.price,
     )
-    .execute(tx)
+    .fetch_optional(tx)
     .await?;
 
-    Ok(())
+    Ok(row.map(|r| {
+        let ask = AskForDb {
+            id: r.id,
+            token_id: r.token_id,
+            account_id: r.account_id,
+            approval_id: r.approval_id,
+            price: r.price,
+        };
+        ContractScoped::new(r.contract_id, ask)
+    }))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/asks",
+    tag = "asks",
+    request_body = AskForRest,
+    responses(
+        (status = 201, description = "The ask is removed."),
+        NetworkError,
+        AuthError,
+        AskError,
+    ),
+    security(("basic_auth" = []))
+)]
 #[tracing::instrument(name = "Delete ask", skip(ask, pool))]
 pub async fn delete_ask(
     web::Json(ask): web::Json<AskForRest>,
+    network: Network,
     pool: web::Data<PgPool>,
+    audit: AuditContext,
 ) -> Result<HttpResponse, AskError> {
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    delete_ask_db(ask, &mut tx)
+    let deleted = delete_ask_db(ask, &network, &mut tx)
         .await
         .context("Failed to remove the ask data from the database.")?;
+    if let Some(ask) = deleted {
+        audit
+            .record(std::slice::from_ref(&ask.id), audit::deleted(&ask), &mut tx)
+            .await?;
+    }
     tx.commit()
         .await
         .context("Failed to commit SQL transaction to complete removing ask.")?;
@@ -114,17 +320,21 @@ pub async fn delete_ask(
 #[tracing::instrument(name = "Remove ask from database", skip(tx))]
 pub async fn delete_ask_db(
     ask: AskForRest,
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
-    sqlx::query!(
+) -> Result<Option<AskForDb>, anyhow::Error> {
+    let row = sqlx::query_as!(
+        AskForDb,
         r#"
         DELETE FROM asks
-        WHERE id = $1 
+        WHERE network = $1 AND id = $2
+        RETURNING id, token_id, account_id, approval_id, price
         "#,
+        network.as_str(),
         ask.id,
     )
-    .execute(tx)
+    .fetch_optional(tx)
     .await?;
 
-    Ok(())
+    Ok(row)
 }
@@ -0,0 +1,138 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::IntoParams;
+
+use crate::audit::AuditEntry;
+use crate::config::PaginationSettings;
+use crate::domain::{
+    AuditLogFilter, FieldError, Limit, LimitBounds, Offset, ParseToPositiveInt, ValidationErrors,
+    Validator,
+};
+use crate::errors::{AuditLogError, AuthError};
+use crate::routes::RowsJsonReport;
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditLogQuery {
+    /// Only the writes of the user.
+    pub user_id: Option<i64>,
+    /// Only the writes made on the network, e.g. `testnet`.
+    pub network: Option<String>,
+    /// Only the writes made through the route, e.g. `sales`.
+    pub route: Option<String>,
+    /// Only the writes made with the http method, e.g. `POST`.
+    pub method: Option<String>,
+    /// Only the writes touching the entity.
+    pub entity_id: Option<String>,
+    /// Only the writes made at or after the moment.
+    pub from: Option<DateTime<Utc>>,
+    /// Only the writes made at or before the moment.
+    pub to: Option<DateTime<Utc>>,
+    /// The page size, bounded by the resource's maximum.
+    pub limit: Option<i64>,
+    /// The number of rows to skip.
+    pub offset: Option<i64>,
+}
+
+impl TryFrom<(AuditLogQuery, LimitBounds)> for AuditLogFilter {
+    type Error = ValidationErrors;
+
+    fn try_from((query, bounds): (AuditLogQuery, LimitBounds)) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let limit = validator.check(Limit::parse(query.limit, bounds));
+        let offset = validator.check(Offset::parse(query.offset));
+        if let (Some(from), Some(to)) = (query.from, query.to) {
+            if from > to {
+                validator.push(FieldError::new(
+                    "from",
+                    "validation.from_after_to",
+                    "The `from` date must not be later than the `to` date.",
+                ));
+            }
+        }
+        let (limit, offset) = validator.finish(|| Some((limit?, offset?)))?;
+        // routes are stored as match patterns, e.g. `/nft_tokens`
+        let route = query
+            .route
+            .map(|r| format!("/{}", r.trim_start_matches('/')));
+        let method = query.method.map(|m| m.to_uppercase());
+
+        Ok(Self {
+            limit,
+            offset,
+            user_id: query.user_id,
+            network: query.network,
+            route,
+            method,
+            entity_id: query.entity_id,
+            from: query.from,
+            to: query.to,
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/audit_log",
+    tag = "audit_log",
+    params(AuditLogQuery),
+    responses(
+        (status = 200, description = "A page of audit entries, the latest first.", body = AuditLogReport),
+        AuthError,
+        AuditLogError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Handle audit log request", skip(filter, pool, pagination))]
+pub async fn get_audit_log(
+    web::Query(filter): web::Query<AuditLogQuery>,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+) -> Result<HttpResponse, AuditLogError> {
+    let filter: AuditLogFilter = (filter, pagination.bounds("audit_log"))
+        .try_into()
+        .map_err(AuditLogError::ValidationError)?;
+    let entries = get_audit_log_db(&filter, &pool)
+        .await
+        .context("Failed to get the audit log data from the database.")?;
+
+    Ok(HttpResponse::Ok().json(RowsJsonReport::from_rows(entries, filter.limit())))
+}
+
+#[tracing::instrument(name = "Query audit log from database", skip(filter, pool))]
+pub async fn get_audit_log_db(
+    filter: &AuditLogFilter,
+    pool: &PgPool,
+) -> Result<Vec<AuditEntry>, anyhow::Error> {
+    let rows = sqlx::query_as!(
+        AuditEntry,
+        r#"
+        SELECT id, user_id, network, route, method, request_id, entity_ids, diff, created_at
+        FROM audit_log
+        WHERE ($1::bigint IS null OR user_id = $1)
+            AND ($9::text IS null OR network = $9)
+            AND ($2::text IS null OR route = $2)
+            AND ($3::text IS null OR method = $3)
+            AND ($4::text IS null OR entity_ids @> ARRAY[$4::text])
+            AND ($5::timestamptz IS null OR created_at >= $5)
+            AND ($6::timestamptz IS null OR created_at <= $6)
+        ORDER BY id DESC LIMIT $7 OFFSET $8
+        "#,
+        filter.user_id(),
+        filter.route(),
+        filter.method(),
+        filter.entity_id(),
+        filter.from(),
+        filter.to(),
+        filter.limit() + 1,
+        filter.offset(),
+        filter.network(),
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
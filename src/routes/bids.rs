@@ -1,72 +1,247 @@
-use crate::domain::{BidFilter, Limit, Offset, Parse, ParseToPositiveInt, TokenId};
-use crate::errors::BidError;
-use crate::routes::{PaginationQuery, RowsJsonReport};
+use crate::audit::{self, AuditContext};
+use crate::cache::RowVersion;
+use crate::config::{PaginationSettings, StreamingSettings, TokenIdSettings};
+use crate::domain::{
+    BidFilter, ContractId, ContractRules, Limit, LimitBounds, NewBid, Offset, ParseToPositiveInt,
+    TokenId, ValidationErrors, Validator,
+};
+use crate::errors::{AuthError, BidError, NetworkError, RateLimitError};
+use crate::network::Network;
+use crate::routes::{
+    get_contract_rules, send_rows, ContractScoped, ExportQuery, PaginationQuery, RowStream,
+};
+use actix_web::http::header::IfNoneMatch;
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
 use battlemon_models::market::bid::{BidForDb, BidForRest};
+use futures_util::stream::{BoxStream, StreamExt, TryStreamExt};
 use sqlx::{PgPool, Postgres, Transaction};
 
-impl TryFrom<PaginationQuery> for BidFilter {
-    type Error = String;
+/// The columns of the exported bids.
+pub const BID_COLUMNS: [&str; 7] = [
+    "contract_id",
+    "id",
+    "token_id",
+    "account_id",
+    "expire_at",
+    "create_at",
+    "price",
+];
 
-    fn try_from(query: PaginationQuery) -> Result<Self, Self::Error> {
-        let token_id = TokenId::parse(query.token_id)?;
-        let limit = Limit::parse(query.limit)?;
-        let offset = Offset::parse(query.offset)?;
+impl TryFrom<(PaginationQuery, LimitBounds, &ContractRules<'_>)> for BidFilter {
+    type Error = ValidationErrors;
 
-        Ok(Self {
-            limit,
-            offset,
-            token_id,
+    fn try_from(
+        (query, bounds, rules): (PaginationQuery, LimitBounds, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse(query.contract_id.as_deref(), rules));
+        let format = rules.token_id_format(query.contract_id.as_deref());
+        let token_id = validator.check(TokenId::parse(query.token_id.as_deref(), format));
+        let limit = validator.check(Limit::parse(query.limit, bounds));
+        let offset = validator.check(Offset::parse(query.offset));
+
+        validator.finish(|| {
+            Some(Self {
+                limit: limit?,
+                offset: offset?,
+                contract_id: contract_id?,
+                token_id: token_id?,
+            })
         })
     }
 }
 
-#[tracing::instrument(name = "Handle bids request", skip(filter, pool))]
+#[utoipa::path(
+    get,
+    path = "/bids",
+    tag = "bids",
+    params(PaginationQuery, ExportQuery),
+    responses(
+        (status = 200, description = "A page of bids, or every bid as a file.", content(
+            ("application/json" = BidsReport),
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+        )),
+        NetworkError,
+        RateLimitError,
+        BidError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle bids request",
+    skip(filter, pool, pagination, token_ids, streaming)
+)]
 pub async fn get_bids(
     web::Query(filter): web::Query<PaginationQuery>,
+    web::Query(export): web::Query<ExportQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    streaming: web::Data<StreamingSettings>,
+) -> Result<HttpResponse, BidError> {
+    let export = export
+        .parse(&BID_COLUMNS)
+        .map_err(BidError::ValidationError)?;
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let filter: BidFilter = (filter, pagination.bounds("bids"), &rules)
+        .try_into()
+        .map_err(BidError::ValidationError)?;
+    let page = filter.limit();
+    // the row after the last one of a page or a file tells that it's truncated
+    let limit = match export.is_file() {
+        true => streaming.max_rows + 1,
+        false => page + 1,
+    };
+    let rows = RowStream::spawn(&streaming, move |sender| async move {
+        send_rows(bids_db(&filter, limit, &network, &pool), sender).await
+    })
+    .await
+    .context("Failed to get the bid's data from the database.")?;
+    if export.is_file() {
+        return Ok(rows.into_file(export, "bids", streaming.max_rows));
+    }
+
+    Ok(rows.into_report(page))
+}
+
+/// The bids after the filter's offset, at most `limit` of them.
+pub fn bids_db<'a>(
+    filter: &'a BidFilter,
+    limit: i64,
+    network: &'a Network,
+    pool: &'a PgPool,
+) -> BoxStream<'a, Result<ContractScoped<BidForDb>, sqlx::Error>> {
+    sqlx::query_file!(
+        "queries/bids.sql",
+        network.as_str(),
+        filter.contract_id(),
+        filter.token_id(),
+        limit,
+        filter.offset()
+    )
+    .fetch(pool)
+    .map_ok(|r| {
+        let bid = BidForDb {
+            id: r.id,
+            token_id: r.token_id,
+            account_id: r.account_id,
+            expire_at: r.expire_at,
+            create_at: r.create_at,
+            price: r.price,
+        };
+        ContractScoped::new(r.contract_id, bid)
+    })
+    .boxed()
+}
+
+#[utoipa::path(
+    get,
+    path = "/bids/{id}",
+    tag = "bids",
+    params(
+        ("id" = String, Path, description = "The id of the bid."),
+        ("If-None-Match" = Option<String>, Header, description = "The entity tags of the cached versions."),
+    ),
+    responses(
+        (status = 200, description = "The bid.", body = ContractBid),
+        (status = 304, description = "The bid matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        BidError,
+    )
+)]
+#[tracing::instrument(name = "Handle bid request", skip(pool))]
+pub async fn get_bid(
+    id: web::Path<String>,
+    network: Network,
     pool: web::Data<PgPool>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
 ) -> Result<HttpResponse, BidError> {
-    let filter = filter.try_into().map_err(BidError::ValidationError)?;
-    let bids = get_bids_db(&filter, &pool)
+    let (bid, version) = get_bid_db(&id, &network, &pool)
         .await
-        .context("Failed to get the bid's data from the database.")?;
+        .context("Failed to get the bid's data from the database.")?
+        .ok_or_else(|| {
+            BidError::NotFound(format!(
+                "The bid `{id}` doesn't exist in the `{network}` network."
+            ))
+        })?;
 
-    Ok(HttpResponse::Ok().json(RowsJsonReport::from_rows(bids, filter.limit())))
+    Ok(version.respond(if_none_match.as_deref(), &bid))
 }
 
-#[tracing::instrument(name = "Query bids from database", skip(filter, pool))]
-pub async fn get_bids_db(
-    filter: &BidFilter,
+#[tracing::instrument(name = "Query bid from database", skip(pool))]
+pub async fn get_bid_db(
+    id: &str,
+    network: &Network,
     pool: &PgPool,
-) -> Result<Vec<BidForDb>, anyhow::Error> {
-    let rows = sqlx::query_as!(
-        BidForDb,
+) -> Result<Option<(ContractScoped<BidForDb>, RowVersion)>, anyhow::Error> {
+    let row = sqlx::query!(
         r#"
-        SELECT id, token_id,  account_id, expire_at, create_at, price
+        SELECT contract_id, id, token_id, account_id, expire_at, create_at, price, row_version
         FROM bids
-        WHERE ($1::text IS null OR token_id = $1)
-        ORDER BY id LIMIT $2 OFFSET $3;
+        WHERE network = $1 AND id = $2
         "#,
-        filter.token_id(),
-        filter.limit() + 1,
-        filter.offset()
+        network.as_str(),
+        id,
     )
-    .fetch_all(pool)
+    .fetch_optional(pool)
     .await?;
 
-    Ok(rows)
+    Ok(row.map(|r| {
+        let bid = BidForDb {
+            id: r.id,
+            token_id: r.token_id,
+            account_id: r.account_id,
+            expire_at: r.expire_at,
+            create_at: r.create_at,
+            price: r.price,
+        };
+        (
+            ContractScoped::new(r.contract_id, bid),
+            RowVersion(r.row_version),
+        )
+    }))
 }
 
-#[tracing::instrument(name = "Insert bid", skip(pool))]
+#[utoipa::path(
+    post,
+    path = "/bids",
+    tag = "bids",
+    request_body = ContractBidPayload,
+    responses(
+        (status = 201, description = "The bid is stored."),
+        NetworkError,
+        AuthError,
+        BidError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Insert bid", skip(pool, token_ids))]
 pub async fn insert_bid(
-    web::Json(bid): web::Json<BidForRest>,
+    web::Json(bid): web::Json<ContractScoped<BidForRest>>,
+    network: Network,
     pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
+    audit: AuditContext,
 ) -> Result<HttpResponse, BidError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let bid =
+        NewBid::try_from((bid.contract_id, bid.item, &rules)).map_err(BidError::ValidationError)?;
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    insert_bid_db(bid, &mut tx)
+    let inserted = insert_bid_db(bid, &network, &mut tx)
         .await
         .context("Failed to insert the bid data into the database.")?;
+    if let Some(bid) = inserted {
+        audit
+            .record(
+                std::slice::from_ref(&bid.item.id),
+                audit::created(&bid),
+                &mut tx,
+            )
+            .await?;
+    }
     tx.commit()
         .await
         .context("Failed to commit SQL transaction to store a new subscriber.")?;
@@ -75,15 +250,20 @@ pub async fn insert_bid(
 
 #[tracing::instrument(name = "Store bid to database", skip(tx))]
 pub async fn insert_bid_db(
-    bid: BidForRest,
+    bid: NewBid,
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
-    sqlx::query!(
+) -> Result<Option<ContractScoped<BidForDb>>, anyhow::Error> {
+    let (contract_id, bid) = bid.into_parts();
+    let row = sqlx::query!(
         r#"
-        INSERT INTO bids (id, token_id, account_id, expire_at, create_at, price)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT (id) DO NOTHING
+        INSERT INTO bids (network, contract_id, id, token_id, account_id, expire_at, create_at, price)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (network, id) DO NOTHING
+        RETURNING contract_id, id, token_id, account_id, expire_at, create_at, price
         "#,
+        network.as_str(),
+        contract_id,
         bid.id,
         bid.token_id,
         bid.account_id,
@@ -91,21 +271,51 @@ pub async fn insert_bid_db(
         bid.create_at,
         bid.price,
     )
-    .execute(tx)
+    .fetch_optional(tx)
     .await?;
 
-    Ok(())
+    Ok(row.map(|r| {
+        let bid = BidForDb {
+            id: r.id,
+            token_id: r.token_id,
+            account_id: r.account_id,
+            expire_at: r.expire_at,
+            create_at: r.create_at,
+            price: r.price,
+        };
+        ContractScoped::new(r.contract_id, bid)
+    }))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/bids",
+    tag = "bids",
+    request_body = BidForRest,
+    responses(
+        (status = 201, description = "The bid is removed."),
+        NetworkError,
+        AuthError,
+        BidError,
+    ),
+    security(("basic_auth" = []))
+)]
 #[tracing::instrument(name = "Delete bid", skip(bid, pool))]
 pub async fn delete_bid(
     web::Json(bid): web::Json<BidForRest>,
+    network: Network,
     pool: web::Data<PgPool>,
+    audit: AuditContext,
 ) -> Result<HttpResponse, BidError> {
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    delete_bid_db(bid, &mut tx)
+    let deleted = delete_bid_db(bid, &network, &mut tx)
         .await
         .context("Failed to remove the bid data from the database.")?;
+    if let Some(bid) = deleted {
+        audit
+            .record(std::slice::from_ref(&bid.id), audit::deleted(&bid), &mut tx)
+            .await?;
+    }
     tx.commit()
         .await
         .context("Failed to commit SQL transaction to complete removing bid.")?;
@@ -115,17 +325,21 @@ pub async fn delete_bid(
 #[tracing::instrument(name = "Remove bid from database", skip(tx))]
 pub async fn delete_bid_db(
     bid: BidForRest,
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
-    sqlx::query!(
+) -> Result<Option<BidForDb>, anyhow::Error> {
+    let row = sqlx::query_as!(
+        BidForDb,
         r#"
         DELETE FROM bids
-        WHERE id = $1 
+        WHERE network = $1 AND id = $2
+        RETURNING id, token_id, account_id, expire_at, create_at, price
         "#,
+        network.as_str(),
         bid.id,
     )
-    .execute(tx)
+    .fetch_optional(tx)
     .await?;
 
-    Ok(())
+    Ok(row)
 }
@@ -0,0 +1,273 @@
+use std::collections::BTreeSet;
+
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::config::TokenIdSettings;
+use crate::domain::{AsInner, ContractId, NftKindName, Validator};
+use crate::errors::{CollectionError, NetworkError, RateLimitError};
+use crate::network::Network;
+use crate::routes::{get_contract_rules, TraitFacet};
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CollectionQuery {
+    /// Only the tokens of the nft contract, one of the active contracts config.
+    pub contract_id: Option<String>,
+}
+
+/// The distribution of the trait values among the tokens of a kind.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct CollectionTraits {
+    /// The kind of the tokens, e.g. `lemon`.
+    pub kind: String,
+    /// The number of the tokens of the kind.
+    pub total: i64,
+    /// The traits by name, their values the most frequent first.
+    pub traits: Vec<TraitDistribution>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TraitDistribution {
+    /// The trait of the model, e.g. `cap`.
+    pub name: String,
+    pub values: Vec<TraitValueCount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TraitValueCount {
+    pub value: String,
+    pub count: i64,
+    /// The share of the tokens of the kind with the value, from 0 to 1.
+    pub frequency: f64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/collections/{kind}/traits",
+    tag = "nft_tokens",
+    params(
+        ("kind" = String, Path, description = "The kind of the tokens, e.g. `lemon`."),
+        CollectionQuery,
+    ),
+    responses(
+        (status = 200, description = "The trait values of the kind and their counts.", body = CollectionTraits),
+        (status = 304, description = "The response matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        CollectionError,
+    )
+)]
+#[tracing::instrument(name = "Handle collection traits request", skip(pool, token_ids))]
+pub async fn get_collection_traits(
+    path: web::Path<String>,
+    web::Query(query): web::Query<CollectionQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
+) -> Result<HttpResponse, CollectionError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let mut validator = Validator::default();
+    let kind = validator.check(NftKindName::parse("kind", &path.into_inner()));
+    let contract_id = validator.check(ContractId::parse(query.contract_id.as_deref(), &rules));
+    let (kind, contract_id) = validator
+        .finish(|| Some((kind?, contract_id?)))
+        .map_err(CollectionError::ValidationError)?;
+    let (total, values) = get_trait_counts_db(&kind, contract_id.as_inner(), &network, &pool)
+        .await
+        .context("Failed to count the traits of the nft tokens in the database.")?;
+
+    Ok(HttpResponse::Ok().json(CollectionTraits {
+        kind: kind.as_str().to_string(),
+        total,
+        traits: group_traits(total, values),
+    }))
+}
+
+#[tracing::instrument(name = "Count traits of nft tokens in database", skip(pool))]
+async fn get_trait_counts_db(
+    kind: &NftKindName,
+    contract_id: Option<&str>,
+    network: &Network,
+    pool: &PgPool,
+) -> Result<(i64, Vec<TraitFacet>), anyhow::Error> {
+    let total = sqlx::query!(
+        r#"
+        SELECT count(*) AS "total!"
+        FROM nft_tokens
+        WHERE network = $1 AND ($2::text IS null OR contract_id = $2) AND model->>'kind' = $3
+        "#,
+        network.as_str(),
+        contract_id,
+        kind.as_str(),
+    )
+    .fetch_one(pool)
+    .await?
+    .total;
+
+    let values = sqlx::query_file_as!(
+        TraitFacet,
+        "queries/collection_traits.sql",
+        network.as_str(),
+        contract_id,
+        kind.as_str(),
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok((total, values))
+}
+
+/// Groups the counts ordered by the trait name.
+fn group_traits(total: i64, values: Vec<TraitFacet>) -> Vec<TraitDistribution> {
+    let mut traits: Vec<TraitDistribution> = Vec::new();
+    for value in values {
+        let count = TraitValueCount {
+            value: value.value,
+            count: value.count,
+            frequency: value.count as f64 / total as f64,
+        };
+        match traits.last_mut() {
+            Some(last) if last.name == value.name => last.values.push(count),
+            _ => traits.push(TraitDistribution {
+                name: value.name,
+                values: vec![count],
+            }),
+        }
+    }
+
+    traits
+}
+
+/// Recomputes the rarity of every token of the kinds, which are scored among the tokens of
+/// the same network, contract and kind.
+///
+/// The statistical score is the sum of `-ln(frequency)` of the token's trait values, the
+/// trait-normalized score is the sum of `1 / frequency` of the values divided by the number of
+/// the values of their trait, the rank orders the latter, the rank 1 is the rarest. The scored
+/// tokens whose rarity changes get a new rarity version, their row version is only bumped by
+/// their own writes so that the `If-Match` of the clients stays valid. A JSON `null` is a trait
+/// value of its own.
+///
+/// Every kind is locked until the commit, in the order of the set, so that the concurrent writes
+/// to the same kind are rescored one after the other rather than from the same snapshot.
+#[tracing::instrument(name = "Recompute rarity of nft tokens", skip(tx))]
+pub async fn recompute_rarity_db(
+    network: &Network,
+    kinds: &BTreeSet<(String, String)>,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), anyhow::Error> {
+    for (contract_id, kind) in kinds {
+        sqlx::query!(
+            r#"
+            SELECT 1 AS "locked!"
+            FROM pg_advisory_xact_lock(hashtext('rarity/' || $1 || '/' || $2 || '/' || $3))
+            "#,
+            network.as_str(),
+            contract_id,
+            kind,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to lock the rarity of nft tokens in database.")?;
+        sqlx::query!(
+            r#"
+            WITH tokens AS (
+                SELECT id, model
+                FROM nft_tokens
+                WHERE network = $1 AND contract_id = $2 AND model->>'kind' = $3
+            ),
+            total AS (
+                SELECT count(*)::float8 AS total FROM tokens
+            ),
+            traits AS (
+                SELECT tokens.id, t.key, t.value
+                -- the values are compared as jsonb so that the nulls aren't dropped by the joins
+                FROM tokens, jsonb_each(tokens.model - 'kind') AS t
+            ),
+            value_counts AS (
+                SELECT key, value, count(*)::float8 AS count FROM traits GROUP BY key, value
+            ),
+            trait_sizes AS (
+                SELECT key, count(*)::float8 AS size FROM value_counts GROUP BY key
+            ),
+            scores AS (
+                SELECT traits.id,
+                    sum(-ln(value_counts.count / total.total)) AS statistical,
+                    sum(total.total / value_counts.count / trait_sizes.size) AS trait_normalized
+                FROM traits
+                    JOIN value_counts USING (key, value)
+                    JOIN trait_sizes USING (key)
+                    CROSS JOIN total
+                GROUP BY traits.id
+            ),
+            ranks AS (
+                SELECT id, statistical, trait_normalized,
+                    rank() OVER (ORDER BY trait_normalized DESC) AS rank
+                FROM scores
+            )
+            UPDATE nft_tokens AS n
+            SET rarity_statistical_score = ranks.statistical,
+                rarity_trait_normalized_score = ranks.trait_normalized,
+                rarity_rank = ranks.rank,
+                -- the first rarity of a token is scored along with its insert
+                rarity_version = n.rarity_version + CASE WHEN n.rarity_rank IS null THEN 0 ELSE 1 END
+            FROM ranks
+            WHERE n.id = ranks.id
+                AND (n.rarity_statistical_score, n.rarity_trait_normalized_score, n.rarity_rank)
+                    IS DISTINCT FROM (ranks.statistical, ranks.trait_normalized, ranks.rank)
+            "#,
+            network.as_str(),
+            contract_id,
+            kind,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facet(name: &str, value: &str, count: i64) -> TraitFacet {
+        TraitFacet {
+            name: name.to_string(),
+            value: value.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn traits_are_grouped_by_name() {
+        let values = vec![
+            facet("cap", "red", 3),
+            facet("cap", "blue", 1),
+            facet("eyes", "green", 4),
+        ];
+
+        let actual = group_traits(4, values);
+        let names: Vec<_> = actual.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["cap", "eyes"]);
+        assert_eq!(
+            actual[0].values[0],
+            TraitValueCount {
+                value: "red".to_string(),
+                count: 3,
+                frequency: 0.75,
+            }
+        );
+        assert_eq!(actual[0].values.len(), 2);
+        assert_eq!(actual[1].values[0].frequency, 1.0);
+    }
+
+    #[test]
+    fn no_traits_are_grouped_into_nothing() {
+        assert!(group_traits(0, vec![]).is_empty());
+    }
+}
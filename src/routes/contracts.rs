@@ -1,74 +1,433 @@
-use crate::errors::ContractError;
+use crate::audit::{self, AuditContext};
+use crate::cache::{if_match_holds, ResponseCache, RowVersion};
+use crate::config::{PaginationSettings, TokenIdSettings};
+use crate::domain::{
+    ContractRules, ContractsHistoryFilter, ContractsSelector, Limit, LimitBounds, Offset,
+    ParseToPositiveInt, ValidationErrors, Validator,
+};
+use crate::errors::{AuthError, ContractError, ContractReadError, NetworkError};
+use crate::network::Network;
+use crate::routes::{PaginationQuery, RowsJsonReport};
+use actix_web::http::header::{IfMatch, IfNoneMatch, ETAG};
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
 use battlemon_models::config::ContractConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::{types::Json, PgPool, Postgres, Transaction};
+use utoipa::{IntoParams, ToSchema};
 
+/// A stored contracts config. Every upsert or rollback appends a new version of the network's
+/// config, the one with the greatest `version` is active.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ContractsVersion {
+    pub version: i64,
+    #[schema(value_type = ContractConfig)]
+    pub contracts_config: Json<ContractConfig>,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub rollback_of: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ContractsQuery {
+    /// The version to return instead of the active one.
+    pub version: Option<i64>,
+    /// Return the version which was active at the moment.
+    pub at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct ContractsRollback {
+    pub version: i64,
+}
+
+impl TryFrom<ContractsQuery> for ContractsSelector {
+    type Error = ValidationErrors;
+
+    fn try_from(query: ContractsQuery) -> Result<Self, Self::Error> {
+        ContractsSelector::parse(query.version, query.at).map_err(Into::into)
+    }
+}
+
+impl TryFrom<(PaginationQuery, LimitBounds)> for ContractsHistoryFilter {
+    type Error = ValidationErrors;
+
+    fn try_from((query, bounds): (PaginationQuery, LimitBounds)) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let limit = validator.check(Limit::parse(query.limit, bounds));
+        let offset = validator.check(Offset::parse(query.offset));
+
+        validator.finish(|| {
+            Some(Self {
+                limit: limit?,
+                offset: offset?,
+            })
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/contracts",
+    tag = "contracts",
+    params(
+        ContractsQuery,
+        ("If-None-Match" = Option<String>, Header, description = "The entity tags of the cached versions."),
+    ),
+    responses(
+        (status = 200, description = "The contracts ids.", body = ContractConfig),
+        (status = 304, description = "The response matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        ContractReadError,
+    )
+)]
 #[tracing::instrument(name = "List contracts ids", skip(pool))]
-pub async fn get_contracts(pool: web::Data<PgPool>) -> Result<HttpResponse, ContractError> {
-    let contracts = get_contracts_db(pool)
+pub async fn get_contracts(
+    web::Query(query): web::Query<ContractsQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
+) -> Result<HttpResponse, ContractError> {
+    let selector: ContractsSelector = query.try_into().map_err(ContractError::ValidationError)?;
+    let contracts = get_contracts_db(selector, &network, &pool)
         .await
-        .context("Failed to get the contracts id data from database.")?;
+        .context("Failed to get the contracts id data from database.")?
+        .ok_or_else(|| ContractError::NotFound(not_found_message(selector, &network)))?;
 
-    Ok(HttpResponse::Ok().json(contracts))
+    Ok(
+        RowVersion(contracts.version)
+            .respond(if_none_match.as_deref(), &contracts.contracts_config),
+    )
 }
 
-struct Record {
-    contracts_config: Json<ContractConfig>,
+fn not_found_message(selector: ContractsSelector, network: &Network) -> String {
+    match selector {
+        ContractsSelector::Active => {
+            format!("The contracts ids aren't configured for the `{network}` network.")
+        }
+        ContractsSelector::Version(version) => {
+            format!(
+                "The contracts ids version `{version}` doesn't exist for the `{network}` network."
+            )
+        }
+        ContractsSelector::At(at) => {
+            format!("The contracts ids weren't configured for the `{network}` network at `{at}`.")
+        }
+    }
+}
+
+/// The rules of the contracts the token related requests may name, i.e. the active ones.
+#[tracing::instrument(name = "Load the rules of the active contracts", skip(pool, token_ids))]
+pub async fn get_contract_rules<'a>(
+    network: &Network,
+    pool: &PgPool,
+    token_ids: &'a TokenIdSettings,
+) -> Result<ContractRules<'a>, anyhow::Error> {
+    let stored = get_nft_contract_ids_db(network, pool)
+        .await
+        .context("Failed to get the nft contracts ids from the database.")?;
+    let configured = network.nft_contracts().iter().cloned();
+
+    Ok(ContractRules::new(
+        stored.into_iter().chain(configured),
+        token_ids,
+    ))
+}
+
+/// The nft contracts of every version of the contracts config, so that the tokens of a
+/// replaced contract stay reachable after an upsert or a rollback.
+#[tracing::instrument(name = "Query nft contracts ids from database", skip(pool))]
+pub async fn get_nft_contract_ids_db(
+    network: &Network,
+    pool: &PgPool,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT contracts_config ->> 'nft_contract_id' AS "nft_contract_id!"
+        FROM contracts
+        WHERE network = $1
+        "#,
+        network.as_str(),
+    )
+    .fetch_all(pool)
+    .await
 }
 
 #[tracing::instrument(name = "Query contracts ids from database", skip(pool))]
-pub async fn get_contracts_db(pool: web::Data<PgPool>) -> Result<ContractConfig, anyhow::Error> {
+pub async fn get_contracts_db(
+    selector: ContractsSelector,
+    network: &Network,
+    pool: &PgPool,
+) -> Result<Option<ContractsVersion>, anyhow::Error> {
+    let (version, at) = match selector {
+        ContractsSelector::Active => (None, None),
+        ContractsSelector::Version(version) => (Some(version), None),
+        ContractsSelector::At(at) => (None, Some(at)),
+    };
     let record = sqlx::query_as!(
-        Record,
+        ContractsVersion,
+        r#"
+        SELECT version, contracts_config as "contracts_config: Json<ContractConfig>",
+            created_by, created_at, rollback_of
+        FROM contracts
+        WHERE network = $1
+            AND ($2::bigint IS null OR version = $2)
+            AND ($3::timestamptz IS null OR created_at <= $3)
+        ORDER BY version DESC
+        LIMIT 1
+        "#,
+        network.as_str(),
+        version,
+        at,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record)
+}
+
+#[utoipa::path(
+    get,
+    path = "/contracts/history",
+    tag = "contracts",
+    params(PaginationQuery),
+    responses(
+        (status = 200, description = "A page of the contracts ids versions, the latest first.", body = ContractsHistoryReport),
+        NetworkError,
+        ContractReadError,
+    )
+)]
+#[tracing::instrument(name = "List contracts ids history", skip(pool, pagination))]
+pub async fn get_contracts_history(
+    web::Query(filter): web::Query<PaginationQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+) -> Result<HttpResponse, ContractError> {
+    let filter: ContractsHistoryFilter = (filter, pagination.bounds("contracts"))
+        .try_into()
+        .map_err(ContractError::ValidationError)?;
+    let history = get_contracts_history_db(&filter, &network, &pool)
+        .await
+        .context("Failed to get the contracts ids history from database.")?;
+
+    Ok(HttpResponse::Ok().json(RowsJsonReport::from_rows(history, filter.limit())))
+}
+
+#[tracing::instrument(name = "Query contracts ids history from database", skip(pool))]
+pub async fn get_contracts_history_db(
+    filter: &ContractsHistoryFilter,
+    network: &Network,
+    pool: &PgPool,
+) -> Result<Vec<ContractsVersion>, anyhow::Error> {
+    let rows = sqlx::query_as!(
+        ContractsVersion,
         r#"
-        SELECT contracts_config as "contracts_config: Json<ContractConfig>" FROM contracts
+        SELECT version, contracts_config as "contracts_config: Json<ContractConfig>",
+            created_by, created_at, rollback_of
+        FROM contracts
+        WHERE network = $1
+        ORDER BY version DESC
+        LIMIT $2 OFFSET $3
         "#,
+        network.as_str(),
+        filter.limit() + 1,
+        filter.offset(),
     )
-    .fetch_one(pool.get_ref())
+    .fetch_all(pool)
     .await?;
 
-    Ok(record.contracts_config.0)
+    Ok(rows)
 }
 
-#[tracing::instrument(name = "Upsert contract ids", skip(pool))]
+#[utoipa::path(
+    post,
+    path = "/contracts",
+    tag = "contracts",
+    params(
+        ("If-Match" = Option<String>, Header, description = "The entity tag of the version the write is based on, `412` if it isn't current."),
+    ),
+    request_body = ContractConfig,
+    responses(
+        (status = 201, description = "The contracts ids are stored as the new active version.", body = ContractsVersion),
+        NetworkError,
+        AuthError,
+        ContractError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Upsert contract ids", skip(pool, cache))]
 pub async fn insert_contracts(
     web::Json(contract_config): web::Json<ContractConfig>,
+    network: Network,
     pool: web::Data<PgPool>,
+    cache: web::Data<ResponseCache>,
+    audit: AuditContext,
+    if_match: Option<web::Header<IfMatch>>,
 ) -> Result<HttpResponse, ContractError> {
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    upsert_contracts_db(contract_config, &mut tx)
+    let contracts = store_contracts_version(
+        contract_config,
+        None,
+        if_match.as_deref(),
+        &network,
+        &audit,
+        &mut tx,
+    )
+    .await?;
+    tx.commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new contracts ids version.")?;
+    // the contracts ids scope the data of every route
+    cache.invalidate(&network, None).await;
+
+    Ok(HttpResponse::Created()
+        .insert_header((ETAG, RowVersion(contracts.version).etag()))
+        .json(contracts))
+}
+
+#[utoipa::path(
+    post,
+    path = "/contracts/rollback",
+    tag = "contracts",
+    params(
+        ("If-Match" = Option<String>, Header, description = "The entity tag of the version the write is based on, `412` if it isn't current."),
+    ),
+    request_body = ContractsRollback,
+    responses(
+        (status = 201, description = "The rolled back contracts ids are stored as the new active version.", body = ContractsVersion),
+        NetworkError,
+        AuthError,
+        ContractError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Roll back contract ids", skip(pool, cache))]
+pub async fn rollback_contracts(
+    web::Json(rollback): web::Json<ContractsRollback>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    cache: web::Data<ResponseCache>,
+    audit: AuditContext,
+    if_match: Option<web::Header<IfMatch>>,
+) -> Result<HttpResponse, ContractError> {
+    let selector = ContractsSelector::parse(Some(rollback.version), None)
+        .map_err(|e| ContractError::ValidationError(e.into()))?;
+    let target = get_contracts_db(selector, &network, &pool)
         .await
-        .context("Failed to upsert the contracts ids data into the database.")?;
+        .context("Failed to get the contracts id data from database.")?
+        .ok_or_else(|| ContractError::NotFound(not_found_message(selector, &network)))?;
+
+    let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+    let contracts = store_contracts_version(
+        target.contracts_config.0,
+        Some(target.version),
+        if_match.as_deref(),
+        &network,
+        &audit,
+        &mut tx,
+    )
+    .await?;
     tx.commit()
         .await
-        .context("Failed to commit SQL transaction to store a new subscriber.")?;
-    Ok(HttpResponse::Created().finish())
+        .context("Failed to commit SQL transaction to roll back the contracts ids.")?;
+    cache.invalidate(&network, None).await;
+
+    Ok(HttpResponse::Created()
+        .insert_header((ETAG, RowVersion(contracts.version).etag()))
+        .json(contracts))
 }
 
-#[tracing::instrument(name = "Store or update contracts ids to database", skip(tx))]
-pub async fn upsert_contracts_db(
+/// Appends the contracts ids version, unless the `If-Match` entity tag isn't the active
+/// version, which the concurrent upserts of the network can't change until the commit.
+async fn store_contracts_version(
     contracts_config: ContractConfig,
+    rollback_of: Option<i64>,
+    if_match: Option<&IfMatch>,
+    network: &Network,
+    audit: &AuditContext,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<ContractsVersion, ContractError> {
+    let active = get_active_contracts_for_update_db(network, tx).await?;
+    if !if_match_holds(if_match, active.as_ref().map(|a| RowVersion(a.version))) {
+        return Err(ContractError::PreconditionFailed(format!(
+            "The contracts ids of the `{network}` network were changed, the active version is {}.",
+            active.map_or_else(|| "missing".to_string(), |a| a.version.to_string())
+        )));
+    }
+    let before = active.map(|active| active.contracts_config);
+    let contracts = insert_contracts_db(contracts_config, rollback_of, network, audit.user_id, tx)
+        .await
+        .context("Failed to store the contracts ids data into the database.")?;
+    audit
+        .record(
+            &[contracts.version.to_string()],
+            audit::updated(&before, &contracts.contracts_config),
+            tx,
+        )
+        .await?;
+
+    Ok(contracts)
+}
+
+/// The active contracts ids, locked until the commit by the lock of the network's versions
+/// since the new versions are appended rather than updated.
+#[tracing::instrument(name = "Lock contracts ids in database", skip(tx))]
+async fn get_active_contracts_for_update_db(
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
+) -> Result<Option<ContractsVersion>, anyhow::Error> {
     sqlx::query!(
+        r#"SELECT 1 AS "locked!" FROM pg_advisory_xact_lock(hashtext('contracts/' || $1))"#,
+        network.as_str(),
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to lock the contracts ids in database.")?;
+    let active = sqlx::query_as!(
+        ContractsVersion,
         r#"
-        DELETE FROM contracts;
-        "#
+        SELECT version, contracts_config as "contracts_config: Json<ContractConfig>",
+            created_by, created_at, rollback_of
+        FROM contracts
+        WHERE network = $1
+        ORDER BY version DESC
+        LIMIT 1
+        "#,
+        network.as_str(),
     )
-    .execute(&mut *tx)
-    .await?;
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to get the current contracts ids from database.")?;
 
-    sqlx::query_as!(
-        ContractConfig,
+    Ok(active)
+}
+
+#[tracing::instrument(name = "Store contracts ids version to database", skip(tx))]
+pub async fn insert_contracts_db(
+    contracts_config: ContractConfig,
+    rollback_of: Option<i64>,
+    network: &Network,
+    created_by: i64,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<ContractsVersion, anyhow::Error> {
+    let contracts = sqlx::query_as!(
+        ContractsVersion,
         r#"
-        INSERT INTO contracts (contracts_config)
-        VALUES ($1)
+        INSERT INTO contracts (contracts_config, created_by, rollback_of, network)
+        VALUES ($1, $2, $3, $4)
+        RETURNING version, contracts_config as "contracts_config: Json<ContractConfig>",
+            created_by, created_at, rollback_of
         "#,
         Json(contracts_config) as _,
+        created_by,
+        rollback_of,
+        network.as_str(),
     )
-    .execute(tx)
+    .fetch_one(tx)
     .await?;
 
-    Ok(())
+    Ok(contracts)
 }
@@ -1,3 +1,9 @@
+#[utoipa::path(
+    get,
+    path = "/health_check",
+    tag = "health_check",
+    responses((status = 200, description = "The service is up."))
+)]
 #[tracing::instrument(name = "Health check", fields(request_id = %uuid::Uuid::new_v4()))]
 pub async fn health_check() -> actix_web::HttpResponse {
     actix_web::HttpResponse::Ok().finish()
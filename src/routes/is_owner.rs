@@ -1,40 +1,239 @@
-use crate::errors::IsOwnerError;
+use std::collections::{HashMap, HashSet};
+
+use crate::config::TokenIdSettings;
+use crate::domain::{
+    AccountId, AsInner, ContractId, ContractRules, IntoInner, TokenId, ValidationErrors, Validator,
+};
+use crate::errors::{IsOwnerError, NetworkError};
+use crate::network::Network;
+use crate::routes::get_contract_rules;
 use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct IsOwnerQuery {
+    /// Only the tokens of the nft contract, one of the network's nft contracts, which must be
+    /// named when the network has several.
+    pub contract_id: Option<String>,
+    /// Whether to report the ownership of every token, `false` by default.
+    pub details: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TokenOwnerQuery {
+    /// The nft contract of the token, one of the network's nft contracts, which must be named
+    /// when the network has several.
+    pub contract_id: Option<String>,
+}
 
-#[tracing::instrument(name = "Checking if user is owner of provided tokens", skip(pool))]
+/// The ownership of a token by the user.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Ownership {
+    Owned,
+    /// The token is owned by another account.
+    NotOwned,
+    /// There's no token with the id, in the nft contract if selected.
+    UnknownToken,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TokenOwnership {
+    pub token_id: String,
+    pub ownership: Ownership,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct IsOwnerReport {
+    /// Whether the user owns all the tokens.
+    pub result: bool,
+    /// The ownership of every distinct token in the order of the request, missing unless
+    /// `details` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<Vec<TokenOwnership>>,
+}
+
+impl IsOwnerReport {
+    fn new(tokens: Vec<TokenOwnership>, details: bool) -> Self {
+        let result = tokens.iter().all(|t| t.ownership == Ownership::Owned);
+
+        Self {
+            result,
+            tokens: details.then_some(tokens),
+        }
+    }
+}
+
+/// Validates the owner candidate, the contract and every token id, which is named by its field,
+/// e.g. `[2]` in a list, and drops the repeated token ids.
+fn parse_is_owner_request<'a>(
+    owner_candidate_id: &str,
+    contract_id: Option<&str>,
+    tokens: impl IntoIterator<Item = (String, &'a str)>,
+    rules: &ContractRules,
+) -> Result<(AccountId, ContractId, Vec<String>), ValidationErrors> {
+    let mut validator = Validator::default();
+    let owner_candidate_id = validator.check(AccountId::parse("user_id", owner_candidate_id));
+    let format = rules.token_id_format(contract_id);
+    let contract_id = validator.check(ContractId::parse_unambiguous(contract_id, rules));
+    let mut token_ids = Vec::new();
+    let mut seen = HashSet::new();
+    for (field, token_id) in tokens {
+        if let Some(token_id) =
+            validator.check(TokenId::parse_field(&field, Some(token_id), format))
+        {
+            token_ids.extend(
+                token_id
+                    .into_inner()
+                    .filter(|token_id| seen.insert(token_id.clone())),
+            );
+        }
+    }
+
+    validator.finish(|| Some((owner_candidate_id?, contract_id?, token_ids)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/{user_id}/is_owner",
+    tag = "users",
+    params(
+        ("user_id" = String, Path, description = "The account id of the owner candidate."),
+        IsOwnerQuery,
+    ),
+    request_body = [String],
+    responses(
+        (status = 200, description = "Whether the user owns all the distinct tokens.", body = IsOwnerReport,
+            example = json!({ "result": false, "tokens": [
+                { "token_id": "1", "ownership": "owned" },
+                { "token_id": "2", "ownership": "unknown_token" },
+            ] })),
+        NetworkError,
+        IsOwnerError,
+    )
+)]
+#[tracing::instrument(
+    name = "Checking if user is owner of provided tokens",
+    skip(pool, token_ids)
+)]
 pub async fn is_owner(
     web::Json(tokens): web::Json<Vec<String>>,
     path: web::Path<String>,
+    web::Query(query): web::Query<IsOwnerQuery>,
+    network: Network,
     pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
 ) -> Result<HttpResponse, IsOwnerError> {
-    // todo ddd here (validation token_id, owner)
-    let owner_candidate_id = path.into_inner();
-    let ret = is_owner_db(&owner_candidate_id, &tokens, &pool).await?;
-    let json = serde_json::json!({ "result": ret });
-    Ok(HttpResponse::Ok().json(json))
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let (owner_candidate_id, contract_id, tokens) = parse_is_owner_request(
+        &path.into_inner(),
+        query.contract_id.as_deref(),
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token_id)| (format!("[{i}]"), token_id.as_str())),
+        &rules,
+    )
+    .map_err(IsOwnerError::ValidationError)?;
+    let tokens = token_ownership_db(
+        owner_candidate_id.as_str(),
+        contract_id.as_inner(),
+        &tokens,
+        &network,
+        &pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(IsOwnerReport::new(
+        tokens,
+        query.details.unwrap_or_default(),
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}/is_owner/{token_id}",
+    tag = "users",
+    params(
+        ("user_id" = String, Path, description = "The account id of the owner candidate."),
+        ("token_id" = String, Path, description = "The id of the token."),
+        TokenOwnerQuery,
+    ),
+    responses(
+        (status = 200, description = "Whether the user owns the token.", body = IsOwnerReport,
+            example = json!({ "result": false, "tokens": [{ "token_id": "1", "ownership": "not_owned" }] })),
+        NetworkError,
+        IsOwnerError,
+    )
+)]
+#[tracing::instrument(name = "Checking if user is owner of the token", skip(pool, token_ids))]
+pub async fn is_token_owner(
+    path: web::Path<(String, String)>,
+    web::Query(query): web::Query<TokenOwnerQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
+) -> Result<HttpResponse, IsOwnerError> {
+    let (user_id, token_id) = path.into_inner();
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let (owner_candidate_id, contract_id, tokens) = parse_is_owner_request(
+        &user_id,
+        query.contract_id.as_deref(),
+        [("token_id".to_string(), token_id.as_str())],
+        &rules,
+    )
+    .map_err(IsOwnerError::ValidationError)?;
+    let tokens = token_ownership_db(
+        owner_candidate_id.as_str(),
+        contract_id.as_inner(),
+        &tokens,
+        &network,
+        &pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(IsOwnerReport::new(tokens, true)))
 }
 
 #[tracing::instrument(
-    name = "Checking that tokens belongs to `owner_candidate_id`",
+    name = "Checking which tokens belong to `owner_candidate_id`",
     skip(pool)
 )]
-async fn is_owner_db(
+async fn token_ownership_db(
     owner_candidate_id: &str,
+    contract_id: Option<&str>,
     tokens: &[String],
+    network: &Network,
     pool: &PgPool,
-) -> Result<bool, anyhow::Error> {
-    let tokens_ids = sqlx::query!(
-        r#"
-        SELECT token_id
-        FROM nft_tokens
-        WHERE owner_id = $1 AND token_id = ANY($2)
-        "#,
+) -> Result<Vec<TokenOwnership>, anyhow::Error> {
+    // a token id may be shared by the tokens of several contracts, the contract is only omitted
+    // for a network with a single one
+    let rows = sqlx::query_file!(
+        "queries/token_ownership.sql",
+        network.as_str(),
         owner_candidate_id,
+        contract_id,
         tokens
     )
     .fetch_all(pool)
     .await?;
+    let owned: HashMap<_, _> = rows.into_iter().map(|r| (r.token_id, r.owned)).collect();
+
+    let tokens = tokens.iter().map(|token_id| {
+        let ownership = match owned.get(token_id) {
+            Some(true) => Ownership::Owned,
+            Some(false) => Ownership::NotOwned,
+            None => Ownership::UnknownToken,
+        };
+        TokenOwnership {
+            token_id: token_id.clone(),
+            ownership,
+        }
+    });
 
-    Ok(tokens_ids.len() == tokens.len())
+    Ok(tokens.collect())
 }
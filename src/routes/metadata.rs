@@ -0,0 +1,228 @@
+use actix_web::http::header::IfNoneMatch;
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use battlemon_models::nft::NftTokenForRest;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::cache::RowVersion;
+use crate::config::{ContractMetadataSettings, PaginationSettings, TokenIdSettings};
+use crate::domain::ContractsSelector;
+use crate::errors::{
+    ContractError, ContractReadError, NetworkError, NftTokensError, NftTokensReadError,
+    RateLimitError,
+};
+use crate::network::Network;
+use crate::routes::{find_nft_token, get_contract_rules, get_contracts_db, NftTokenItemQuery};
+
+/// The version of the NEP-177 metadata standard.
+pub const NFT_METADATA_SPEC: &str = "nft-1.0.0";
+
+/// The NEP-177 `NFTContractMetadata` of a nft contract.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct NftContractMetadata {
+    /// The version of the standard, `nft-1.0.0`.
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    /// A data URL of a small image of the contract.
+    pub icon: Option<String>,
+    /// The base of the relative `media` and `reference` URLs of the tokens.
+    pub base_uri: Option<String>,
+    /// A URL of a JSON file with more info.
+    pub reference: Option<String>,
+    /// The base64-encoded sha256 hash of the `reference` file.
+    pub reference_hash: Option<String>,
+}
+
+/// The NEP-177 `TokenMetadata` of a nft token.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// A URL of the token's media, relative to the contract's `base_uri` if it's set.
+    pub media: Option<String>,
+    /// The base64-encoded sha256 hash of the `media` file.
+    pub media_hash: Option<String>,
+    /// The number of copies of the token's metadata.
+    pub copies: Option<u64>,
+    /// When the token was issued, in Unix epoch milliseconds.
+    pub issued_at: Option<String>,
+    /// When the token expires, in Unix epoch milliseconds.
+    pub expires_at: Option<String>,
+    /// When the token starts being valid, in Unix epoch milliseconds.
+    pub starts_at: Option<String>,
+    /// When the token was last updated, in Unix epoch milliseconds.
+    pub updated_at: Option<String>,
+    /// The stringified JSON of the token's `model`, e.g. `{"model":{"kind":"lemon",...}}`.
+    pub extra: Option<String>,
+    /// A URL of a JSON file with more info.
+    pub reference: Option<String>,
+    /// The base64-encoded sha256 hash of the `reference` file.
+    pub reference_hash: Option<String>,
+}
+
+impl From<&NftTokenForRest> for TokenMetadata {
+    fn from(nft_token: &NftTokenForRest) -> Self {
+        let extra = serde_json::json!({ "model": nft_token.model });
+
+        Self {
+            title: nft_token.title.clone(),
+            description: nft_token.description.clone(),
+            media: Some(nft_token.media.clone()),
+            media_hash: nft_token.media_hash.clone(),
+            // the contracts emit the copies as a string, the standard has a number
+            copies: nft_token.copies.as_deref().and_then(|c| c.parse().ok()),
+            issued_at: nft_token.issued_at.clone(),
+            expires_at: nft_token.expires_at.clone(),
+            starts_at: None,
+            updated_at: None,
+            extra: Some(extra.to_string()),
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TokenMetadataQuery {
+    /// The nft contract of the token, one of the active contracts config, the token of any
+    /// contract if omitted.
+    pub contract_id: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/nft_tokens/{token_id}/metadata",
+    tag = "nft_tokens",
+    params(
+        ("token_id" = String, Path, description = "The id of the token."),
+        TokenMetadataQuery,
+        ("If-None-Match" = Option<String>, Header, description = "The entity tags of the cached versions."),
+    ),
+    responses(
+        (status = 200, description = "The NEP-177 metadata of the token.", body = TokenMetadata),
+        (status = 304, description = "The token matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        NftTokensReadError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle nft token metadata request",
+    skip(pool, pagination, token_ids)
+)]
+pub async fn get_nft_token_metadata(
+    token_id: web::Path<String>,
+    web::Query(query): web::Query<TokenMetadataQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
+) -> Result<HttpResponse, NftTokensError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let query = NftTokenItemQuery {
+        contract_id: query.contract_id,
+        expand: None,
+    };
+    let nft_token = find_nft_token(
+        token_id.into_inner(),
+        query,
+        &network,
+        pool,
+        &pagination,
+        &rules,
+    )
+    .await?;
+    let metadata = TokenMetadata::from(&nft_token.item.nft_token);
+
+    Ok(RowVersion(nft_token.item.version).respond(if_none_match.as_deref(), &metadata))
+}
+
+#[utoipa::path(
+    get,
+    path = "/contract_metadata",
+    tag = "contracts",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "The entity tags of the cached versions."),
+    ),
+    responses(
+        (status = 200, description = "The NEP-177 metadata of the active nft contract.", body = NftContractMetadata),
+        (status = 304, description = "The response matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        ContractReadError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle contract metadata request",
+    skip(pool, contract_metadata)
+)]
+pub async fn get_contract_metadata(
+    network: Network,
+    pool: web::Data<PgPool>,
+    contract_metadata: web::Data<ContractMetadataSettings>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
+) -> Result<HttpResponse, ContractError> {
+    let contracts = get_contracts_db(ContractsSelector::Active, &network, &pool)
+        .await
+        .context("Failed to get the active contracts ids from the database.")?
+        .ok_or_else(|| {
+            ContractError::NotFound(format!(
+                "The contracts ids aren't configured for the `{network}` network."
+            ))
+        })?;
+    let settings = contract_metadata.metadata(&contracts.contracts_config.nft_contract_id);
+    let metadata = NftContractMetadata {
+        spec: NFT_METADATA_SPEC.to_string(),
+        name: settings.name.clone(),
+        symbol: settings.symbol.clone(),
+        icon: settings.icon.clone(),
+        base_uri: settings.base_uri.clone(),
+        reference: None,
+        reference_hash: None,
+    };
+
+    Ok(RowVersion(contracts.version).respond(if_none_match.as_deref(), &metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use battlemon_models::nft::{Firearm, ModelKind};
+    use sqlx::types::Json;
+
+    use super::*;
+
+    #[test]
+    fn token_metadata_carries_the_model_in_extra() {
+        let model = ModelKind::Firearm(Firearm {
+            level: 1,
+            r#type: "rifle".to_string(),
+        });
+        let nft_token = NftTokenForRest {
+            owner_id: "alice.near".to_string(),
+            token_id: "1".to_string(),
+            title: Some("Rifle".to_string()),
+            description: None,
+            media: "rifle.png".to_string(),
+            media_hash: None,
+            copies: Some("10".to_string()),
+            issued_at: Some("1662000000000".to_string()),
+            expires_at: None,
+            model: Json(model),
+        };
+
+        let metadata = TokenMetadata::from(&nft_token);
+
+        assert_eq!(metadata.copies, Some(10));
+        assert_eq!(metadata.media.as_deref(), Some("rifle.png"));
+        let extra: serde_json::Value = serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(
+            extra,
+            serde_json::json!({ "model": { "kind": "firearm", "level": 1, "type": "rifle" } })
+        );
+    }
+}
@@ -1,36 +1,198 @@
+use std::collections::BTreeSet;
+
+use actix_web::http::header::{IfMatch, IfNoneMatch, ETAG};
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
+use battlemon_models::market::ask::AskForDb;
+use battlemon_models::market::bid::BidForDb;
+use battlemon_models::market::sale::SaleForDb;
 use battlemon_models::nft::{ModelKind, NftKind, NftTokenForRest};
 use chrono::Utc;
-use serde::Deserialize;
+use futures_util::stream::{BoxStream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
 use sqlx::types::Json;
 use sqlx::{PgPool, Postgres, Transaction};
+use utoipa::{IntoParams, ToSchema};
 
+use crate::audit::{self, AuditContext};
+use crate::cache::{if_match_holds, RepresentationVersion, ResponseCache, RowVersion};
+use crate::config::{PaginationSettings, StreamingSettings, TokenIdSettings};
 use crate::domain::{
-    Limit, NftTokenDays, NftTokenFilter, NftTokenOwnerId, Offset, Parse, ParseToPositiveInt,
-    TokenId,
+    ContractId, ContractRules, FieldError, Limit, LimitBounds, NewNftToken, NftTokenDays,
+    NftTokenExpand, NftTokenFilter, NftTokenOwnerId, NftTokenSort, Offset, Parse,
+    ParseToPositiveInt, TokenId, ValidationErrors, Validator,
+};
+use crate::errors::{AuthError, NetworkError, NftTokensError, NftTokensReadError, RateLimitError};
+use crate::media::MediaResolver;
+use crate::network::Network;
+use crate::routes::{
+    get_contract_rules, recompute_rarity_db, send_rows, ContractScoped, ExportQuery, RowStream,
 };
-use crate::errors::NftTokensError;
-use crate::routes::RowsJsonReport;
 
-#[derive(Debug, Deserialize, Clone)]
+/// The columns of the exported nft tokens, the nested ones are JSON-encoded in CSV.
+pub const NFT_TOKEN_COLUMNS: [&str; 17] = [
+    "contract_id",
+    "owner_id",
+    "token_id",
+    "title",
+    "description",
+    "media",
+    "media_hash",
+    "copies",
+    "issued_at",
+    "expires_at",
+    "model",
+    "rarity",
+    "version",
+    "media_url",
+    "ask",
+    "best_bid",
+    "last_sale",
+];
+
+#[derive(Debug, Default, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct NftTokenQuery {
+    /// Prohibited for the `nft_tokens` route.
     pub days: Option<i64>,
+    /// The page size, bounded by the resource's maximum.
     pub limit: Option<i64>,
+    /// The number of rows to skip.
     pub offset: Option<i64>,
+    /// Only the tokens of the owner.
     pub owner_id: Option<String>,
+    /// Only the tokens of the nft contract, one of the active contracts config.
+    pub contract_id: Option<String>,
+    /// Only the token with the id.
     pub token_id: Option<String>,
     pub nft_trait: Option<String>,
+    /// Only the tokens of the kind, e.g. `lemon`.
+    #[param(value_type = Option<String>)]
     pub nft_kind: Option<NftKind>,
+    /// The order of the tokens, `id` by default or `rarity`, the rarest first.
+    #[param(value_type = Option<String>)]
+    pub sort: Option<NftTokenSort>,
+    /// The market rows to embed in the tokens, a comma separated list of `ask` (the cheapest),
+    /// `best_bid` (the highest unexpired) and `last_sale`.
+    pub expand: Option<String>,
+    /// Only the tokens with (`true`) or without (`false`) an ask.
+    pub listed: Option<bool>,
+    /// Only the tokens with (`true`) or without (`false`) an unexpired bid.
+    pub has_bids: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NftTokenItemQuery {
+    /// The nft contract of the token, one of the network's nft contracts, which must be named
+    /// when several of them have a token with the id.
+    pub contract_id: Option<String>,
+    /// The market rows to embed in the token as in the `nft_tokens` route, an expanded token
+    /// isn't tagged by its version.
+    pub expand: Option<String>,
+}
+
+/// A stored nft token with its rarity.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct NftTokenRow {
+    #[serde(flatten)]
+    #[schema(value_type = NftTokenForRest)]
+    pub nft_token: NftTokenForRest,
+    /// Missing for the tokens without traits.
+    pub rarity: Option<Rarity>,
+    /// Bumped by every update of the token, its `If-Match` entity tag.
+    pub version: i64,
+    /// Bumped by the rescoring of the rarity caused by the other tokens, which changes the
+    /// representation but not the `If-Match` version of the token.
+    #[serde(skip)]
+    pub rarity_version: i64,
+    /// The `media` resolved to a http url, missing if it's relative and unresolvable.
+    #[serde(default)]
+    pub media_url: Option<String>,
+    /// The cheapest ask, missing unless expanded or if there's none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<AskForDb>)]
+    pub ask: Option<AskForDb>,
+    /// The highest unexpired bid, missing unless expanded or if there's none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<BidForDb>)]
+    pub best_bid: Option<BidForDb>,
+    /// The latest sale, missing unless expanded or if there's none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<SaleForDb>)]
+    pub last_sale: Option<SaleForDb>,
+}
+
+impl NftTokenRow {
+    /// A token without the expanded market rows.
+    pub fn new(
+        nft_token: NftTokenForRest,
+        rarity: Option<Rarity>,
+        version: i64,
+        rarity_version: i64,
+    ) -> Self {
+        Self {
+            nft_token,
+            rarity,
+            version,
+            rarity_version,
+            media_url: None,
+            ask: None,
+            best_bid: None,
+            last_sale: None,
+        }
+    }
+
+    /// The entity tag version of the token's representation.
+    pub fn representation_version(&self) -> RepresentationVersion {
+        RowVersion(self.version).with_derived(self.rarity_version)
+    }
+}
+
+/// The rarity of a token among the tokens of the same contract and kind, the higher scores
+/// are rarer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct Rarity {
+    /// The sum of `-ln(frequency)` of the token's trait values.
+    pub statistical_score: f64,
+    /// The sum of `1 / frequency` of the token's trait values, each divided by the number of
+    /// the values of its trait.
+    pub trait_normalized_score: f64,
+    /// The rank by the trait-normalized score, 1 is the rarest.
+    pub rank: i64,
+}
+
+impl Rarity {
+    pub fn from_columns(
+        statistical_score: Option<f64>,
+        trait_normalized_score: Option<f64>,
+        rank: Option<i64>,
+    ) -> Option<Self> {
+        match (statistical_score, trait_normalized_score, rank) {
+            (Some(statistical_score), Some(trait_normalized_score), Some(rank)) => Some(Self {
+                statistical_score,
+                trait_normalized_score,
+                rank,
+            }),
+            _ => None,
+        }
+    }
 }
 
-impl TryFrom<NftTokenQuery> for NftTokenFilter {
-    type Error = String;
-    fn try_from(query: NftTokenQuery) -> Result<Self, Self::Error> {
-        let token_id = TokenId::parse(query.token_id)?;
-        let owner_id = NftTokenOwnerId::parse(query.owner_id)?;
-        let limit = Limit::parse(query.limit)?;
-        let offset = Offset::parse(query.offset)?;
+impl TryFrom<(NftTokenQuery, LimitBounds, &ContractRules<'_>)> for NftTokenFilter {
+    type Error = ValidationErrors;
+    fn try_from(
+        (query, bounds, rules): (NftTokenQuery, LimitBounds, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse(query.contract_id.as_deref(), rules));
+        let format = rules.token_id_format(query.contract_id.as_deref());
+        let token_id = validator.check(TokenId::parse(query.token_id.as_deref(), format));
+        let owner_id = validator.check(NftTokenOwnerId::parse(query.owner_id));
+        let limit = validator.check(Limit::parse(query.limit, bounds));
+        let offset = validator.check(Offset::parse(query.offset));
+        validator.check(NftTokenDays::parse(query.days));
+        let expand = validator.check(NftTokenExpand::parse(query.expand.as_deref()));
         let nft_kind = query.nft_kind.map(|k| {
             serde_json::to_value(k)
                 .unwrap()
@@ -38,86 +200,365 @@ impl TryFrom<NftTokenQuery> for NftTokenFilter {
                 .unwrap()
                 .to_string()
         });
-        NftTokenDays::parse(query.days)?;
-
-        Ok(Self {
-            token_id,
-            owner_id,
-            limit,
-            offset,
-            nft_kind,
+
+        validator.finish(|| {
+            Some(Self {
+                contract_id: contract_id?,
+                token_id: token_id?,
+                owner_id: owner_id?,
+                limit: limit?,
+                offset: offset?,
+                nft_kind,
+                sort: query.sort.unwrap_or_default(),
+                expand: expand?,
+                listed: query.listed,
+                has_bids: query.has_bids,
+            })
         })
     }
 }
 
-#[tracing::instrument(name = "Handle nft tokens request", skip(filter, pool))]
+#[utoipa::path(
+    get,
+    path = "/nft_tokens",
+    tag = "nft_tokens",
+    params(NftTokenQuery, ExportQuery),
+    responses(
+        (status = 200, description = "A page of nft tokens, or every nft token as a file.", content(
+            ("application/json" = NftTokensReport),
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+        )),
+        NetworkError,
+        RateLimitError,
+        NftTokensReadError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle nft tokens request",
+    skip(filter, pool, pagination, token_ids, media, streaming)
+)]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_nft_tokens(
     web::Query(filter): web::Query<NftTokenQuery>,
+    web::Query(export): web::Query<ExportQuery>,
+    network: Network,
     pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    media: web::Data<MediaResolver>,
+    streaming: web::Data<StreamingSettings>,
 ) -> Result<HttpResponse, NftTokensError> {
-    let filter: NftTokenFilter = filter.try_into().map_err(NftTokensError::ValidationError)?;
-    let nft_tokens = get_nft_tokens_db(pool, &filter)
+    let export = export
+        .parse(&NFT_TOKEN_COLUMNS)
+        .map_err(NftTokensError::ValidationError)?;
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let filter: NftTokenFilter = (filter, pagination.bounds("nft_tokens"), &rules)
+        .try_into()
+        .map_err(NftTokensError::ValidationError)?;
+    let page = filter.limit();
+    // the row after the last one of a page or a file tells that it's truncated
+    let limit = match export.is_file() {
+        true => streaming.max_rows + 1,
+        false => page + 1,
+    };
+    let rows = RowStream::spawn(&streaming, move |sender| async move {
+        let nft_tokens = nft_tokens_db(&pool, &filter, limit, &network).map_ok(|mut nft_token| {
+            media.resolve_token(&mut nft_token);
+            nft_token
+        });
+        send_rows(nft_tokens, sender).await
+    })
+    .await
+    .context("Failed to get the nft tokens data from database.")?;
+    if export.is_file() {
+        return Ok(rows.into_file(export, "nft_tokens", streaming.max_rows));
+    }
+
+    Ok(rows.into_report(page))
+}
+
+#[utoipa::path(
+    get,
+    path = "/nft_tokens/{token_id}",
+    tag = "nft_tokens",
+    params(
+        ("token_id" = String, Path, description = "The id of the token."),
+        NftTokenItemQuery,
+        ("If-None-Match" = Option<String>, Header, description = "The entity tags of the cached versions."),
+    ),
+    responses(
+        (status = 200, description = "The nft token.", body = ContractNftToken),
+        (status = 304, description = "The token matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        NftTokensReadError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle nft token request",
+    skip(pool, pagination, token_ids, media)
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_nft_token(
+    token_id: web::Path<String>,
+    web::Query(query): web::Query<NftTokenItemQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    media: web::Data<MediaResolver>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
+) -> Result<HttpResponse, NftTokensError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let expanded = query.expand.is_some();
+    let mut nft_token = find_nft_token(
+        token_id.into_inner(),
+        query,
+        &network,
+        pool,
+        &pagination,
+        &rules,
+    )
+    .await?;
+    media.resolve_token(&mut nft_token);
+    if expanded {
+        return Ok(HttpResponse::Ok().json(nft_token));
+    }
+
+    Ok(nft_token
+        .item
+        .representation_version()
+        .respond(if_none_match.as_deref(), &nft_token))
+}
+
+/// The token of the path, of the contract of the query or else of the only contract having a
+/// token with the id.
+pub async fn find_nft_token(
+    token_id: String,
+    query: NftTokenItemQuery,
+    network: &Network,
+    pool: web::Data<PgPool>,
+    pagination: &PaginationSettings,
+    rules: &ContractRules<'_>,
+) -> Result<ContractScoped<NftTokenRow>, NftTokensError> {
+    let query = NftTokenQuery {
+        contract_id: query.contract_id,
+        token_id: Some(token_id.clone()),
+        expand: query.expand,
+        limit: Some(1),
+        ..Default::default()
+    };
+    let filter: NftTokenFilter = (query, pagination.bounds("nft_tokens"), rules)
+        .try_into()
+        .map_err(NftTokensError::ValidationError)?;
+    let mut nft_tokens = get_nft_tokens_db(pool, &filter, network)
         .await
-        .context("Failed to get the nft tokens data from database.")?;
+        .context("Failed to get the nft token data from database.")?
+        .into_iter();
+    let nft_token = nft_tokens.next().ok_or_else(|| {
+        NftTokensError::NotFound(format!(
+            "The nft token `{token_id}` doesn't exist in the `{network}` network."
+        ))
+    })?;
+    // the next token is of another contract, only an omitted contract matches several
+    if nft_tokens.next().is_some() {
+        return Err(NftTokensError::ValidationError(
+            FieldError::new(
+                "contract_id",
+                "validation.contract_id_ambiguous",
+                format!(
+                    "The nft token `{token_id}` exists in several nft contracts, the \
+                    `contract_id` must name one."
+                ),
+            )
+            .into(),
+        ));
+    }
 
-    Ok(HttpResponse::Ok().json(RowsJsonReport::from_rows(nft_tokens, filter.limit())))
+    Ok(nft_token)
 }
 
 #[tracing::instrument(name = "Query nft tokens from database", skip(filter, pool))]
 pub async fn get_nft_tokens_db(
     pool: web::Data<PgPool>,
     filter: &NftTokenFilter,
-) -> Result<Vec<NftTokenForRest>, anyhow::Error> {
-    dbg!(filter.nft_kind());
-    let rows= sqlx::query_as!(
-        NftTokenForRest,
-        r#"
-        SELECT token_id, owner_id, media, model as "model: Json<ModelKind>", copies, description, expires_at, issued_at, title, media_hash
-        FROM nft_tokens
-        WHERE ($1::text IS null OR token_id = $1)
-            AND ($2::text IS null OR owner_id = $2)
-            AND ($3::text IS null OR model->>'kind' = $3)
-        ORDER BY id LIMIT $4 OFFSET $5
-        "#,
+    network: &Network,
+) -> Result<Vec<ContractScoped<NftTokenRow>>, anyhow::Error> {
+    let nft_tokens = nft_tokens_db(&pool, filter, filter.limit() + 1, network)
+        .try_collect()
+        .await?;
+
+    Ok(nft_tokens)
+}
+
+/// The nft tokens after the filter's offset, at most `limit` of them.
+pub fn nft_tokens_db<'a>(
+    pool: &'a PgPool,
+    filter: &'a NftTokenFilter,
+    limit: i64,
+    network: &'a Network,
+) -> BoxStream<'a, Result<ContractScoped<NftTokenRow>, sqlx::Error>> {
+    sqlx::query_file!(
+        "queries/nft_tokens.sql",
+        network.as_str(),
+        filter.contract_id(),
         filter.token_id(),
         filter.owner_id(),
         filter.nft_kind(),
-        filter.limit() + 1,
+        limit,
         filter.offset(),
+        filter.sort(),
+        filter.expand.ask,
+        filter.expand.best_bid,
+        filter.expand.last_sale,
+        filter.listed,
+        filter.has_bids,
     )
-        .fetch_all(pool.get_ref())
-        .await?;
-
-    Ok(rows)
+    .fetch(pool)
+    .map_ok(|r| {
+        let ask = match (r.ask_id, r.ask_account_id, r.ask_approval_id, r.ask_price) {
+            (Some(id), Some(account_id), Some(approval_id), Some(price)) => Some(AskForDb {
+                id,
+                token_id: r.token_id.clone(),
+                account_id,
+                approval_id,
+                price,
+            }),
+            _ => None,
+        };
+        let best_bid = match (r.bid_id, r.bid_account_id, r.bid_create_at, r.bid_price) {
+            (Some(id), Some(account_id), Some(create_at), Some(price)) => Some(BidForDb {
+                id,
+                token_id: r.token_id.clone(),
+                account_id,
+                expire_at: r.bid_expire_at,
+                create_at,
+                price,
+            }),
+            _ => None,
+        };
+        let last_sale = match (
+            r.sale_id,
+            r.sale_prev_owner,
+            r.sale_curr_owner,
+            r.sale_price,
+            r.sale_date,
+        ) {
+            (Some(id), Some(prev_owner), Some(curr_owner), Some(price), Some(date)) => {
+                Some(SaleForDb {
+                    id,
+                    prev_owner,
+                    curr_owner,
+                    token_id: r.token_id.clone(),
+                    price,
+                    date,
+                })
+            }
+            _ => None,
+        };
+        let nft_token = NftTokenForRest {
+            token_id: r.token_id,
+            owner_id: r.owner_id,
+            media: r.media,
+            model: r.model,
+            copies: r.copies,
+            description: r.description,
+            expires_at: r.expires_at,
+            issued_at: r.issued_at,
+            title: r.title,
+            media_hash: r.media_hash,
+        };
+        let rarity = Rarity::from_columns(
+            r.rarity_statistical_score,
+            r.rarity_trait_normalized_score,
+            r.rarity_rank,
+        );
+        let nft_token = NftTokenRow {
+            ask,
+            best_bid,
+            last_sale,
+            ..NftTokenRow::new(nft_token, rarity, r.row_version, r.rarity_version)
+        };
+        ContractScoped::new(r.contract_id, nft_token)
+    })
+    .boxed()
 }
 
-#[tracing::instrument(name = "Insert nft tokens", skip(nft_tokens, pool))]
+#[utoipa::path(
+    post,
+    path = "/nft_tokens",
+    tag = "nft_tokens",
+    request_body = [ContractNftTokenPayload],
+    responses(
+        (status = 201, description = "The new nft tokens are stored, the known ones are skipped."),
+        NetworkError,
+        AuthError,
+        NftTokensError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Insert nft tokens", skip(nft_tokens, pool, token_ids, cache))]
 pub async fn insert_nft_token(
-    web::Json(nft_tokens): web::Json<Vec<NftTokenForRest>>,
+    web::Json(nft_tokens): web::Json<Vec<ContractScoped<NftTokenForRest>>>,
+    network: Network,
     pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
+    cache: web::Data<ResponseCache>,
+    audit: AuditContext,
 ) -> Result<HttpResponse, NftTokensError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let nft_tokens = nft_tokens
+        .into_iter()
+        .map(|t| (t.contract_id, t.item))
+        .collect();
+    let nft_tokens =
+        NewNftToken::parse_batch(nft_tokens, &rules).map_err(NftTokensError::ValidationError)?;
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    insert_nft_token_db(nft_tokens, &mut tx)
+    let inserted = insert_nft_token_db(nft_tokens, &network, &mut tx)
         .await
         .context("Failed to insert the nft token data into the database.")?;
+    if !inserted.is_empty() {
+        let token_ids: Vec<_> = inserted
+            .iter()
+            .map(|t| nft_token_entity_id(&t.contract_id, &t.item.token_id))
+            .collect();
+        audit
+            .record(&token_ids, audit::created(&inserted), &mut tx)
+            .await?;
+        let kinds = inserted
+            .iter()
+            .filter_map(|t| {
+                rarity_scope(&t.contract_id, &serde_json::to_value(&t.item.model).ok()?)
+            })
+            .collect();
+        recompute_rarity_db(&network, &kinds, &mut tx)
+            .await
+            .context("Failed to recompute the rarity of the nft tokens.")?;
+    }
     tx.commit()
         .await
         .context("Failed to commit SQL transaction to store a new subscriber.")?;
+    cache
+        .invalidate(&network, Some("collections/{kind}/traits"))
+        .await;
     Ok(HttpResponse::Created().finish())
 }
 
 #[tracing::instrument(name = "Store nft tokens to database", skip(tx))]
 pub async fn insert_nft_token_db(
-    nft_tokens: Vec<NftTokenForRest>,
+    nft_tokens: Vec<NewNftToken>,
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
-    for nft_token in nft_tokens {
-        sqlx::query_as!(
-            NftToken,
+) -> Result<Vec<ContractScoped<NftTokenForRest>>, anyhow::Error> {
+    let mut inserted = Vec::with_capacity(nft_tokens.len());
+    for (contract_id, nft_token) in nft_tokens.into_iter().map(NewNftToken::into_parts) {
+        let row = sqlx::query!(
             r#"
-            INSERT INTO nft_tokens (owner_id, token_id, title, description, media, media_hash, copies, issued_at, expires_at, model, db_created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            ON CONFLICT (token_id) DO NOTHING
+            INSERT INTO nft_tokens (owner_id, token_id, title, description, media, media_hash, copies, issued_at, expires_at, model, db_created_at, network, contract_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (network, contract_id, token_id) DO NOTHING
+            RETURNING contract_id, token_id, owner_id, media, model as "model: Json<ModelKind>", copies, description, expires_at, issued_at, title, media_hash
             "#,
             nft_token.owner_id,
             nft_token.token_id,
@@ -129,45 +570,169 @@ pub async fn insert_nft_token_db(
             nft_token.issued_at,
             nft_token.expires_at,
             Json(nft_token.model) as _,
-            Utc::now()
+            Utc::now(),
+            network.as_str(),
+            contract_id,
         )
-        .execute(&mut *tx)
+        .fetch_optional(&mut *tx)
         .await?;
+        inserted.extend(row.map(|r| {
+            let nft_token = NftTokenForRest {
+                token_id: r.token_id,
+                owner_id: r.owner_id,
+                media: r.media,
+                model: r.model,
+                copies: r.copies,
+                description: r.description,
+                expires_at: r.expires_at,
+                issued_at: r.issued_at,
+                title: r.title,
+                media_hash: r.media_hash,
+            };
+            ContractScoped::new(r.contract_id, nft_token)
+        }));
     }
 
-    Ok(())
+    Ok(inserted)
 }
 
-#[tracing::instrument(name = "Update nft tokens", skip(nft_token, pool))]
+#[utoipa::path(
+    patch,
+    path = "/nft_tokens",
+    tag = "nft_tokens",
+    params(
+        ("If-Match" = Option<String>, Header, description = "The entity tag of the version the write is based on, `412` if it isn't current."),
+    ),
+    request_body = ContractNftTokenPayload,
+    responses(
+        (status = 201, description = "The nft token's model is updated."),
+        NetworkError,
+        AuthError,
+        NftTokensError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Update nft tokens", skip(nft_token, pool, token_ids, cache))]
 pub async fn update_nft_token(
-    web::Json(nft_token): web::Json<NftTokenForRest>,
+    web::Json(nft_token): web::Json<ContractScoped<NftTokenForRest>>,
+    network: Network,
     pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
+    cache: web::Data<ResponseCache>,
+    audit: AuditContext,
+    if_match: Option<web::Header<IfMatch>>,
 ) -> Result<HttpResponse, NftTokensError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let nft_token = NewNftToken::try_from((nft_token.contract_id, nft_token.item, &rules))
+        .map_err(NftTokensError::ValidationError)?;
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    update_nft_token_db(nft_token, &mut tx)
+    let token_id = nft_token_entity_id(nft_token.contract_id(), nft_token.token_id());
+    let (contract_id, nft_token_id) = (
+        nft_token.contract_id().to_string(),
+        nft_token.token_id().to_string(),
+    );
+    let current =
+        get_nft_token_version_for_update_db(&contract_id, &nft_token_id, &network, &mut tx)
+            .await
+            .context("Failed to lock the nft token in the database.")?;
+    if !if_match_holds(if_match.as_deref(), current) {
+        return Err(NftTokensError::PreconditionFailed(format!(
+            "The nft token `{token_id}` isn't at the `If-Match` version."
+        )));
+    }
+    if current.is_none() {
+        return Err(NftTokensError::NotFound(format!(
+            "The nft token `{token_id}` doesn't exist in the `{network}` network."
+        )));
+    }
+    let change = update_nft_token_db(nft_token, &network, &mut tx)
         .await
         .context("Failed to insert the nft token data into the database.")?;
+    let mut response = HttpResponse::Created();
+    if let Some((before, after)) = change {
+        audit
+            .record(&[token_id], audit::updated(&before, &after), &mut tx)
+            .await?;
+        let kinds: BTreeSet<_> = [&before, &after]
+            .into_iter()
+            .filter_map(|model| rarity_scope(&contract_id, model))
+            .collect();
+        recompute_rarity_db(&network, &kinds, &mut tx)
+            .await
+            .context("Failed to recompute the rarity of the nft tokens.")?;
+        let version =
+            get_nft_token_version_for_update_db(&contract_id, &nft_token_id, &network, &mut tx)
+                .await
+                .context("Failed to get the nft token version from the database.")?;
+        if let Some(version) = version {
+            response.insert_header((ETAG, version.etag()));
+        }
+    }
     tx.commit()
         .await
         .context("Failed to commit SQL transaction to store a new subscriber.")?;
-    Ok(HttpResponse::Created().finish())
+    cache
+        .invalidate(&network, Some("collections/{kind}/traits"))
+        .await;
+    Ok(response.finish())
+}
+
+#[tracing::instrument(name = "Lock nft token in database", skip(tx))]
+async fn get_nft_token_version_for_update_db(
+    contract_id: &str,
+    token_id: &str,
+    network: &Network,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Option<RowVersion>, anyhow::Error> {
+    let version = sqlx::query_scalar!(
+        r#"
+        SELECT row_version FROM nft_tokens
+        WHERE network = $1 AND contract_id = $2 AND token_id = $3
+        FOR UPDATE
+        "#,
+        network.as_str(),
+        contract_id,
+        token_id,
+    )
+    .fetch_optional(tx)
+    .await?;
+
+    Ok(version.map(RowVersion))
 }
 
 #[tracing::instrument(name = "Store nft tokens to database", skip(tx))]
 pub async fn update_nft_token_db(
-    nft_token: NftTokenForRest,
+    nft_token: NewNftToken,
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
-    sqlx::query_as!(
-        NftToken,
+) -> Result<Option<(serde_json::Value, serde_json::Value)>, anyhow::Error> {
+    let (contract_id, nft_token) = nft_token.into_parts();
+    let row = sqlx::query!(
         r#"
-        UPDATE nft_tokens
-        SET model = $1
+        UPDATE nft_tokens AS n
+        SET model = $1, row_version = n.row_version + 1
+        FROM (SELECT id, model FROM nft_tokens WHERE network = $2 AND contract_id = $3 AND token_id = $4 FOR UPDATE) AS old
+        WHERE n.id = old.id
+        RETURNING old.model AS "before!", n.model AS "after!"
         "#,
         Json(nft_token.model) as _,
+        network.as_str(),
+        contract_id,
+        nft_token.token_id,
     )
-    .execute(tx)
+    .fetch_optional(tx)
     .await?;
 
-    Ok(())
+    Ok(row.map(|r| (r.before, r.after)))
+}
+
+/// The audit log's entity id of a nft token, the token ids are only unique within a contract.
+fn nft_token_entity_id(contract_id: &str, token_id: &str) -> String {
+    format!("{contract_id}/{token_id}")
+}
+
+/// The contract and the kind of a model, the rarity is scored among the tokens of both.
+fn rarity_scope(contract_id: &str, model: &serde_json::Value) -> Option<(String, String)> {
+    let kind = model["kind"].as_str()?;
+    Some((contract_id.to_string(), kind.to_string()))
 }
@@ -0,0 +1,30 @@
+use actix_web::HttpResponse;
+use utoipa::OpenApi;
+
+use crate::openapi::ApiDoc;
+
+/// The Redoc bundle is pinned, so that its releases don't change the page unnoticed.
+const DOCS_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <title>Battlemon REST API</title>
+    <meta charset="utf-8"/>
+  </head>
+  <body>
+    <redoc spec-url="openapi.json"></redoc>
+    <script src="https://cdn.redoc.ly/redoc/v2.1.3/bundles/redoc.standalone.js"></script>
+  </body>
+</html>
+"#;
+
+#[tracing::instrument(name = "Serve OpenAPI specification")]
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+#[tracing::instrument(name = "Serve API documentation page")]
+pub async fn openapi_docs() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(DOCS_PAGE)
+}
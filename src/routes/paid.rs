@@ -5,39 +5,102 @@ use rust_decimal::prelude::Zero;
 use rust_decimal::Decimal;
 
 use battlemon_models::market::{paid::Paid, sale::SaleForDb};
+use futures_util::stream::{BoxStream, StreamExt, TryStreamExt};
 use sqlx::PgPool;
 
-use crate::domain::{Limit, Offset, PaidDays, PaidFilter, ParseToPositiveInt};
-use crate::errors::PaidError;
+use crate::config::{PaginationSettings, StreamingSettings, TokenIdSettings};
+use crate::domain::{
+    ContractId, ContractRules, Limit, LimitBounds, Offset, PaidDays, PaidFilter,
+    ParseToPositiveInt, ValidationErrors, Validator,
+};
+use crate::errors::{NetworkError, PaidError, RateLimitError};
+use crate::network::Network;
+use crate::routes::{get_contract_rules, send_rows, ContractScoped, ExportQuery, RowStream};
 
 use super::PaginationQuery;
 
-impl TryFrom<PaginationQuery> for PaidFilter {
-    type Error = String;
-
-    fn try_from(query: PaginationQuery) -> Result<Self, Self::Error> {
-        let limit = Limit::parse(query.limit)?;
-        let offset = Offset::parse(query.offset)?;
-        let days = PaidDays::parse(query.days)?;
-
-        Ok(Self {
-            limit,
-            offset,
-            days,
+/// The columns of the exported trades.
+pub const TRADE_COLUMNS: [&str; 7] = [
+    "contract_id",
+    "id",
+    "prev_owner",
+    "curr_owner",
+    "token_id",
+    "price",
+    "date",
+];
+
+impl TryFrom<(PaginationQuery, LimitBounds, &ContractRules<'_>)> for PaidFilter {
+    type Error = ValidationErrors;
+
+    fn try_from(
+        (query, bounds, rules): (PaginationQuery, LimitBounds, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse(query.contract_id.as_deref(), rules));
+        let limit = validator.check(Limit::parse(query.limit, bounds));
+        let offset = validator.check(Offset::parse(query.offset));
+        let days = validator.check(PaidDays::parse(query.days));
+
+        validator.finish(|| {
+            Some(Self {
+                limit: limit?,
+                offset: offset?,
+                days: days?,
+                contract_id: contract_id?,
+            })
         })
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/paid",
+    tag = "paid",
+    params(PaginationQuery, ExportQuery),
+    responses(
+        (status = 200, description = "The trades of the last days and their statistics, or every trade as a file.", content(
+            ("application/json" = Paid),
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+        )),
+        (status = 304, description = "The response matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        PaidError,
+    )
+)]
 #[tracing::instrument(
     name = "Get statistics and trades history for last days",
-    skip(filter, pool)
+    skip(filter, pool, pagination, token_ids, streaming)
 )]
 pub async fn paid(
     web::Query(filter): web::Query<PaginationQuery>,
+    web::Query(export): web::Query<ExportQuery>,
+    network: Network,
     pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    streaming: web::Data<StreamingSettings>,
 ) -> Result<HttpResponse, PaidError> {
-    let filter = filter.try_into().map_err(PaidError::ValidationError)?;
-    let trades = query_trades(filter, &pool)
+    let export = export
+        .parse(&TRADE_COLUMNS)
+        .map_err(PaidError::ValidationError)?;
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let filter: PaidFilter = (filter, pagination.bounds("paid"), &rules)
+        .try_into()
+        .map_err(PaidError::ValidationError)?;
+    if export.is_file() {
+        // the row after the last one tells that the file is truncated
+        let limit = streaming.max_rows + 1;
+        let rows = RowStream::spawn(&streaming, move |sender| async move {
+            send_rows(trades_db(&filter, limit, &network, &pool), sender).await
+        })
+        .await
+        .context("Failed to get sale's data from the database.")?;
+        return Ok(rows.into_file(export, "paid", streaming.max_rows));
+    }
+    let trades = query_trades(&filter, &network, &pool)
         .await
         .context("Failed to get sale's data from the database.")?;
 
@@ -48,23 +111,48 @@ pub async fn paid(
 }
 
 #[tracing::instrument(name = "Query trades for last days from database", skip(pool))]
-async fn query_trades(filter: PaidFilter, pool: &PgPool) -> Result<Vec<SaleForDb>, anyhow::Error> {
-    let now = Utc::now();
-    let start_from = now - Duration::days(filter.days());
-    let trades = sqlx::query_as!(
-        SaleForDb,
-        r#"
-        SELECT id, prev_owner, curr_owner, token_id, price, date
-        FROM sales WHERE date >= $1 ORDER BY date OFFSET $2 LIMIT $3;
-        "#,
+async fn query_trades(
+    filter: &PaidFilter,
+    network: &Network,
+    pool: &PgPool,
+) -> Result<Vec<SaleForDb>, anyhow::Error> {
+    let trades = trades_db(filter, filter.limit(), network, pool)
+        .map_ok(|trade| trade.item)
+        .try_collect()
+        .await?;
+
+    Ok(trades)
+}
+
+/// The trades of the filter's last days after its offset, at most `limit` of them.
+fn trades_db<'a>(
+    filter: &'a PaidFilter,
+    limit: i64,
+    network: &'a Network,
+    pool: &'a PgPool,
+) -> BoxStream<'a, Result<ContractScoped<SaleForDb>, sqlx::Error>> {
+    let start_from = Utc::now() - Duration::days(filter.days());
+    sqlx::query_file!(
+        "queries/trades.sql",
+        network.as_str(),
+        filter.contract_id(),
         start_from,
         filter.offset(),
-        filter.limit(),
+        limit,
     )
-    .fetch_all(pool)
-    .await?;
-
-    Ok(trades)
+    .fetch(pool)
+    .map_ok(|r| {
+        let trade = SaleForDb {
+            id: r.id,
+            prev_owner: r.prev_owner,
+            curr_owner: r.curr_owner,
+            token_id: r.token_id,
+            price: r.price,
+            date: r.date,
+        };
+        ContractScoped::new(r.contract_id, trade)
+    })
+    .boxed()
 }
 
 fn calculate_report(trades: &[SaleForDb]) -> (Decimal, Decimal) {
@@ -0,0 +1,103 @@
+use actix_web::http::header::IfNoneMatch;
+use actix_web::web::Bytes;
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::cache::{CachedResponse, ResponseCache};
+use crate::config::{PaginationSettings, TokenIdSettings};
+use crate::errors::{NetworkError, NftTokensError, NftTokensReadError, RateLimitError};
+use crate::network::Network;
+use crate::preview::{PreviewFormat, PreviewRenderer};
+use crate::routes::{find_nft_token, get_contract_rules, NftTokenItemQuery, TokenMetadataQuery};
+use crate::telemetry::spawn_blocking_with_tracing;
+
+/// The route of the previews in the cache, whose entries are keyed by the model hash.
+const PREVIEW_ROUTE: &str = "nft_tokens/{token_id}/preview";
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewPath {
+    pub token_id: String,
+    pub format: PreviewFormat,
+}
+
+#[utoipa::path(
+    get,
+    path = "/nft_tokens/{token_id}/preview.{format}",
+    tag = "nft_tokens",
+    params(
+        ("token_id" = String, Path, description = "The id of the token."),
+        ("format" = PreviewFormat, Path, description = "The image format, `svg` or the rasterized `png`."),
+        TokenMetadataQuery,
+        ("If-None-Match" = Option<String>, Header, description = "The entity tags of the cached versions."),
+    ),
+    responses(
+        (status = 200, description = "The preview layering the assets of the token's traits.", content(
+            ("image/svg+xml" = String),
+            ("image/png" = String),
+        )),
+        (status = 304, description = "The preview matching the `If-None-Match` entity tag is not modified."),
+        NetworkError,
+        RateLimitError,
+        NftTokensReadError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle nft token preview request",
+    skip(pool, pagination, token_ids, renderer, cache)
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_nft_token_preview(
+    path: web::Path<PreviewPath>,
+    web::Query(query): web::Query<TokenMetadataQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    renderer: web::Data<PreviewRenderer>,
+    cache: web::Data<ResponseCache>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
+) -> Result<HttpResponse, NftTokensError> {
+    let PreviewPath { token_id, format } = path.into_inner();
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let query = NftTokenItemQuery {
+        contract_id: query.contract_id,
+        expand: None,
+    };
+    let nft_token = find_nft_token(token_id, query, &network, pool, &pagination, &rules).await?;
+    let model = serde_json::to_value(&nft_token.item.nft_token.model)
+        .context("Failed to serialize the model of the nft token.")?;
+    let resource = format!(
+        "{}/{}",
+        format.as_str(),
+        PreviewRenderer::model_hash(&model)
+    );
+    let key = ResponseCache::key(&network, PREVIEW_ROUTE, &resource);
+    if cache.enabled() {
+        if let Some(cached) = cache.get(&key).await {
+            return Ok(cached.respond(if_none_match.as_deref()));
+        }
+    }
+
+    let preview = spawn_blocking_with_tracing(move || renderer.render(&model, format))
+        .await
+        .context("Failed to spawn blocking task.")??
+        .ok_or_else(|| {
+            NftTokensError::NotFound(format!(
+                "The nft token `{}` has no preview, none of its traits has an asset.",
+                nft_token.item.nft_token.token_id
+            ))
+        })?;
+    let preview = CachedResponse::new(
+        Some(format.content_type().to_string()),
+        Bytes::from(preview),
+    );
+    if cache.enabled() {
+        cache
+            .set(key, preview.clone(), cache.ttl(PREVIEW_ROUTE))
+            .await;
+    }
+
+    Ok(preview.respond(if_none_match.as_deref()))
+}
@@ -2,100 +2,274 @@ use actix_web::{web, HttpResponse};
 use anyhow::Context;
 use battlemon_models::market::{sale::SaleForDb, sale::SaleForRest};
 use chrono::Utc;
+use futures_util::stream::{BoxStream, StreamExt, TryStreamExt};
 use sqlx::{PgPool, Postgres, Transaction};
 
-use crate::domain::{Limit, Offset, Parse, ParseToPositiveInt, SaleDays, SaleFilter, TokenId};
-use crate::errors::SaleError;
-use crate::routes::RowsJsonReport;
+use crate::audit::{self, AuditContext};
+use crate::cache::ResponseCache;
+use crate::config::{PaginationSettings, StreamingSettings, TokenIdSettings};
+use crate::domain::{
+    ContractId, ContractRules, Limit, LimitBounds, NewSale, Offset, ParseToPositiveInt, SaleDays,
+    SaleFilter, TokenId, ValidationErrors, Validator,
+};
+use crate::errors::{AuthError, NetworkError, RateLimitError, SaleError};
+use crate::network::Network;
+use crate::routes::{get_contract_rules, send_rows, ContractScoped, ExportQuery, RowStream};
 
 use super::PaginationQuery;
 
-impl TryFrom<PaginationQuery> for SaleFilter {
-    type Error = String;
+/// The columns of the exported sales.
+pub const SALE_COLUMNS: [&str; 7] = [
+    "contract_id",
+    "id",
+    "prev_owner",
+    "curr_owner",
+    "token_id",
+    "price",
+    "date",
+];
 
-    fn try_from(query: PaginationQuery) -> Result<Self, Self::Error> {
-        let token_id = TokenId::parse(query.token_id)?;
-        let limit = Limit::parse(query.limit)?;
-        let offset = Offset::parse(query.offset)?;
-        SaleDays::parse(query.days)?;
+impl TryFrom<(PaginationQuery, LimitBounds, &ContractRules<'_>)> for SaleFilter {
+    type Error = ValidationErrors;
 
-        Ok(Self {
-            limit,
-            offset,
-            token_id,
+    fn try_from(
+        (query, bounds, rules): (PaginationQuery, LimitBounds, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let contract_id = validator.check(ContractId::parse(query.contract_id.as_deref(), rules));
+        let format = rules.token_id_format(query.contract_id.as_deref());
+        let token_id = validator.check(TokenId::parse(query.token_id.as_deref(), format));
+        let limit = validator.check(Limit::parse(query.limit, bounds));
+        let offset = validator.check(Offset::parse(query.offset));
+        validator.check(SaleDays::parse(query.days));
+
+        validator.finish(|| {
+            Some(Self {
+                limit: limit?,
+                offset: offset?,
+                contract_id: contract_id?,
+                token_id: token_id?,
+            })
         })
     }
 }
 
-#[tracing::instrument(name = "Handle sales request", skip(filter, pool))]
+#[utoipa::path(
+    get,
+    path = "/sales",
+    tag = "sales",
+    params(PaginationQuery, ExportQuery),
+    responses(
+        (status = 200, description = "A page of sales, or every sale as a file.", content(
+            ("application/json" = SalesReport),
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+        )),
+        NetworkError,
+        RateLimitError,
+        SaleError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle sales request",
+    skip(filter, pool, pagination, token_ids, streaming)
+)]
 pub async fn get_sales(
     web::Query(filter): web::Query<PaginationQuery>,
+    web::Query(export): web::Query<ExportQuery>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    streaming: web::Data<StreamingSettings>,
+) -> Result<HttpResponse, SaleError> {
+    let export = export
+        .parse(&SALE_COLUMNS)
+        .map_err(SaleError::ValidationError)?;
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let filter: SaleFilter = (filter, pagination.bounds("sales"), &rules)
+        .try_into()
+        .map_err(SaleError::ValidationError)?;
+    let page = filter.limit();
+    // the row after the last one of a page or a file tells that it's truncated
+    let limit = match export.is_file() {
+        true => streaming.max_rows + 1,
+        false => page + 1,
+    };
+    let rows = RowStream::spawn(&streaming, move |sender| async move {
+        send_rows(sales_db(&filter, limit, &network, &pool), sender).await
+    })
+    .await
+    .context("Failed to get the sale's data from the database.")?;
+    if export.is_file() {
+        return Ok(rows.into_file(export, "sales", streaming.max_rows));
+    }
+
+    Ok(rows.into_report(page))
+}
+
+/// The sales after the filter's offset, at most `limit` of them.
+pub fn sales_db<'a>(
+    filter: &'a SaleFilter,
+    limit: i64,
+    network: &'a Network,
+    pool: &'a PgPool,
+) -> BoxStream<'a, Result<ContractScoped<SaleForDb>, sqlx::Error>> {
+    sqlx::query_file!(
+        "queries/sales.sql",
+        network.as_str(),
+        filter.contract_id(),
+        filter.token_id(),
+        limit,
+        filter.offset()
+    )
+    .fetch(pool)
+    .map_ok(|r| {
+        let sale = SaleForDb {
+            id: r.id,
+            prev_owner: r.prev_owner,
+            curr_owner: r.curr_owner,
+            token_id: r.token_id,
+            price: r.price,
+            date: r.date,
+        };
+        ContractScoped::new(r.contract_id, sale)
+    })
+    .boxed()
+}
+
+#[utoipa::path(
+    get,
+    path = "/sales/{id}",
+    tag = "sales",
+    params(("id" = i64, Path, description = "The id of the sale.")),
+    responses(
+        (status = 200, description = "The sale.", body = ContractSale),
+        NetworkError,
+        RateLimitError,
+        SaleError,
+    )
+)]
+#[tracing::instrument(name = "Handle sale request", skip(pool))]
+pub async fn get_sale(
+    id: web::Path<i64>,
+    network: Network,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, SaleError> {
-    let filter = filter.try_into().map_err(SaleError::ValidationError)?;
-    let sales = get_sales_db(&filter, &pool)
+    let id = id.into_inner();
+    let sale = get_sale_db(id, &network, &pool)
         .await
-        .context("Failed to get the sale's data from the database.")?;
+        .context("Failed to get the sale's data from the database.")?
+        .ok_or_else(|| {
+            SaleError::NotFound(format!(
+                "The sale `{id}` doesn't exist in the `{network}` network."
+            ))
+        })?;
 
-    Ok(HttpResponse::Ok().json(RowsJsonReport::from_rows(sales, filter.limit())))
+    Ok(HttpResponse::Ok().json(sale))
 }
 
-#[tracing::instrument(name = "Query sales from database", skip(filter, pool))]
-pub async fn get_sales_db(
-    filter: &SaleFilter,
+#[tracing::instrument(name = "Query sale from database", skip(pool))]
+pub async fn get_sale_db(
+    id: i64,
+    network: &Network,
     pool: &PgPool,
-) -> Result<Vec<SaleForDb>, anyhow::Error> {
-    let rows = sqlx::query_as!(
-        SaleForDb,
+) -> Result<Option<ContractScoped<SaleForDb>>, anyhow::Error> {
+    let row = sqlx::query!(
         r#"
-        SELECT id, prev_owner, curr_owner, token_id, price, date
+        SELECT id, contract_id, prev_owner, curr_owner, token_id, price, date
         FROM sales
-        WHERE ($1::text IS null OR token_id = $1)
-        ORDER BY id LIMIT $2 OFFSET $3;
+        WHERE network = $1 AND id = $2
         "#,
-        filter.token_id(),
-        filter.limit() + 1,
-        filter.offset()
+        network.as_str(),
+        id,
     )
-    .fetch_all(pool)
+    .fetch_optional(pool)
     .await?;
 
-    Ok(rows)
+    Ok(row.map(|r| {
+        let sale = SaleForDb {
+            id: r.id,
+            prev_owner: r.prev_owner,
+            curr_owner: r.curr_owner,
+            token_id: r.token_id,
+            price: r.price,
+            date: r.date,
+        };
+        ContractScoped::new(r.contract_id, sale)
+    }))
 }
 
-#[tracing::instrument(name = "Insert sale", skip(pool))]
+#[utoipa::path(
+    post,
+    path = "/sales",
+    tag = "sales",
+    request_body = ContractSalePayload,
+    responses(
+        (status = 201, description = "The sale is stored."),
+        NetworkError,
+        AuthError,
+        SaleError,
+    ),
+    security(("basic_auth" = []))
+)]
+#[tracing::instrument(name = "Insert sale", skip(pool, token_ids, cache))]
 pub async fn insert_sale(
-    web::Json(sale): web::Json<SaleForRest>,
+    web::Json(sale): web::Json<ContractScoped<SaleForRest>>,
+    network: Network,
     pool: web::Data<PgPool>,
+    token_ids: web::Data<TokenIdSettings>,
+    cache: web::Data<ResponseCache>,
+    audit: AuditContext,
 ) -> Result<HttpResponse, SaleError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let sale = NewSale::try_from((sale.contract_id, sale.item, &rules))
+        .map_err(SaleError::ValidationError)?;
     let mut tx = pool.begin().await.context("Failed to start transaction.")?;
-    insert_sale_db(sale, &mut tx)
+    let sale = insert_sale_db(sale, &network, &mut tx)
         .await
         .context("Failed to insert the nft token data into the database.")?;
+    audit
+        .record(&[sale.item.id.to_string()], audit::created(&sale), &mut tx)
+        .await?;
     tx.commit()
         .await
         .context("Failed to commit SQL transaction to store a new subscriber.")?;
+    cache.invalidate(&network, Some("paid")).await;
     Ok(HttpResponse::Created().finish())
 }
 
 #[tracing::instrument(name = "Store sale to database", skip(tx))]
 pub async fn insert_sale_db(
-    sale: SaleForRest,
+    sale: NewSale,
+    network: &Network,
     tx: &mut Transaction<'_, Postgres>,
-) -> Result<(), anyhow::Error> {
-    sqlx::query!(
+) -> Result<ContractScoped<SaleForDb>, anyhow::Error> {
+    let (contract_id, sale) = sale.into_parts();
+    let r = sqlx::query!(
         r#"
-        INSERT INTO sales (prev_owner, curr_owner, token_id, price, date)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, contract_id, prev_owner, curr_owner, token_id, price, date
         "#,
+        network.as_str(),
+        contract_id,
         sale.prev_owner,
         sale.curr_owner,
         sale.token_id,
         sale.price,
         Utc::now()
     )
-    .execute(tx)
+    .fetch_one(tx)
     .await?;
 
-    Ok(())
+    let sale = SaleForDb {
+        id: r.id,
+        prev_owner: r.prev_owner,
+        curr_owner: r.curr_owner,
+        token_id: r.token_id,
+        price: r.price,
+        date: r.date,
+    };
+    Ok(ContractScoped::new(r.contract_id, sale))
 }
@@ -0,0 +1,244 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use battlemon_models::nft::{ModelKind, NftKind, NftTokenForRest};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::config::{PaginationSettings, TokenIdSettings};
+use crate::domain::{
+    ContractId, ContractRules, Limit, LimitBounds, Offset, ParseToPositiveInt, SearchFilter,
+    SearchQuery, ValidationErrors, Validator,
+};
+use crate::errors::{NetworkError, RateLimitError, SearchError};
+use crate::media::MediaResolver;
+use crate::network::Network;
+use crate::routes::{get_contract_rules, ContractScoped, NftTokenRow, Rarity, RowsJsonReport};
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// The words to search in the title, the description, the owner and the trait values of
+    /// the tokens, the last word may be a prefix, e.g. `lemon re`.
+    pub q: Option<String>,
+    /// Only the tokens of the nft contract, one of the active contracts config.
+    pub contract_id: Option<String>,
+    /// Only the tokens of the kind, e.g. `lemon`.
+    #[param(value_type = Option<String>)]
+    pub nft_kind: Option<NftKind>,
+    /// The page size, bounded by the resource's maximum.
+    pub limit: Option<i64>,
+    /// The number of rows to skip.
+    pub offset: Option<i64>,
+}
+
+/// A page of the matched tokens, the best matches first, and the facets of all the matches.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SearchReport {
+    #[schema(value_type = Vec<ContractNftToken>)]
+    pub rows: Vec<ContractScoped<NftTokenRow>>,
+    /// Whether it's the last page.
+    pub end: bool,
+    /// The effective page size.
+    pub limit: i64,
+    pub facets: SearchFacets,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SearchFacets {
+    /// The number of the matches of each kind, the most frequent first.
+    pub kinds: Vec<KindFacet>,
+    /// The number of the matches of each trait value, by trait name.
+    pub traits: Vec<TraitFacet>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct KindFacet {
+    /// The kind of the model, e.g. `lemon`.
+    pub kind: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TraitFacet {
+    /// The trait of the model, e.g. `cap`.
+    pub name: String,
+    pub value: String,
+    pub count: i64,
+}
+
+impl TryFrom<(SearchParams, LimitBounds, &ContractRules<'_>)> for SearchFilter {
+    type Error = ValidationErrors;
+
+    fn try_from(
+        (params, bounds, rules): (SearchParams, LimitBounds, &ContractRules<'_>),
+    ) -> Result<Self, Self::Error> {
+        let mut validator = Validator::default();
+        let query = validator.check(SearchQuery::parse(params.q.as_deref()));
+        let contract_id = validator.check(ContractId::parse(params.contract_id.as_deref(), rules));
+        let limit = validator.check(Limit::parse(params.limit, bounds));
+        let offset = validator.check(Offset::parse(params.offset));
+        let nft_kind = params.nft_kind.map(|k| {
+            serde_json::to_value(k)
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .to_string()
+        });
+
+        validator.finish(|| {
+            Some(Self {
+                query: query?,
+                limit: limit?,
+                offset: offset?,
+                contract_id: contract_id?,
+                nft_kind,
+            })
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/search",
+    tag = "nft_tokens",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "The matched nft tokens and their facets.", body = SearchReport),
+        NetworkError,
+        RateLimitError,
+        SearchError,
+    )
+)]
+#[tracing::instrument(
+    name = "Handle search request",
+    skip(params, pool, pagination, token_ids, media)
+)]
+pub async fn search(
+    web::Query(params): web::Query<SearchParams>,
+    network: Network,
+    pool: web::Data<PgPool>,
+    pagination: web::Data<PaginationSettings>,
+    token_ids: web::Data<TokenIdSettings>,
+    media: web::Data<MediaResolver>,
+) -> Result<HttpResponse, SearchError> {
+    let rules = get_contract_rules(&network, &pool, &token_ids).await?;
+    let filter: SearchFilter = (params, pagination.bounds("search"), &rules)
+        .try_into()
+        .map_err(SearchError::ValidationError)?;
+    let mut nft_tokens = search_nft_tokens_db(&filter, &network, &pool)
+        .await
+        .context("Failed to search the nft tokens in the database.")?;
+    media.resolve_all(&mut nft_tokens);
+    let facets = search_facets_db(&filter, &network, &pool)
+        .await
+        .context("Failed to count the facets of the nft tokens in the database.")?;
+
+    let page = RowsJsonReport::from_rows(nft_tokens, filter.limit());
+    Ok(HttpResponse::Ok().json(SearchReport {
+        rows: page.rows,
+        end: page.end,
+        limit: page.limit,
+        facets,
+    }))
+}
+
+/// The full-text matches of the word prefixes rank above the trigram matches, which catch
+/// the typos.
+#[tracing::instrument(name = "Search nft tokens in database", skip(filter, pool))]
+pub async fn search_nft_tokens_db(
+    filter: &SearchFilter,
+    network: &Network,
+    pool: &PgPool,
+) -> Result<Vec<ContractScoped<NftTokenRow>>, anyhow::Error> {
+    let rows = sqlx::query_file!(
+        "queries/search_nft_tokens.sql",
+        network.as_str(),
+        filter.query.prefixes(),
+        filter.query.text(),
+        filter.contract_id(),
+        filter.nft_kind(),
+        filter.limit() + 1,
+        filter.offset(),
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let nft_tokens = rows.into_iter().map(|r| {
+        let nft_token = NftTokenForRest {
+            token_id: r.token_id,
+            owner_id: r.owner_id,
+            media: r.media,
+            model: r.model,
+            copies: r.copies,
+            description: r.description,
+            expires_at: r.expires_at,
+            issued_at: r.issued_at,
+            title: r.title,
+            media_hash: r.media_hash,
+        };
+        let rarity = Rarity::from_columns(
+            r.rarity_statistical_score,
+            r.rarity_trait_normalized_score,
+            r.rarity_rank,
+        );
+        ContractScoped::new(
+            r.contract_id,
+            NftTokenRow::new(nft_token, rarity, r.row_version, r.rarity_version),
+        )
+    });
+
+    Ok(nft_tokens.collect())
+}
+
+#[tracing::instrument(name = "Count search facets in database", skip(filter, pool))]
+pub async fn search_facets_db(
+    filter: &SearchFilter,
+    network: &Network,
+    pool: &PgPool,
+) -> Result<SearchFacets, anyhow::Error> {
+    let kinds = sqlx::query_as!(
+        KindFacet,
+        r#"
+        SELECT model->>'kind' AS "kind!", count(*) AS "count!"
+        FROM nft_tokens
+        WHERE network = $1
+            AND ($4::text IS null OR contract_id = $4)
+            AND ($5::text IS null OR model->>'kind' = $5)
+            AND (search_document @@ to_tsquery('simple', $2) OR $3 <% search_text)
+        GROUP BY 1
+        ORDER BY 2 DESC, 1
+        "#,
+        network.as_str(),
+        filter.query.prefixes(),
+        filter.query.text(),
+        filter.contract_id(),
+        filter.nft_kind(),
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let traits = sqlx::query_as!(
+        TraitFacet,
+        r#"
+        SELECT t.key AS "name!", t.value AS "value!", count(*) AS "count!"
+        FROM nft_tokens, jsonb_each_text(model - 'kind') AS t
+        WHERE network = $1
+            AND ($4::text IS null OR contract_id = $4)
+            AND ($5::text IS null OR model->>'kind' = $5)
+            AND (search_document @@ to_tsquery('simple', $2) OR $3 <% search_text)
+        GROUP BY 1, 2
+        ORDER BY 1, 3 DESC, 2
+        "#,
+        network.as_str(),
+        filter.query.prefixes(),
+        filter.query.text(),
+        filter.contract_id(),
+        filter.nft_kind(),
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(SearchFacets { kinds, traits })
+}
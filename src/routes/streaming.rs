@@ -0,0 +1,300 @@
+use std::future::Future;
+
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web::Bytes;
+use actix_web::{error, HttpResponse};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tokio::sync::mpsc;
+use utoipa::IntoParams;
+
+use crate::config::StreamingSettings;
+use crate::domain::{Export, ExportFormat, ValidationErrors};
+use crate::telemetry::spawn_with_tracing;
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// The format of the rows, `json` by default. The `csv` and `ndjson` files aren't paged,
+    /// they stream every row after `offset`, up to the configured maximum. A longer file ends
+    /// with a `{"truncated":true,"max_rows":N}` line, or a `# truncated after N rows` line in
+    /// `csv`.
+    pub format: Option<String>,
+    /// The comma separated columns of the `csv` and `ndjson` rows, e.g. `token_id,price`,
+    /// every column by default.
+    pub columns: Option<String>,
+}
+
+impl ExportQuery {
+    pub fn parse(&self, available: &[&'static str]) -> Result<Export, ValidationErrors> {
+        Export::parse(self.format.as_deref(), self.columns.as_deref(), available)
+    }
+}
+
+/// The sending half of the rows of a [`RowStream`].
+pub type RowSender<T> = mpsc::Sender<Result<T, sqlx::Error>>;
+
+/// The rows of a query sent by a spawned task, which waits while `buffer_rows` of them are
+/// unsent to the client.
+pub struct RowStream<T> {
+    first: Option<T>,
+    rows: mpsc::Receiver<Result<T, sqlx::Error>>,
+}
+
+impl<T> RowStream<T>
+where
+    T: Serialize + 'static,
+{
+    /// Spawns the query sending its rows and waits for the first one, so that a failing query
+    /// is still answered with an error rather than a truncated body.
+    pub async fn spawn<F, Fut>(settings: &StreamingSettings, query: F) -> Result<Self, sqlx::Error>
+    where
+        F: FnOnce(RowSender<T>) -> Fut,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let (sender, rows) = mpsc::channel(settings.buffer_rows.max(1));
+        spawn_with_tracing(query(sender));
+
+        Self::receive(rows).await
+    }
+
+    async fn receive(
+        mut rows: mpsc::Receiver<Result<T, sqlx::Error>>,
+    ) -> Result<Self, sqlx::Error> {
+        let first = rows.recv().await.transpose()?;
+
+        Ok(Self { first, rows })
+    }
+
+    fn into_stream(self) -> impl Stream<Item = Result<T, actix_web::Error>> {
+        let rows = stream::unfold(self.rows, |mut rows| async move {
+            rows.recv().await.map(|row| (row, rows))
+        });
+
+        stream::iter(self.first.map(Ok)).chain(rows).map(|row| {
+            row.map_err(|e| {
+                tracing::error!(error = ?e, "Failed to stream the rows.");
+                error::ErrorInternalServerError(e)
+            })
+        })
+    }
+
+    /// The rows as a `RowsJsonReport` page, the row after the `limit` ones only tells that it
+    /// isn't the last page.
+    pub fn into_report(self, limit: i64) -> HttpResponse {
+        let rows = Box::pin(self.into_stream());
+        let body = stream::unfold(Some((rows, 0)), move |state| async move {
+            let (mut rows, count) = state?;
+            let end = match rows.next().await {
+                Some(Ok(row)) if count < limit => {
+                    let row = serde_json::to_string(&row).map(|row| match count {
+                        0 => Bytes::from(row),
+                        _ => Bytes::from(format!(",{row}")),
+                    });
+                    return Some((row.map_err(Into::into), Some((rows, count + 1))));
+                }
+                Some(Err(e)) => return Some((Err(e), None)),
+                Some(Ok(_)) => false,
+                None => true,
+            };
+            let tail = format!(r#"],"end":{end},"limit":{limit}}}"#);
+            Some((Ok(Bytes::from(tail)), None))
+        });
+
+        HttpResponse::Ok()
+            .content_type("application/json")
+            .streaming(stream::iter([Ok(Bytes::from_static(b"{\"rows\":["))]).chain(body))
+    }
+
+    /// The rows as a file, e.g. `sales.csv`, whose transfer is aborted by a failing query. The
+    /// row after the `max_rows` ones is replaced by the line telling that the file is truncated.
+    pub fn into_file(self, export: Export, name: &str, max_rows: i64) -> HttpResponse {
+        let filename = format!("{name}.{}", export.format.extension());
+        let header = match export.format {
+            ExportFormat::Csv => {
+                let columns = export.columns.iter().map(|c| Some(Value::from(*c)));
+                Some(Ok(Bytes::from(csv_line(columns))))
+            }
+            _ => None,
+        };
+        let content_type = export.format.content_type();
+        let truncated = Bytes::from(truncated_line(export.format, max_rows));
+        let lines = Box::pin(self.into_stream().map(move |row| {
+            let row = serde_json::to_value(&row?)?;
+            Ok::<_, actix_web::Error>(Bytes::from(export_line(&export, &row)))
+        }));
+        let rows = stream::unfold(Some((lines, 0)), move |state| {
+            let truncated = truncated.clone();
+            async move {
+                let (mut lines, count) = state?;
+                match lines.next().await? {
+                    Ok(_) if count == max_rows => Some((Ok(truncated), None)),
+                    Ok(line) => Some((Ok(line), Some((lines, count + 1)))),
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        });
+
+        HttpResponse::Ok()
+            .content_type(content_type)
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(filename)],
+            })
+            .streaming(stream::iter(header).chain(rows))
+    }
+}
+
+/// Sends the rows until the query ends or fails, or the client leaves.
+pub async fn send_rows<T>(rows: impl Stream<Item = Result<T, sqlx::Error>>, sender: RowSender<T>) {
+    futures_util::pin_mut!(rows);
+    while let Some(row) = rows.next().await {
+        let failed = row.is_err();
+        if sender.send(row).await.is_err() || failed {
+            break;
+        }
+    }
+}
+
+/// The line of the selected columns of the row, the missing ones are null.
+fn export_line(export: &Export, row: &Value) -> String {
+    let columns = export
+        .columns
+        .iter()
+        .map(|column| row.get(*column).cloned());
+    match export.format {
+        ExportFormat::Csv => csv_line(columns),
+        _ => {
+            let object: Map<String, Value> = export
+                .columns
+                .iter()
+                .map(|c| c.to_string())
+                .zip(columns.map(Option::unwrap_or_default))
+                .collect();
+            format!("{}\n", Value::Object(object))
+        }
+    }
+}
+
+/// The last line of a file whose rows after the `max_rows` ones are left out.
+fn truncated_line(format: ExportFormat, max_rows: i64) -> String {
+    match format {
+        ExportFormat::Csv => format!("# truncated after {max_rows} rows\r\n"),
+        _ => format!("{}\n", json!({ "truncated": true, "max_rows": max_rows })),
+    }
+}
+
+/// The values as a CSV line, the nested objects are JSON-encoded, the nulls are empty.
+fn csv_line(values: impl Iterator<Item = Option<Value>>) -> String {
+    let fields: Vec<_> = values
+        .map(|value| {
+            let field = match value {
+                None | Some(Value::Null) => return String::new(),
+                Some(Value::String(s)) => s,
+                Some(value) => value.to_string(),
+            };
+            if field.contains(&[',', '"', '\r', '\n'][..]) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+
+    format!("{}\r\n", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::body::to_bytes;
+
+    use super::*;
+    use crate::routes::RowsJsonReport;
+
+    async fn row_stream<T: Serialize + Send + 'static>(rows: Vec<T>) -> RowStream<T> {
+        let (sender, receiver) = mpsc::channel(1);
+        actix_web::rt::spawn(send_rows(stream::iter(rows.into_iter().map(Ok)), sender));
+        RowStream::receive(receiver).await.unwrap()
+    }
+
+    async fn report(rows: Vec<i64>, limit: i64) -> RowsJsonReport<i64> {
+        let response = row_stream(rows).await.into_report(limit);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn reports_end_unless_a_row_follows_the_page() {
+        let actual = report(vec![1, 2, 3], 2).await;
+        assert_eq!(
+            (actual.rows, actual.end, actual.limit),
+            (vec![1, 2], false, 2)
+        );
+
+        let actual = report(vec![1, 2], 2).await;
+        assert_eq!((actual.rows, actual.end), (vec![1, 2], true));
+
+        let actual = report(vec![], 2).await;
+        assert_eq!((actual.rows, actual.end), (vec![], true));
+    }
+
+    async fn file(format: ExportFormat, values: Vec<i64>, max_rows: i64) -> String {
+        let export = Export {
+            format,
+            columns: vec!["value"],
+        };
+        let rows = values
+            .into_iter()
+            .map(|value| json!({ "value": value, "hidden": true }))
+            .collect();
+        let response = row_stream(rows).await.into_file(export, "values", max_rows);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn files_past_the_max_rows_end_with_the_truncated_line() {
+        let actual = file(ExportFormat::Csv, vec![1, 2, 3], 2).await;
+        assert_eq!(actual, "value\r\n1\r\n2\r\n# truncated after 2 rows\r\n");
+
+        let actual = file(ExportFormat::Csv, vec![1, 2], 2).await;
+        assert_eq!(actual, "value\r\n1\r\n2\r\n");
+
+        let actual = file(ExportFormat::Ndjson, vec![1, 2, 3], 2).await;
+        assert_eq!(
+            actual,
+            "{\"value\":1}\n{\"value\":2}\n{\"max_rows\":2,\"truncated\":true}\n"
+        );
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        let values = [
+            Some(json!("plain")),
+            Some(json!("a,b")),
+            Some(json!("say \"hi\"")),
+            None,
+            Some(Value::Null),
+            Some(json!(1.5)),
+            Some(json!({ "kind": "lemon" })),
+        ];
+
+        let actual = csv_line(values.into_iter());
+        assert_eq!(
+            actual,
+            "plain,\"a,b\",\"say \"\"hi\"\"\",,,1.5,\"{\"\"kind\"\":\"\"lemon\"\"}\"\r\n"
+        );
+    }
+
+    #[test]
+    fn ndjson_lines_have_the_selected_columns() {
+        let export = Export {
+            format: ExportFormat::Ndjson,
+            columns: vec!["price", "missing"],
+        };
+
+        let actual = export_line(&export, &json!({ "id": 1, "price": "1.5" }));
+        assert_eq!(actual, "{\"missing\":null,\"price\":\"1.5\"}\n");
+    }
+}
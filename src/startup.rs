@@ -1,13 +1,25 @@
 use std::net::TcpListener;
 
-use crate::auth::middleware::auth;
+use crate::api_version::deprecation;
+use crate::auth::middleware::{admin, auth};
+use crate::cache::middleware::cache;
+use crate::cache::ResponseCache;
+use crate::errors::middleware::error_request_id;
+use crate::errors::JsonError;
+use crate::media::MediaResolver;
+use crate::preview::PreviewRenderer;
+use crate::rate_limit::middleware::rate_limit;
+use crate::rate_limit::RateLimiter;
 use actix_web::dev::Server;
 use actix_web::{error, web, HttpResponse};
 use actix_web_lab::middleware::from_fn;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 
-use crate::config::{DatabaseSettings, Settings};
+use crate::config::{
+    ApiVersionSettings, ContractMetadataSettings, DatabaseSettings, NetworkSettings,
+    PaginationSettings, Settings, StreamingSettings, TokenIdSettings,
+};
 use crate::routes;
 
 pub struct Application {
@@ -23,7 +35,24 @@ impl Application {
         tracing::info!("Binding address - {address} for app");
         let listener = TcpListener::bind(&address)?;
         let port = listener.local_addr().unwrap().port();
-        let server = run(listener, connection_pool)?;
+        let rate_limiter = RateLimiter::new(config.rate_limit);
+        let response_cache = ResponseCache::new(config.cache);
+        let media_resolver = MediaResolver::new(config.media, config.contract_metadata.clone());
+        let preview_renderer = PreviewRenderer::new(config.preview);
+        let server = run(
+            listener,
+            connection_pool,
+            rate_limiter,
+            response_cache,
+            config.pagination,
+            config.networks,
+            config.api_versions,
+            config.token_ids,
+            config.contract_metadata,
+            media_resolver,
+            preview_renderer,
+            config.streaming,
+        )?;
 
         Ok(Self { port, server })
     }
@@ -43,67 +72,239 @@ pub fn get_connection_pool(config: &DatabaseSettings) -> PgPool {
         .connect_lazy_with(config.with_db())
 }
 
-fn add_default_error_body(err: impl std::error::Error + 'static) -> actix_web::Error {
-    let json_body = serde_json::json!({
-        "error": err.to_string(),
-    });
+/// The routes of the `v1` API, also served at the root as its deprecated legacy aliases.
+///
+/// A new version is mounted side by side as its own scope, e.g. `web::scope("v2")`, which
+/// registers the handlers of the changed routes first and then configures the routes of the
+/// previous version, since the first registered match wins the unchanged routes are shared.
+fn v1_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("health_check", web::get().to(routes::health_check))
+        .route("openapi.json", web::get().to(routes::openapi_json))
+        .route("docs", web::get().to(routes::openapi_docs))
+        .service(
+            web::resource("audit_log").route(
+                web::get()
+                    .to(routes::get_audit_log)
+                    .wrap(from_fn(admin))
+                    .wrap(from_fn(auth)),
+            ),
+        )
+        .configure(network_routes)
+        .service(web::scope("networks/{network}").configure(network_routes));
+}
+
+/// The routes serving a network's data, mounted both at the root, where the network is
+/// taken from the header or the default, and under the `networks/{network}` prefix.
+fn network_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("contracts")
+            .route(web::get().to(routes::get_contracts).wrap(from_fn(cache)))
+            .route(web::post().to(routes::insert_contracts).wrap(from_fn(auth))),
+    )
+    .route(
+        "contracts/history",
+        web::get().to(routes::get_contracts_history),
+    )
+    .route(
+        "contract_metadata",
+        web::get()
+            .to(routes::get_contract_metadata)
+            .wrap(from_fn(rate_limit)),
+    )
+    .route(
+        "contracts/rollback",
+        web::post()
+            .to(routes::rollback_contracts)
+            .wrap(from_fn(auth)),
+    )
+    .route(
+        "paid",
+        web::get()
+            .to(routes::paid)
+            .wrap(from_fn(cache))
+            .wrap(from_fn(rate_limit)),
+    )
+    .service(
+        web::resource("sales")
+            .route(web::get().to(routes::get_sales).wrap(from_fn(rate_limit)))
+            .route(web::post().to(routes::insert_sale).wrap(from_fn(auth))),
+    )
+    .route(
+        "sales/{id}",
+        web::get().to(routes::get_sale).wrap(from_fn(rate_limit)),
+    )
+    .route(
+        "search",
+        web::get().to(routes::search).wrap(from_fn(rate_limit)),
+    )
+    .route(
+        "collections/{kind}/traits",
+        web::get()
+            .to(routes::get_collection_traits)
+            .wrap(from_fn(cache))
+            .wrap(from_fn(rate_limit)),
+    )
+    .service(
+        web::resource("nft_tokens")
+            .route(
+                web::get()
+                    .to(routes::get_nft_tokens)
+                    .wrap(from_fn(rate_limit)),
+            )
+            .route(web::post().to(routes::insert_nft_token).wrap(from_fn(auth)))
+            .route(
+                web::patch()
+                    .to(routes::update_nft_token)
+                    .wrap(from_fn(auth)),
+            ),
+    )
+    .route(
+        "nft_tokens/{token_id}",
+        web::get()
+            .to(routes::get_nft_token)
+            .wrap(from_fn(rate_limit)),
+    )
+    .route(
+        "nft_tokens/{token_id}/metadata",
+        web::get()
+            .to(routes::get_nft_token_metadata)
+            .wrap(from_fn(rate_limit)),
+    )
+    .route(
+        "nft_tokens/{token_id}/preview.{format}",
+        web::get()
+            .to(routes::get_nft_token_preview)
+            .wrap(from_fn(rate_limit)),
+    )
+    .service(
+        web::resource("asks")
+            .route(web::get().to(routes::get_asks).wrap(from_fn(rate_limit)))
+            .route(web::post().to(routes::insert_ask).wrap(from_fn(auth)))
+            .route(web::delete().to(routes::delete_ask).wrap(from_fn(auth))),
+    )
+    .route(
+        "asks/{id}",
+        web::get().to(routes::get_ask).wrap(from_fn(rate_limit)),
+    )
+    .service(
+        web::resource("bids")
+            .route(web::get().to(routes::get_bids).wrap(from_fn(rate_limit)))
+            .route(web::post().to(routes::insert_bid).wrap(from_fn(auth)))
+            .route(web::delete().to(routes::delete_bid).wrap(from_fn(auth))),
+    )
+    .route(
+        "bids/{id}",
+        web::get().to(routes::get_bid).wrap(from_fn(rate_limit)),
+    )
+    .service(
+        web::scope("users").service(
+            web::scope("{user_id}")
+                .service(web::resource("is_owner").route(web::post().to(routes::is_owner)))
+                .route("is_owner/{token_id}", web::get().to(routes::is_token_owner)),
+        ),
+    );
+}
+
+fn query_error_body(err: error::QueryPayloadError) -> actix_web::Error {
+    let json_body = JsonError::new("request.invalid_query", &err);
     error::InternalError::from_response(err, HttpResponse::BadRequest().json(json_body)).into()
 }
 
-#[tracing::instrument(name = "Running application", skip(listener, pool))]
-pub fn run(listener: TcpListener, pool: PgPool) -> Result<Server, std::io::Error> {
+fn path_error_body(err: error::PathError) -> actix_web::Error {
+    let json_body = JsonError::new("request.invalid_path", &err);
+    error::InternalError::from_response(err, HttpResponse::BadRequest().json(json_body)).into()
+}
+
+fn json_error_body(err: error::JsonPayloadError) -> actix_web::Error {
+    let mut json_body = JsonError::new("request.invalid_body", &err);
+    if let error::JsonPayloadError::Deserialize(e) = &err {
+        json_body = json_body.with_details(serde_json::json!({
+            "line": e.line(),
+            "column": e.column(),
+        }));
+    }
+    error::InternalError::from_response(err, HttpResponse::BadRequest().json(json_body)).into()
+}
+
+#[tracing::instrument(
+    name = "Running application",
+    skip(
+        listener,
+        pool,
+        rate_limiter,
+        response_cache,
+        pagination,
+        networks,
+        api_versions,
+        token_ids,
+        contract_metadata,
+        media_resolver,
+        preview_renderer,
+        streaming
+    )
+)]
+pub fn run(
+    listener: TcpListener,
+    pool: PgPool,
+    rate_limiter: RateLimiter,
+    response_cache: ResponseCache,
+    pagination: PaginationSettings,
+    networks: NetworkSettings,
+    api_versions: ApiVersionSettings,
+    token_ids: TokenIdSettings,
+    contract_metadata: ContractMetadataSettings,
+    media_resolver: MediaResolver,
+    preview_renderer: PreviewRenderer,
+    streaming: StreamingSettings,
+) -> Result<Server, std::io::Error> {
     let pool = web::Data::new(pool);
+    let rate_limiter = web::Data::new(rate_limiter);
+    let response_cache = web::Data::new(response_cache);
+    let pagination = web::Data::new(pagination);
+    let networks = web::Data::new(networks);
+    let api_versions = web::Data::new(api_versions);
+    let token_ids = web::Data::new(token_ids);
+    let contract_metadata = web::Data::new(contract_metadata);
+    let media_resolver = web::Data::new(media_resolver);
+    let preview_renderer = web::Data::new(preview_renderer);
+    let streaming = web::Data::new(streaming);
     let server = actix_web::HttpServer::new(move || {
         let query_config =
-            web::QueryConfig::default().error_handler(|err, _req| add_default_error_body(err));
+            web::QueryConfig::default().error_handler(|err, _req| query_error_body(err));
+
+        let path_config =
+            web::PathConfig::default().error_handler(|err, _req| path_error_body(err));
 
         let json_config =
-            web::JsonConfig::default().error_handler(|err, _req| add_default_error_body(err));
+            web::JsonConfig::default().error_handler(|err, _req| json_error_body(err));
 
         actix_web::App::new()
+            .wrap(from_fn(error_request_id))
             .wrap(tracing_actix_web::TracingLogger::default())
             .route("health_check", web::get().to(routes::health_check))
             .service(
-                web::resource("contracts")
-                    .route(web::get().to(routes::get_contracts))
-                    .route(web::post().to(routes::insert_contracts).wrap(from_fn(auth))),
-            )
-            .route("paid", web::get().to(routes::paid))
-            .service(
-                web::resource("sales")
-                    .route(web::get().to(routes::get_sales))
-                    .route(web::post().to(routes::insert_sale).wrap(from_fn(auth))),
-            )
-            .service(
-                web::resource("nft_tokens")
-                    .route(web::get().to(routes::get_nft_tokens))
-                    .route(web::post().to(routes::insert_nft_token).wrap(from_fn(auth)))
-                    .route(
-                        web::patch()
-                            .to(routes::update_nft_token)
-                            .wrap(from_fn(auth)),
-                    ),
-            )
-            .service(
-                web::resource("asks")
-                    .route(web::get().to(routes::get_asks))
-                    .route(web::post().to(routes::insert_ask).wrap(from_fn(auth)))
-                    .route(web::delete().to(routes::delete_ask).wrap(from_fn(auth))),
-            )
-            .service(
-                web::resource("bids")
-                    .route(web::get().to(routes::get_bids))
-                    .route(web::post().to(routes::insert_bid).wrap(from_fn(auth)))
-                    .route(web::delete().to(routes::delete_bid).wrap(from_fn(auth))),
+                web::scope("v1")
+                    .wrap(from_fn(deprecation))
+                    .configure(v1_routes),
             )
             .service(
-                web::scope("users")
-                    .service(web::scope("{user_id}").service(
-                        web::resource("is_owner").route(web::post().to(routes::is_owner)),
-                    )),
+                web::scope("")
+                    .wrap(from_fn(deprecation))
+                    .configure(v1_routes),
             )
             .app_data(pool.clone())
+            .app_data(rate_limiter.clone())
+            .app_data(response_cache.clone())
+            .app_data(pagination.clone())
+            .app_data(networks.clone())
+            .app_data(api_versions.clone())
+            .app_data(token_ids.clone())
+            .app_data(contract_metadata.clone())
+            .app_data(media_resolver.clone())
+            .app_data(preview_renderer.clone())
+            .app_data(streaming.clone())
             .app_data(query_config)
+            .app_data(path_config)
             .app_data(json_config)
     })
     .listen(listener)?
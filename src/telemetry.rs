@@ -1,5 +1,7 @@
+use std::future::Future;
+
 use actix_web::rt::task::JoinHandle;
-use tracing::{subscriber, Subscriber};
+use tracing::{subscriber, Instrument, Subscriber};
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
 use tracing_subscriber::{fmt::MakeWriter, layer::SubscriberExt, EnvFilter, Registry};
@@ -40,3 +42,11 @@ where
     let current_span = tracing::Span::current();
     actix_web::rt::task::spawn_blocking(move || current_span.in_scope(f))
 }
+
+pub fn spawn_with_tracing<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    actix_web::rt::spawn(future.instrument(tracing::Span::current()))
+}
@@ -0,0 +1,186 @@
+use crate::dummies::NFT_CONTRACT_ID;
+use crate::helpers::{assert_json_error, spawn_app};
+
+use battlemon_models::market::sale::SaleForRest;
+use battlemon_rest::audit::AuditEntry;
+use battlemon_rest::routes::{ContractScoped, RowsJsonReport};
+use fake::Fake;
+use reqwest::Method;
+
+mod dummies;
+mod helpers;
+
+#[tokio::test]
+async fn audit_log_rejects_non_admin_user_with_403() {
+    let app = spawn_app().await;
+
+    let response = app.get_audit_log("").await;
+    let actual_status = response.status();
+    assert_eq!(
+        actual_status,
+        reqwest::StatusCode::FORBIDDEN,
+        "The expected status code must be 403, actual is `{}`",
+        actual_status
+    );
+
+    assert_json_error(response).await;
+}
+
+#[tokio::test]
+async fn audit_log_rejects_request_without_credentials() {
+    let app = spawn_app().await;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/audit_log", &app.address))
+        .send()
+        .await
+        .expect("Failed to send request");
+    let actual_status = response.status();
+    assert_eq!(
+        actual_status,
+        reqwest::StatusCode::BAD_REQUEST,
+        "The expected status code must be 400, actual is `{}`",
+        actual_status
+    );
+}
+
+#[tokio::test]
+async fn audit_log_records_inserted_sale() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    app.make_test_user_admin().await;
+    let sale = dummies::AliceToBobSale.fake::<SaleForRest>();
+
+    let response = app
+        .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+        .await;
+    assert!(
+        response.status().is_success(),
+        "Failed to insert sale, status is `{}`",
+        response.status()
+    );
+
+    let response = app.get_audit_log("route=sales&method=post").await;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let report = response
+        .json::<RowsJsonReport<AuditEntry>>()
+        .await
+        .expect("Failed to parse the audit log report");
+    assert_eq!(report.rows.len(), 1, "Expected exactly one audit entry");
+    let entry = &report.rows[0];
+    assert_eq!(entry.route, "/sales");
+    assert_eq!(entry.method, "POST");
+    assert!(!entry.request_id.is_empty(), "The request id is missing");
+    assert!(entry.diff["before"].is_null());
+    assert_eq!(entry.diff["after"]["token_id"], sale.token_id);
+
+    let query = format!("entity_id={}", entry.entity_ids[0]);
+    let report = app
+        .get_audit_log(&query)
+        .await
+        .json::<RowsJsonReport<AuditEntry>>()
+        .await
+        .expect("Failed to parse the audit log report");
+    assert_eq!(
+        report.rows.len(),
+        1,
+        "Expected to find the entry by entity id"
+    );
+
+    let report = app
+        .get_audit_log("route=asks")
+        .await
+        .json::<RowsJsonReport<AuditEntry>>()
+        .await
+        .expect("Failed to parse the audit log report");
+    assert!(report.rows.is_empty(), "Expected no entries for asks");
+}
+
+#[tokio::test]
+async fn audit_log_rejects_invalid_queries() {
+    let app = spawn_app().await;
+    app.make_test_user_admin().await;
+    let invalid_queries = [
+        ("limit=-1", reqwest::StatusCode::UNPROCESSABLE_ENTITY),
+        ("offset=-1", reqwest::StatusCode::UNPROCESSABLE_ENTITY),
+        (
+            "from=2022-09-02T00:00:00Z&to=2022-09-01T00:00:00Z",
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        ("from=yesterday", reqwest::StatusCode::BAD_REQUEST),
+    ];
+
+    for (query, expected_status) in invalid_queries {
+        let response = app.get_audit_log(query).await;
+        assert_eq!(
+            response.status(),
+            expected_status,
+            "Query `{query}` should be rejected"
+        );
+    }
+}
+
+#[tokio::test]
+async fn audit_log_records_the_root_route_of_aliases() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    app.make_test_user_admin().await;
+
+    for path in ["v1/sales", "v1/networks/mainnet/sales"] {
+        let sale = dummies::AliceToBobSale.fake::<SaleForRest>();
+        let response = app
+            .write_with_header(
+                Method::POST,
+                path,
+                &ContractScoped::new(NFT_CONTRACT_ID, &sale),
+                ("Accept", "application/json"),
+            )
+            .await;
+        assert!(
+            response.status().is_success(),
+            "Failed to insert sale through `{path}`, status is `{}`",
+            response.status()
+        );
+    }
+
+    let report = app
+        .get_audit_log("route=sales")
+        .await
+        .json::<RowsJsonReport<AuditEntry>>()
+        .await
+        .expect("Failed to parse the audit log report");
+    let routes: Vec<_> = report.rows.iter().map(|e| e.route.as_str()).collect();
+    assert_eq!(routes, ["/sales", "/sales"]);
+}
+
+#[tokio::test]
+async fn audit_log_is_filtered_by_network() {
+    let app = spawn_app().await;
+    app.make_test_user_admin().await;
+    for network in ["mainnet", "testnet"] {
+        app.configure_contracts(network, NFT_CONTRACT_ID).await;
+        let sale = dummies::AliceToBobSale.fake::<SaleForRest>();
+        let response = app
+            .post_with_network_header(
+                network,
+                "sales",
+                &ContractScoped::new(NFT_CONTRACT_ID, &sale),
+            )
+            .await;
+        assert!(
+            response.status().is_success(),
+            "Failed to insert sale on `{network}`, status is `{}`",
+            response.status()
+        );
+    }
+
+    let report = app
+        .get_audit_log("network=testnet")
+        .await
+        .json::<RowsJsonReport<AuditEntry>>()
+        .await
+        .expect("Failed to parse the audit log report");
+    assert_eq!(report.rows.len(), 1, "Expected one entry on testnet");
+    assert_eq!(report.rows[0].network, "testnet");
+    assert_eq!(report.rows[0].route, "/sales");
+}
@@ -0,0 +1,127 @@
+use battlemon_models::market::{paid::Paid, sale::SaleForRest};
+use chrono::Utc;
+use fake::Fake;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::Response;
+use serde_json::json;
+
+use crate::dummies::{AliceToBobSale, NFT_CONTRACT_ID};
+use battlemon_rest::routes::ContractScoped;
+use helpers::{spawn_app, spawn_app_with, TestApp};
+
+mod dummies;
+mod helpers;
+
+fn etag(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(ETAG)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+async fn get_paid_if_none_match(app: &TestApp, query: &str, etag: &str) -> Response {
+    reqwest::Client::new()
+        .get(format!("{}/paid?{query}", app.address))
+        .header(IF_NONE_MATCH, etag)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn paid_history_len(app: &TestApp) -> usize {
+    let response = app.get_paid("days=1").await;
+    assert_eq!(response.status(), 200);
+    response.json::<Paid>().await.unwrap().history.len()
+}
+
+async fn store_sale(app: &TestApp) {
+    let sale: SaleForRest = AliceToBobSale.fake();
+    sqlx::query!(
+        r#"
+        INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+        VALUES ('mainnet', $1, $2, $3, $4, $5, $6)
+        "#,
+        NFT_CONTRACT_ID,
+        sale.prev_owner,
+        sale.curr_owner,
+        sale.token_id,
+        sale.price,
+        Utc::now()
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the sale");
+}
+
+#[tokio::test]
+async fn cached_responses_are_not_modified_for_their_etag() {
+    let app = spawn_app().await;
+
+    let response = app.get_paid("days=1&limit=10").await;
+    assert_eq!(response.status(), 200);
+    let tag = etag(&response).expect("The response has no ETag");
+
+    let response = get_paid_if_none_match(&app, "limit=10&days=1", &tag).await;
+    assert_eq!(response.status(), 304);
+    assert_eq!(etag(&response).as_deref(), Some(tag.as_str()));
+    assert!(response.bytes().await.unwrap().is_empty());
+
+    let response = get_paid_if_none_match(&app, "days=1&limit=10", "\"other\"").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(etag(&response), Some(tag));
+}
+
+#[tokio::test]
+async fn cached_paid_is_invalidated_by_new_sales() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    assert_eq!(paid_history_len(&app).await, 0);
+
+    // the sales written around the api are served once the cached response expires
+    store_sale(&app).await;
+    assert_eq!(paid_history_len(&app).await, 0);
+
+    let sale: SaleForRest = AliceToBobSale.fake();
+    let response = app
+        .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+        .await;
+    assert_eq!(response.status(), 201);
+
+    assert_eq!(paid_history_len(&app).await, 2);
+}
+
+#[tokio::test]
+async fn cached_contracts_are_invalidated_by_new_contracts() {
+    let app = spawn_app().await;
+    let response = app.get_contracts("").await;
+    assert_eq!(response.status(), 404);
+    assert_eq!(etag(&response), None, "The errors are cached");
+
+    for nft_contract_id in ["nft.battlemon.near", "nft2.battlemon.near"] {
+        let response = app
+            .post_contracts(&json!({
+                "nft_contract_id": nft_contract_id,
+                "market_contract_id": "market.battlemon.near",
+            }))
+            .await;
+        assert_eq!(response.status(), 201);
+
+        let response = app.get_contracts("").await;
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["nft_contract_id"], nft_contract_id);
+    }
+}
+
+#[tokio::test]
+async fn disabled_cache_serves_the_database() {
+    let app = spawn_app_with(|cfg| cfg.cache.enabled = false).await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    assert_eq!(paid_history_len(&app).await, 0);
+
+    store_sale(&app).await;
+    let response = app.get_paid("days=1").await;
+
+    assert_eq!(etag(&response), None);
+    assert_eq!(response.json::<Paid>().await.unwrap().history.len(), 1);
+}
@@ -0,0 +1,195 @@
+use battlemon_models::nft::{FromTraitWeights, Lemon, ModelKind, NftTokenForRest};
+use battlemon_rest::routes::{
+    CollectionTraits, ContractScoped, NftTokenRow, RowsJsonReport, TraitValueCount,
+};
+
+use crate::dummies::{NftToken, NFT_CONTRACT_ID};
+use crate::helpers::{assert_error_code, spawn_app, TestApp};
+
+mod dummies;
+mod helpers;
+
+fn lemon(token_id: &str, weights: [u8; 5]) -> NftToken {
+    NftToken {
+        contract_id: NFT_CONTRACT_ID.to_string(),
+        owner_id: "alice.near".to_string(),
+        token_id: token_id.to_string(),
+        media: "lemon.png".to_string(),
+        model: ModelKind::Lemon(Lemon::from_trait_weights(&"".to_string(), &weights)),
+    }
+}
+
+/// Stores two common lemons and a rare one.
+async fn store_lemons(app: &TestApp) {
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let lemons = vec![
+        lemon("1", [1, 1, 1, 1, 1]),
+        lemon("2", [1, 1, 1, 1, 1]),
+        lemon("3", [2, 2, 2, 2, 2]),
+    ];
+    let response = app.post_nft_token(&lemons).await;
+    assert_eq!(response.status(), 201, "Failed to store the lemons");
+}
+
+async fn tokens_by_rarity(app: &TestApp) -> Vec<NftTokenRow> {
+    let report: RowsJsonReport<ContractScoped<NftTokenRow>> = app
+        .get_nft_tokens("sort=rarity")
+        .await
+        .json()
+        .await
+        .expect("Failed to parse the nft tokens");
+    report.rows.into_iter().map(|r| r.item).collect()
+}
+
+fn token_id(row: &NftTokenRow) -> &str {
+    &row.nft_token.token_id
+}
+
+#[tokio::test]
+async fn collection_traits_count_the_values_of_every_trait() {
+    let app = spawn_app().await;
+    store_lemons(&app).await;
+
+    let response = app.get_collection_traits("lemon", "").await;
+    assert_eq!(response.status(), 200);
+    let traits: CollectionTraits = response.json().await.unwrap();
+
+    assert_eq!(traits.kind, "lemon");
+    assert_eq!(traits.total, 3);
+    let cap = traits
+        .traits
+        .iter()
+        .find(|t| t.name == "cap")
+        .expect("The cap trait is missing");
+    assert_eq!(
+        cap.values,
+        [
+            TraitValueCount {
+                value: "cap1".to_string(),
+                count: 2,
+                frequency: 2.0 / 3.0,
+            },
+            TraitValueCount {
+                value: "cap2".to_string(),
+                count: 1,
+                frequency: 1.0 / 3.0,
+            },
+        ]
+    );
+
+    let response = app.get_collection_traits("firearm", "").await;
+    let traits: CollectionTraits = response.json().await.unwrap();
+    assert_eq!(traits.total, 0);
+    assert!(traits.traits.is_empty());
+}
+
+#[tokio::test]
+async fn rarity_is_scored_on_insert_and_rescored_on_update() {
+    let app = spawn_app().await;
+    store_lemons(&app).await;
+
+    let tokens = tokens_by_rarity(&app).await;
+    let token_ids: Vec<_> = tokens.iter().map(token_id).collect();
+    assert_eq!(token_ids, ["3", "1", "2"]);
+    let rare = tokens[0].rarity.as_ref().expect("The rarity is missing");
+    let common = tokens[1].rarity.as_ref().expect("The rarity is missing");
+    assert_eq!((rare.rank, common.rank), (1, 2));
+    assert!(rare.statistical_score > common.statistical_score);
+    assert!(rare.trait_normalized_score > common.trait_normalized_score);
+
+    let response = app.patch_nft_token(&lemon("3", [1, 1, 1, 1, 1])).await;
+    assert_eq!(response.status(), 201);
+
+    let tokens = tokens_by_rarity(&app).await;
+    let ranks: Vec<_> = tokens
+        .iter()
+        .map(|t| t.rarity.as_ref().map(|r| r.rank))
+        .collect();
+    assert_eq!(ranks, [Some(1), Some(1), Some(1)]);
+}
+
+async fn rarity_of(app: &TestApp, token_id: &str) -> (Option<f64>, Option<f64>, Option<i64>) {
+    let row = sqlx::query!(
+        r#"
+        SELECT rarity_statistical_score, rarity_trait_normalized_score, rarity_rank
+        FROM nft_tokens
+        WHERE token_id = $1
+        "#,
+        token_id,
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to fetch the rarity");
+    (
+        row.rarity_statistical_score,
+        row.rarity_trait_normalized_score,
+        row.rarity_rank,
+    )
+}
+
+#[tokio::test]
+async fn null_trait_values_are_scored_as_values_of_their_own() {
+    let app = spawn_app().await;
+    store_lemons(&app).await;
+    let rare = rarity_of(&app, "3").await;
+    sqlx::query!(
+        "UPDATE nft_tokens SET model = jsonb_set(model, '{cap}', 'null') WHERE token_id = '3'"
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // rescores the lemons, the null cap is as rare as the cap it replaces
+    let response = app.patch_nft_token(&lemon("1", [1, 1, 1, 1, 1])).await;
+    assert_eq!(response.status(), 201);
+
+    assert_eq!(rarity_of(&app, "3").await, rare);
+}
+
+#[tokio::test]
+async fn nft_tokens_without_rarity_are_listed_as_null() {
+    let app = spawn_app().await;
+    store_lemons(&app).await;
+    sqlx::query!("UPDATE nft_tokens SET rarity_rank = null WHERE token_id = '3'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let tokens = tokens_by_rarity(&app).await;
+    let token_ids: Vec<_> = tokens.iter().map(token_id).collect();
+    assert_eq!(token_ids, ["1", "2", "3"]);
+    assert_eq!(tokens[2].rarity, None);
+
+    let report: RowsJsonReport<ContractScoped<NftTokenForRest>> =
+        app.get_nft_tokens("token_id=3").await.json().await.unwrap();
+    assert_eq!(report.rows[0].item.token_id, "3");
+}
+
+#[tokio::test]
+async fn collection_traits_reject_invalid_requests_with_422() {
+    let app = spawn_app().await;
+    let test_cases = [
+        ("dragon", "", "validation.nft_kind_unknown"),
+        (
+            "lemon",
+            "contract_id=items.near",
+            "validation.contract_id_unknown",
+        ),
+    ];
+
+    for (kind, query, code) in test_cases {
+        let response = app.get_collection_traits(kind, query).await;
+        assert_eq!(response.status(), 422, "`{kind}?{query}` isn't rejected");
+        assert_error_code(response, code).await;
+    }
+}
+
+#[tokio::test]
+async fn unknown_sort_of_nft_tokens_is_rejected() {
+    let app = spawn_app().await;
+
+    let response = app.get_nft_tokens("sort=price").await;
+
+    assert_eq!(response.status(), 400);
+    assert_error_code(response, "request.invalid_query").await;
+}
@@ -0,0 +1,184 @@
+use battlemon_models::nft::{FromTraitWeights, Lemon, ModelKind};
+use battlemon_rest::routes::{ContractScoped, ContractsVersion, NftTokenRow, RowsJsonReport};
+use reqwest::header::ETAG;
+use reqwest::{Method, Response};
+use serde_json::json;
+
+use crate::dummies::{NftToken, NFT_CONTRACT_ID};
+use crate::helpers::{assert_error_code, spawn_app, spawn_app_with, TestApp};
+
+mod dummies;
+mod helpers;
+
+fn etag(response: &Response) -> Option<&str> {
+    response.headers().get(ETAG).map(|v| v.to_str().unwrap())
+}
+
+fn contracts(nft_contract_id: &str) -> serde_json::Value {
+    json!({
+        "nft_contract_id": nft_contract_id,
+        "market_contract_id": "market.battlemon.near",
+    })
+}
+
+async fn post_contracts_if_match(app: &TestApp, nft_contract_id: &str, tag: &str) -> Response {
+    let json = contracts(nft_contract_id);
+    app.write_with_header(Method::POST, "contracts", &json, ("If-Match", tag))
+        .await
+}
+
+fn lemon(weights: [u8; 5]) -> NftToken {
+    NftToken {
+        contract_id: NFT_CONTRACT_ID.to_string(),
+        owner_id: "alice.near".to_string(),
+        token_id: "1".to_string(),
+        media: "lemon.png".to_string(),
+        model: ModelKind::Lemon(Lemon::from_trait_weights(&"".to_string(), &weights)),
+    }
+}
+
+async fn patch_lemon_if_match(app: &TestApp, weights: [u8; 5], tag: &str) -> Response {
+    app.write_with_header(
+        Method::PATCH,
+        "nft_tokens",
+        &lemon(weights),
+        ("If-Match", tag),
+    )
+    .await
+}
+
+async fn token_version(app: &TestApp) -> i64 {
+    let report: RowsJsonReport<ContractScoped<NftTokenRow>> = app
+        .get_nft_tokens("token_id=1")
+        .await
+        .json()
+        .await
+        .expect("Failed to parse the nft tokens");
+    report.rows[0].item.version
+}
+
+#[tokio::test]
+async fn contracts_are_tagged_by_their_version() {
+    for enabled in [true, false] {
+        let app = spawn_app_with(|cfg| cfg.cache.enabled = enabled).await;
+        let response = app.post_contracts(&contracts(NFT_CONTRACT_ID)).await;
+        assert_eq!(response.status(), 201);
+        let tag = etag(&response).unwrap().to_string();
+        let stored: ContractsVersion = response.json().await.unwrap();
+        assert_eq!(tag, format!("\"{}\"", stored.version));
+
+        let response = app.get_contracts("").await;
+        assert_eq!(etag(&response), Some(tag.as_str()));
+
+        let response = app
+            .get_with_header("contracts", "", ("If-None-Match", &tag))
+            .await;
+        assert_eq!(response.status(), 304, "Unexpected status, cache {enabled}");
+        assert_eq!(etag(&response), Some(tag.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn contract_upserts_fail_unless_the_tag_is_active() {
+    let app = spawn_app().await;
+
+    let response = post_contracts_if_match(&app, NFT_CONTRACT_ID, "*").await;
+    assert_eq!(response.status(), 412);
+    assert_error_code(response, "contracts.precondition_failed").await;
+
+    let response = app.post_contracts(&contracts(NFT_CONTRACT_ID)).await;
+    let first = etag(&response).unwrap().to_string();
+    let response = post_contracts_if_match(&app, "nft2.battlemon.near", &first).await;
+    assert_eq!(response.status(), 201);
+    let second = etag(&response).unwrap().to_string();
+
+    let response = post_contracts_if_match(&app, "nft3.battlemon.near", &first).await;
+    assert_eq!(response.status(), 412);
+    assert_error_code(response, "contracts.precondition_failed").await;
+
+    let response = app
+        .write_with_header(
+            Method::POST,
+            "contracts/rollback",
+            &json!({ "version": 1 }),
+            ("If-Match", &first),
+        )
+        .await;
+    assert_eq!(response.status(), 412);
+
+    let body: serde_json::Value = app.get_contracts("").await.json().await.unwrap();
+    assert_eq!(body["nft_contract_id"], "nft2.battlemon.near");
+    let response = post_contracts_if_match(&app, NFT_CONTRACT_ID, &second).await;
+    assert_eq!(response.status(), 201);
+}
+
+#[tokio::test]
+async fn nft_token_updates_fail_unless_the_tag_is_current() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let response = patch_lemon_if_match(&app, [2, 2, 2, 2, 2], "*").await;
+    assert_eq!(response.status(), 412, "The missing token is updated");
+
+    let response = app.post_nft_token(&vec![lemon([1, 1, 1, 1, 1])]).await;
+    assert_eq!(response.status(), 201);
+    assert_eq!(token_version(&app).await, 1);
+
+    let response = patch_lemon_if_match(&app, [2, 2, 2, 2, 2], "\"1\"").await;
+    assert_eq!(response.status(), 201);
+    assert_eq!(etag(&response), Some("\"2\""));
+
+    // a concurrent writer still holding the first version
+    let response = patch_lemon_if_match(&app, [3, 3, 3, 3, 3], "\"1\"").await;
+    assert_eq!(response.status(), 412);
+    assert_error_code(response, "nft_tokens.precondition_failed").await;
+    assert_eq!(token_version(&app).await, 2);
+
+    let response = app.patch_nft_token(&lemon([3, 3, 3, 3, 3])).await;
+    assert_eq!(response.status(), 201);
+    assert_eq!(etag(&response), Some("\"3\""));
+}
+
+#[tokio::test]
+async fn updates_of_missing_nft_tokens_are_not_found() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+
+    let response = app.patch_nft_token(&lemon([2, 2, 2, 2, 2])).await;
+    assert_eq!(response.status(), 404);
+    assert_error_code(response, "nft_tokens.not_found").await;
+    let response = app.get_nft_tokens("token_id=1").await;
+    let report: RowsJsonReport<ContractScoped<NftTokenRow>> = response.json().await.unwrap();
+    assert!(report.rows.is_empty(), "The missing token is stored");
+}
+
+#[tokio::test]
+async fn rescoring_the_rarity_keeps_the_if_match_tag_valid() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let response = app.post_nft_token(&vec![lemon([1, 1, 1, 1, 1])]).await;
+    assert_eq!(response.status(), 201);
+    let response = app.get_nft_token("1", "").await;
+    let tag = etag(&response).unwrap().to_string();
+
+    // another lemon rescores the rarity of the first one
+    let other = NftToken {
+        token_id: "2".to_string(),
+        ..lemon([3, 3, 3, 3, 3])
+    };
+    let response = app.post_nft_token(&vec![other]).await;
+    assert_eq!(response.status(), 201);
+    let response = app
+        .get_with_header("nft_tokens/1", "", ("If-None-Match", &tag))
+        .await;
+    assert_eq!(
+        response.status(),
+        200,
+        "The rescored token isn't revalidated"
+    );
+    assert_ne!(etag(&response), Some(tag.as_str()));
+    assert_eq!(token_version(&app).await, 1);
+
+    let response = patch_lemon_if_match(&app, [2, 2, 2, 2, 2], &tag).await;
+    assert_eq!(response.status(), 201);
+    assert_eq!(etag(&response), Some("\"2\""));
+}
@@ -0,0 +1,205 @@
+use crate::helpers::{assert_json_error, spawn_app, TestApp};
+
+use battlemon_rest::routes::{ContractsRollback, ContractsVersion, RowsJsonReport};
+use chrono::SecondsFormat;
+use serde_json::json;
+
+mod helpers;
+
+#[tokio::test]
+async fn contracts_return_404_when_nothing_is_configured() {
+    let app = spawn_app().await;
+    let queries = ["", "version=1", "at=2022-09-01T00:00:00Z"];
+
+    for query in queries {
+        let response = app.get_contracts(query).await;
+        let actual_status = response.status();
+        assert_eq!(
+            actual_status,
+            reqwest::StatusCode::NOT_FOUND,
+            "The expected status code must be 404 for query `{query}`, actual is `{}`",
+            actual_status
+        );
+        assert_json_error(response).await;
+    }
+}
+
+#[tokio::test]
+async fn contracts_reject_invalid_queries() {
+    let app = spawn_app().await;
+    let queries = [
+        ("version=0", reqwest::StatusCode::UNPROCESSABLE_ENTITY),
+        ("version=-1", reqwest::StatusCode::UNPROCESSABLE_ENTITY),
+        (
+            "version=1&at=2022-09-01T00:00:00Z",
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        ("at=yesterday", reqwest::StatusCode::BAD_REQUEST),
+    ];
+
+    for (query, expected_status) in queries {
+        let response = app.get_contracts(query).await;
+        let actual_status = response.status();
+        assert_eq!(
+            actual_status, expected_status,
+            "Unexpected status code for query `{query}`, actual is `{}`",
+            actual_status
+        );
+        assert_json_error(response).await;
+    }
+}
+
+#[tokio::test]
+async fn contracts_history_is_empty_when_nothing_is_configured() {
+    let app = spawn_app().await;
+
+    let response = app.get_contracts_history("").await;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let report = response
+        .json::<RowsJsonReport<ContractsVersion>>()
+        .await
+        .expect("Failed to parse the contracts history report");
+    assert!(report.rows.is_empty());
+    assert!(report.end);
+}
+
+#[tokio::test]
+async fn contracts_rollback_to_unknown_version_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_contracts_rollback(&ContractsRollback { version: 1 })
+        .await;
+    let actual_status = response.status();
+    assert_eq!(
+        actual_status,
+        reqwest::StatusCode::NOT_FOUND,
+        "The expected status code must be 404, actual is `{}`",
+        actual_status
+    );
+    assert_json_error(response).await;
+}
+
+/// Upserts the contracts ids with the nft contract, returning the stored version.
+async fn upsert_contracts(app: &TestApp, nft_contract_id: &str) -> ContractsVersion {
+    let response = app
+        .post_contracts(&json!({
+            "nft_contract_id": nft_contract_id,
+            "market_contract_id": "market.battlemon.near",
+        }))
+        .await;
+    assert_eq!(response.status(), reqwest::StatusCode::CREATED);
+    response
+        .json()
+        .await
+        .expect("Failed to parse the stored contracts version")
+}
+
+async fn get_nft_contract_id(app: &TestApp, query: &str) -> String {
+    let response = app.get_contracts(query).await;
+    assert_eq!(
+        response.status(),
+        reqwest::StatusCode::OK,
+        "Unexpected status code for query `{query}`"
+    );
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["nft_contract_id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn contracts_upserts_create_new_active_versions() {
+    let app = spawn_app().await;
+
+    let first = upsert_contracts(&app, "nft.battlemon.near").await;
+    assert_eq!(first.version, 1);
+    assert_eq!(first.rollback_of, None);
+    assert!(first.created_by.is_some(), "The author isn't recorded");
+
+    let second = upsert_contracts(&app, "nft2.battlemon.near").await;
+    assert_eq!(second.version, 2);
+    assert_eq!(
+        second.contracts_config.nft_contract_id,
+        "nft2.battlemon.near"
+    );
+    assert_eq!(get_nft_contract_id(&app, "").await, "nft2.battlemon.near");
+}
+
+#[tokio::test]
+async fn contracts_return_historical_versions() {
+    let app = spawn_app().await;
+    let first = upsert_contracts(&app, "nft.battlemon.near").await;
+    upsert_contracts(&app, "nft2.battlemon.near").await;
+
+    assert_eq!(
+        get_nft_contract_id(&app, "version=1").await,
+        "nft.battlemon.near"
+    );
+    assert_eq!(
+        get_nft_contract_id(&app, "version=2").await,
+        "nft2.battlemon.near"
+    );
+    let at = first
+        .created_at
+        .to_rfc3339_opts(SecondsFormat::Micros, true);
+    assert_eq!(
+        get_nft_contract_id(&app, &format!("at={at}")).await,
+        "nft.battlemon.near"
+    );
+    let response = app.get_contracts("version=3").await;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn contracts_history_is_paged_from_the_latest_version() {
+    let app = spawn_app().await;
+    for nft_contract_id in [
+        "nft.battlemon.near",
+        "nft2.battlemon.near",
+        "nft3.battlemon.near",
+    ] {
+        upsert_contracts(&app, nft_contract_id).await;
+    }
+
+    let app = &app;
+    let history = |query: &'static str| async move {
+        let response = app.get_contracts_history(query).await;
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        response
+            .json::<RowsJsonReport<ContractsVersion>>()
+            .await
+            .expect("Failed to parse the contracts history report")
+    };
+    let report = history("limit=2").await;
+    let versions: Vec<_> = report.rows.iter().map(|v| v.version).collect();
+    assert_eq!(versions, [3, 2]);
+    assert!(!report.end);
+
+    let report = history("limit=2&offset=2").await;
+    let versions: Vec<_> = report.rows.iter().map(|v| v.version).collect();
+    assert_eq!(versions, [1]);
+    assert!(report.end);
+}
+
+#[tokio::test]
+async fn contracts_rollback_stores_the_target_as_new_active_version() {
+    let app = spawn_app().await;
+    upsert_contracts(&app, "nft.battlemon.near").await;
+    upsert_contracts(&app, "nft2.battlemon.near").await;
+
+    let response = app
+        .post_contracts_rollback(&ContractsRollback { version: 1 })
+        .await;
+    assert_eq!(response.status(), reqwest::StatusCode::CREATED);
+    let rollback: ContractsVersion = response.json().await.unwrap();
+    assert_eq!(rollback.version, 3);
+    assert_eq!(rollback.rollback_of, Some(1));
+    assert_eq!(
+        rollback.contracts_config.nft_contract_id,
+        "nft.battlemon.near"
+    );
+
+    assert_eq!(get_nft_contract_id(&app, "").await, "nft.battlemon.near");
+    let response = app.get_contracts_history("limit=1").await;
+    let report: RowsJsonReport<ContractsVersion> = response.json().await.unwrap();
+    assert_eq!(report.rows[0].rollback_of, Some(1));
+}
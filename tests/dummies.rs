@@ -1,12 +1,18 @@
 use fake::faker::address::en::Geohash;
 
+use battlemon_models::market::sale::SaleForRest;
 use battlemon_models::nft::{FromTraitWeights, Lemon, ModelKind};
-use fake::{Dummy, Fake};
+use fake::{Dummy, Fake, Faker};
 use rand::Rng;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+/// The nft contract of the dummy tokens.
+pub const NFT_CONTRACT_ID: &str = "nft.battlemon.near";
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct NftToken {
+    pub contract_id: String,
     pub owner_id: String,
     pub token_id: String,
     pub media: String,
@@ -24,6 +30,7 @@ impl Dummy<AliceNftToken> for NftToken {
         let token_id = rng.gen::<u64>().to_string();
 
         Self {
+            contract_id: NFT_CONTRACT_ID.to_string(),
             owner_id: "alice.near".to_string(),
             token_id,
             media: Geohash(24).fake(),
@@ -56,6 +63,19 @@ impl Dummy<DannyNftToken> for NftToken {
     }
 }
 
+pub struct AliceToBobSale;
+
+impl Dummy<AliceToBobSale> for SaleForRest {
+    fn dummy_with_rng<R: Rng + ?Sized>(_: &AliceToBobSale, rng: &mut R) -> Self {
+        let mut sale: SaleForRest = Faker.fake_with_rng(rng);
+        sale.prev_owner = "alice.near".to_string();
+        sale.curr_owner = "bob.near".to_string();
+        sale.token_id = rng.gen::<u64>().to_string();
+        sale.price = Decimal::new(rng.gen_range(1..100_000), 2);
+        sale
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,4 +97,11 @@ mod tests {
         let token: NftToken = DannyNftToken.fake();
         assert_eq!(token.owner_id, "danny.near".to_string());
     }
+
+    #[test]
+    fn alice_to_bob_sale_correct() {
+        let sale: SaleForRest = AliceToBobSale.fake();
+        assert_eq!(sale.prev_owner, "alice.near".to_string());
+        assert_eq!(sale.curr_owner, "bob.near".to_string());
+    }
 }
@@ -0,0 +1,140 @@
+use battlemon_rest::config::Budget;
+use helpers::{assert_error_code, spawn_app, spawn_app_with};
+use serde_json::json;
+
+mod helpers;
+
+#[tokio::test]
+async fn validation_error_names_the_code_and_the_field() {
+    let app = spawn_app().await;
+
+    let response = app.get_sales("limit=-1").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.limit_negative").await;
+    assert_eq!(error.field(), Some("limit"));
+}
+
+#[tokio::test]
+async fn validation_error_carries_the_details() {
+    let app = spawn_app().await;
+
+    let response = app.get_nft_tokens("limit=100000").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.limit_too_large").await;
+    assert_eq!(error.field(), Some("limit"));
+    assert_eq!(error.details(), Some(&json!({ "max": 200 })));
+}
+
+#[tokio::test]
+async fn every_domain_parser_has_its_own_code() {
+    let app = spawn_app().await;
+    let test_cases = [
+        ("offset=-1", "validation.offset_negative", "offset"),
+        (
+            "token_id=lemon%07",
+            "validation.token_id_invalid_chars",
+            "token_id",
+        ),
+        ("days=1", "validation.days_prohibited", "days"),
+    ];
+
+    for (query, code, field) in test_cases {
+        let response = app.get_sales(query).await;
+        let error = assert_error_code(response, code).await;
+        assert_eq!(error.field(), Some(field), "Unexpected field for `{query}`");
+    }
+}
+
+#[tokio::test]
+async fn every_invalid_query_is_reported_at_once() {
+    let app = spawn_app().await;
+
+    let response = app
+        .get_nft_tokens("limit=-1&offset=-1&token_id=lemon%07&owner_id=a&days=1")
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.token_id_invalid_chars").await;
+    assert_eq!(error.field(), Some("token_id"));
+    let fields: Vec<_> = error
+        .errors()
+        .iter()
+        .map(|e| (e.field(), e.code()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("token_id", "validation.token_id_invalid_chars"),
+            ("owner_id", "validation.owner_id_too_short"),
+            ("limit", "validation.limit_negative"),
+            ("offset", "validation.offset_negative"),
+            ("days", "validation.days_prohibited"),
+        ]
+    );
+    assert!(error.errors().iter().all(|e| !e.error().is_empty()));
+}
+
+#[tokio::test]
+async fn malformed_query_is_a_request_error() {
+    let app = spawn_app().await;
+
+    let response = app.get_sales("limit=ten").await;
+    assert_eq!(response.status().as_u16(), 400);
+    let error = assert_error_code(response, "request.invalid_query").await;
+    assert_eq!(error.field(), None);
+}
+
+#[tokio::test]
+async fn malformed_body_reports_the_position() {
+    let app = spawn_app().await;
+
+    let response = app.post_sale(&json!({ "token_id": 1 })).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let error = assert_error_code(response, "request.invalid_body").await;
+    let details = error.details().expect("The error has no details");
+    assert!(details["line"].is_u64() && details["column"].is_u64());
+}
+
+#[tokio::test]
+async fn invalid_credentials_have_an_auth_code() {
+    let app = spawn_app().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/sales", app.address))
+        .basic_auth(&app.test_user.username, Some("wrong password"))
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(response.status().as_u16(), 401);
+    assert_error_code(response, "auth.invalid_credentials").await;
+}
+
+#[tokio::test]
+async fn unknown_network_names_the_network() {
+    let app = spawn_app().await;
+
+    let response = app.get_on_network("devnet", "sales", "").await;
+    assert_eq!(response.status().as_u16(), 404);
+    let error = assert_error_code(response, "network.unknown").await;
+    assert_eq!(error.details(), Some(&json!({ "network": "devnet" })));
+}
+
+#[tokio::test]
+async fn exhausted_budget_reports_the_retry_delay() {
+    let app = spawn_app_with(|cfg| {
+        cfg.rate_limit.routes.insert(
+            "sales".to_string(),
+            Budget {
+                capacity: 1,
+                refill_per_second: 0.001,
+            },
+        );
+    })
+    .await;
+    app.get_sales("").await;
+
+    let response = app.get_sales("").await;
+    assert_eq!(response.status().as_u16(), 429);
+    let error = assert_error_code(response, "rate_limit.exceeded").await;
+    assert!(error.details().unwrap()["retry_after"].is_u64());
+}
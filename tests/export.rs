@@ -0,0 +1,209 @@
+use battlemon_models::market::sale::SaleForRest;
+use battlemon_models::nft::{FromTraitWeights, Lemon, ModelKind};
+use battlemon_rest::routes::{ContractScoped, TRADE_COLUMNS};
+use fake::Fake;
+use reqwest::header::{CONTENT_DISPOSITION, CONTENT_TYPE, ETAG};
+use reqwest::Response;
+
+use crate::dummies::{AliceToBobSale, NftToken, NFT_CONTRACT_ID};
+use crate::helpers::{assert_error_code, spawn_app, spawn_app_with, TestApp};
+
+mod dummies;
+mod helpers;
+
+fn header<'a>(response: &'a Response, name: &str) -> Option<&'a str> {
+    response
+        .headers()
+        .get(name)
+        .map(|value| value.to_str().unwrap())
+}
+
+async fn post_sales(app: &TestApp, count: usize) -> Vec<SaleForRest> {
+    let mut sales = Vec::new();
+    for _ in 0..count {
+        let sale: SaleForRest = AliceToBobSale.fake();
+        let response = app
+            .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+            .await;
+        assert_eq!(response.status(), 201);
+        sales.push(sale);
+    }
+
+    sales
+}
+
+#[tokio::test]
+async fn sales_are_exported_as_csv_with_the_selected_columns() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sales = post_sales(&app, 3).await;
+
+    // the files aren't paged
+    let response = app
+        .get_sales("format=csv&columns=token_id,prev_owner&limit=1")
+        .await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        header(&response, CONTENT_TYPE.as_str()),
+        Some("text/csv; charset=utf-8")
+    );
+    assert_eq!(
+        header(&response, CONTENT_DISPOSITION.as_str()),
+        Some("attachment; filename=\"sales.csv\"")
+    );
+    let body = response.text().await.unwrap();
+    let mut expected = vec!["token_id,prev_owner".to_string()];
+    expected.extend(
+        sales
+            .iter()
+            .map(|sale| format!("{},{}", sale.token_id, sale.prev_owner)),
+    );
+    assert_eq!(body.lines().collect::<Vec<_>>(), expected);
+}
+
+#[tokio::test]
+async fn exports_are_bounded_by_the_max_rows() {
+    let app = spawn_app_with(|cfg| cfg.streaming.max_rows = 2).await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sales = post_sales(&app, 3).await;
+
+    let response = app.get_sales("format=ndjson&columns=token_id").await;
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+    let mut expected: Vec<_> = sales[..2]
+        .iter()
+        .map(|sale| format!(r#"{{"token_id":"{}"}}"#, sale.token_id))
+        .collect();
+    expected.push(r#"{"max_rows":2,"truncated":true}"#.to_string());
+    assert_eq!(body.lines().collect::<Vec<_>>(), expected);
+
+    let response = app
+        .get_sales("format=ndjson&columns=token_id&offset=1")
+        .await;
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+    let expected: Vec<_> = sales[1..]
+        .iter()
+        .map(|sale| format!(r#"{{"token_id":"{}"}}"#, sale.token_id))
+        .collect();
+    assert_eq!(body.lines().collect::<Vec<_>>(), expected);
+}
+
+#[tokio::test]
+async fn truncated_csv_exports_end_with_a_comment_line() {
+    let app = spawn_app_with(|cfg| cfg.streaming.max_rows = 1).await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sales = post_sales(&app, 2).await;
+
+    let response = app.get_sales("format=csv&columns=token_id").await;
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+    let expected = format!(
+        "token_id\r\n{}\r\n# truncated after 1 rows\r\n",
+        sales[0].token_id
+    );
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn nft_tokens_are_exported_as_ndjson() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let tokens: Vec<_> = ["1", "2"]
+        .into_iter()
+        .map(|token_id| NftToken {
+            contract_id: NFT_CONTRACT_ID.to_string(),
+            owner_id: "alice.near".to_string(),
+            token_id: token_id.to_string(),
+            media: "lemon.png".to_string(),
+            model: ModelKind::Lemon(Lemon::from_trait_weights(&"".to_string(), &[1, 1, 1, 1, 1])),
+        })
+        .collect();
+    let response = app.post_nft_token(&tokens).await;
+    assert_eq!(response.status(), 201);
+
+    let response = app
+        .get_nft_tokens("format=ndjson&columns=token_id,model")
+        .await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        header(&response, CONTENT_TYPE.as_str()),
+        Some("application/x-ndjson")
+    );
+    assert_eq!(
+        header(&response, CONTENT_DISPOSITION.as_str()),
+        Some("attachment; filename=\"nft_tokens.ndjson\"")
+    );
+    let body = response.text().await.unwrap();
+    let rows: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let expected: Vec<_> = tokens
+        .iter()
+        .map(|token| {
+            serde_json::json!({
+                "token_id": token.token_id,
+                "model": serde_json::to_value(&token.model).unwrap(),
+            })
+        })
+        .collect();
+    assert_eq!(rows, expected);
+}
+
+#[tokio::test]
+async fn unknown_formats_and_columns_are_rejected() {
+    let app = spawn_app().await;
+
+    let response = app.get_sales("format=xml").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.format_unknown").await;
+    assert_eq!(error.field(), Some("format"));
+
+    let response = app
+        .get_nft_tokens("format=csv&columns=token_id,secret")
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.columns_unknown").await;
+    assert_eq!(error.field(), Some("columns"));
+}
+
+#[tokio::test]
+async fn exported_trades_are_not_cached() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    post_sales(&app, 1).await;
+
+    let response = app.get_paid("days=1&format=csv&columns=price").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(header(&response, ETAG.as_str()), None);
+    assert_eq!(response.text().await.unwrap().lines().count(), 2);
+
+    post_sales(&app, 1).await;
+    let response = app.get_paid("days=1&format=csv&columns=price").await;
+    assert_eq!(response.text().await.unwrap().lines().count(), 3);
+}
+
+#[tokio::test]
+async fn exported_trades_name_their_contract() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sales = post_sales(&app, 1).await;
+
+    let response = app.get_paid("days=1&format=ndjson").await;
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+    let row: serde_json::Value = serde_json::from_str(body.lines().next().unwrap()).unwrap();
+    let mut columns: Vec<_> = row
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    let mut expected = TRADE_COLUMNS.to_vec();
+    columns.sort();
+    expected.sort();
+    assert_eq!(columns, expected);
+    assert_eq!(row["contract_id"], NFT_CONTRACT_ID);
+    assert_eq!(row["token_id"], sales[0].token_id);
+}
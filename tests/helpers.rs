@@ -1,13 +1,14 @@
 use argon2::password_hash::SaltString;
 use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version};
 use once_cell::sync::Lazy;
-use reqwest::{Client, RequestBuilder, Response};
+use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::Serialize;
+use serde_json::json;
 use sqlx::{Connection, Executor, PgConnection, PgPool};
 use uuid::Uuid;
 
 use battlemon_rest::config;
-use battlemon_rest::config::DatabaseSettings;
+use battlemon_rest::config::{DatabaseSettings, Settings};
 use battlemon_rest::errors::JsonError;
 use battlemon_rest::startup::{get_connection_pool, Application};
 use battlemon_rest::telemetry::{get_subscriber, init_subscriber};
@@ -55,6 +56,77 @@ impl TestApp {
             .json(json)
     }
 
+    pub async fn get_on_version(&self, version: &str, path: &str, query: &str) -> Response {
+        self.get(&format!("{version}/{path}"), query).await
+    }
+
+    pub async fn get_on_network(&self, network: &str, path: &str, query: &str) -> Response {
+        self.get(&format!("networks/{network}/{path}"), query).await
+    }
+
+    pub async fn get_with_network_header(
+        &self,
+        network: &str,
+        path: &str,
+        query: &str,
+    ) -> Response {
+        Client::new()
+            .get(format!("{}/{path}?{query}", self.address))
+            .header("X-Network", network)
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
+
+    pub async fn post_with_network_header<T: Serialize>(
+        &self,
+        network: &str,
+        path: &str,
+        json: &T,
+    ) -> Response {
+        self.builder_post_json(path, json)
+            .header("X-Network", network)
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
+
+    pub async fn get_with_header(&self, path: &str, query: &str, header: (&str, &str)) -> Response {
+        Client::new()
+            .get(format!("{}/{path}?{query}", self.address))
+            .header(header.0, header.1)
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
+
+    /// An authorized write with the header, e.g. the `If-Match` precondition.
+    pub async fn write_with_header<T: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        json: &T,
+        header: (&str, &str),
+    ) -> Response {
+        Client::new()
+            .request(method, format!("{}/{path}", self.address))
+            .header(header.0, header.1)
+            .json(json)
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
+
+    pub async fn get_openapi(&self) -> Response {
+        self.get("openapi.json", "").await
+    }
+
+    pub async fn get_docs(&self) -> Response {
+        self.get("docs", "").await
+    }
+
     pub async fn get_paid(&self, query: &str) -> Response {
         self.get("paid", query).await
     }
@@ -67,6 +139,73 @@ impl TestApp {
         self.get("nft_tokens", query).await
     }
 
+    pub async fn get_nft_token(&self, token_id: &str, query: &str) -> Response {
+        self.get(&format!("nft_tokens/{token_id}"), query).await
+    }
+
+    pub async fn get_ask(&self, id: &str) -> Response {
+        self.get(&format!("asks/{id}"), "").await
+    }
+
+    pub async fn get_bid(&self, id: &str) -> Response {
+        self.get(&format!("bids/{id}"), "").await
+    }
+
+    pub async fn get_sale(&self, id: &str) -> Response {
+        self.get(&format!("sales/{id}"), "").await
+    }
+
+    pub async fn get_nft_token_metadata(&self, token_id: &str, query: &str) -> Response {
+        self.get(&format!("nft_tokens/{token_id}/metadata"), query)
+            .await
+    }
+
+    pub async fn get_contract_metadata(&self) -> Response {
+        self.get("contract_metadata", "").await
+    }
+
+    pub async fn get_nft_token_preview(&self, token_id: &str, format: &str) -> Response {
+        self.get(&format!("nft_tokens/{token_id}/preview.{format}"), "")
+            .await
+    }
+
+    pub async fn get_is_owner(&self, user_id: &str, token_id: &str, query: &str) -> Response {
+        self.get(&format!("users/{user_id}/is_owner/{token_id}"), query)
+            .await
+    }
+
+    pub async fn get_search(&self, query: &str) -> Response {
+        self.get("search", query).await
+    }
+
+    pub async fn get_collection_traits(&self, kind: &str, query: &str) -> Response {
+        self.get(&format!("collections/{kind}/traits"), query).await
+    }
+
+    pub async fn get_contracts(&self, query: &str) -> Response {
+        self.get("contracts", query).await
+    }
+
+    pub async fn get_contracts_history(&self, query: &str) -> Response {
+        self.get("contracts/history", query).await
+    }
+
+    pub async fn post_contracts<T: Serialize>(&self, json: &T) -> Response {
+        self.builder_post_json("contracts", json)
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
+
+    pub async fn post_contracts_rollback<T: Serialize>(&self, json: &T) -> Response {
+        self.builder_post_json("contracts/rollback", json)
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
+
     pub async fn post_sale<T: Serialize>(&self, json: &T) -> Response {
         self.builder_post_json("sales", json)
             .basic_auth(&self.test_user.username, Some(&self.test_user.password))
@@ -75,6 +214,41 @@ impl TestApp {
             .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
     }
 
+    pub async fn get_audit_log(&self, query: &str) -> Response {
+        Client::new()
+            .get(format!("{}/audit_log?{query}", self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
+
+    /// Stores the contracts config of the network, which the token related writes are
+    /// validated against.
+    pub async fn configure_contracts(&self, network: &str, nft_contract_id: &str) {
+        sqlx::query!(
+            "INSERT INTO contracts (network, contracts_config) VALUES ($1, $2)",
+            network,
+            json!({
+                "nft_contract_id": nft_contract_id,
+                "market_contract_id": "market.battlemon.near",
+            }),
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to configure the contracts");
+    }
+
+    pub async fn make_test_user_admin(&self) {
+        sqlx::query!(
+            "UPDATE users SET is_admin = true WHERE username = $1",
+            self.test_user.username
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to grant admin to test user");
+    }
+
     pub async fn post_nft_token<T: Serialize>(&self, json: &T) -> Response {
         self.builder_post_json("nft_tokens", json)
             .basic_auth(&self.test_user.username, Some(&self.test_user.password))
@@ -82,6 +256,16 @@ impl TestApp {
             .await
             .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
     }
+
+    pub async fn patch_nft_token<T: Serialize>(&self, json: &T) -> Response {
+        Client::new()
+            .patch(&format!("{}/nft_tokens", self.address))
+            .json(json)
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to execute request {:#?}", e))
+    }
 }
 
 pub struct TestUser {
@@ -119,13 +303,22 @@ impl TestUser {
     }
 }
 
+#[allow(dead_code)]
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(|_| {}).await
+}
+
+pub async fn spawn_app_with<F>(configure: F) -> TestApp
+where
+    F: FnOnce(&mut Settings),
+{
     Lazy::force(&TRACING);
 
     let config = {
         let mut cfg = config::get_config().expect("Failed to read configuration");
         cfg.database.database_name = Uuid::new_v4().to_string();
         cfg.application.port = 0;
+        configure(&mut cfg);
         cfg
     };
     configure_database(&config.database).await;
@@ -176,3 +369,18 @@ pub async fn assert_json_error(response: Response) {
         result
     )
 }
+
+#[allow(dead_code)]
+pub async fn assert_error_code(response: Response, code: &str) -> JsonError {
+    let error = response
+        .json::<JsonError>()
+        .await
+        .expect("The response doesn't contain json error scheme");
+    assert_eq!(error.code(), code, "Unexpected error code in {:?}", error);
+    assert!(
+        error.request_id().is_some(),
+        "The error doesn't carry the request id: {:?}",
+        error
+    );
+    error
+}
@@ -1,4 +1,5 @@
-use crate::helpers::{assert_json_error, spawn_app};
+use crate::dummies::NFT_CONTRACT_ID;
+use crate::helpers::{assert_error_code, assert_json_error, spawn_app};
 use anyhow::Context;
 
 use battlemon_models::nft::NftTokenForRest;
@@ -89,6 +90,7 @@ async fn requests_missing_authorization_are_rejected() {
 #[tokio::test]
 async fn insert_valid_nft_token_success() -> anyhow::Result<()> {
     let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
     let token: dummies::NftToken = dummies::AliceNftToken.fake();
     let token_to_insert = vec![&token];
     let response = app.post_nft_token(&token_to_insert).await;
@@ -121,6 +123,7 @@ async fn insert_valid_nft_token_success() -> anyhow::Result<()> {
 #[tokio::test]
 async fn insert_valid_two_equals_nft_token_reject_without_error_response() -> anyhow::Result<()> {
     let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
     let token: dummies::NftToken = dummies::AliceNftToken.fake();
     let tokens_to_insert = vec![&token];
     for _ in 0..2 {
@@ -173,11 +176,40 @@ async fn insert_invalid_nft_token_rejects_and_returns_400_status() {
     assert_json_error(response).await;
 }
 
+#[tokio::test]
+async fn insert_batch_with_invalid_nft_token_rejects_whole_batch_with_422_status(
+) -> anyhow::Result<()> {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let valid_token: dummies::NftToken = dummies::AliceNftToken.fake();
+    let invalid_token = dummies::NftToken {
+        owner_id: "Bob".to_string(),
+        media: "javascript://alert(1)".to_string(),
+        ..dummies::BobNftToken.fake()
+    };
+
+    let response = app
+        .post_nft_token(&vec![&valid_token, &invalid_token])
+        .await;
+    assert_eq!(response.status(), 422, "Response status is not `422`");
+    let error = assert_error_code(response, "validation.account_id_invalid_chars").await;
+    let fields: Vec<_> = error.errors().iter().map(|e| e.field()).collect();
+    assert_eq!(fields, ["[1].owner_id", "[1].media"]);
+
+    let response: RowsJsonReport<NftTokenForRest> = app.get_nft_tokens("").await.json().await?;
+    assert!(
+        response.rows.is_empty(),
+        "The valid token of the batch is stored"
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn insert_nft_token_fails_and_return_500_if_there_is_a_fatal_database_error() {
     let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
     let token: Vec<dummies::NftToken> = vec![dummies::AliceNftToken.fake()];
-    sqlx::query!("ALTER TABLE nft_tokens DROP COLUMN owner_id;",)
+    sqlx::query!("ALTER TABLE nft_tokens DROP COLUMN owner_id CASCADE;",)
         .execute(&app.db_pool)
         .await
         .unwrap();
@@ -199,3 +231,40 @@ async fn insert_nft_token_fails_and_return_500_if_there_is_a_fatal_database_erro
     let response = app.post_nft_token(&token).await;
     assert_json_error(response).await
 }
+
+#[tokio::test]
+async fn insert_nft_token_of_unknown_contract_rejects_with_422_status() -> anyhow::Result<()> {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let token = dummies::NftToken {
+        contract_id: "items.near".to_string(),
+        ..dummies::AliceNftToken.fake()
+    };
+
+    let response = app.post_nft_token(&vec![&token]).await;
+    assert_eq!(response.status(), 422, "Response status is not `422`");
+    let error = assert_error_code(response, "validation.contract_id_unknown").await;
+    assert_eq!(error.field(), Some("[0].contract_id"));
+
+    let response: RowsJsonReport<NftTokenForRest> = app.get_nft_tokens("").await.json().await?;
+    assert!(response.rows.is_empty(), "The token is stored");
+    Ok(())
+}
+
+#[tokio::test]
+async fn insert_nft_token_with_invalid_media_hash_rejects_with_422_status() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let token: dummies::NftToken = dummies::AliceNftToken.fake();
+    let mut json = serde_json::to_value(&token).unwrap();
+
+    json["media_hash"] = json!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    let response = app.post_nft_token(&vec![&json]).await;
+    assert_eq!(response.status(), 422, "Response status is not `422`");
+    let error = assert_error_code(response, "validation.media_hash_invalid").await;
+    assert_eq!(error.field(), Some("[0].media_hash"));
+
+    json["media_hash"] = json!("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=");
+    let response = app.post_nft_token(&vec![&json]).await;
+    assert_eq!(response.status(), 201, "The sha256 media hash is rejected");
+}
@@ -1,9 +1,10 @@
-use crate::helpers::{assert_json_error, spawn_app};
+use crate::dummies::NFT_CONTRACT_ID;
+use crate::helpers::{assert_error_code, assert_json_error, spawn_app};
 use anyhow::Context;
 
 use battlemon_models::market::sale::{SaleForDb, SaleForRest};
-use battlemon_rest::routes::RowsJsonReport;
-use fake::{Fake, Faker};
+use battlemon_rest::routes::{ContractScoped, RowsJsonReport};
+use fake::Fake;
 use serde_json::json;
 use uuid::Uuid;
 
@@ -23,7 +24,7 @@ async fn invalid_password_is_rejected() {
     let response = reqwest::Client::new()
         .post(&format!("{}/sales", &app.address))
         .basic_auth(username, Some(password))
-        .json(&Faker.fake::<SaleForRest>())
+        .json(&dummies::AliceToBobSale.fake::<SaleForRest>())
         .send()
         .await
         .expect("Failed to send request");
@@ -48,7 +49,7 @@ async fn non_existing_user_is_rejected() {
     let response = reqwest::Client::new()
         .post(&format!("{}/sales", &app.address))
         .basic_auth(username, Some(password))
-        .json(&Faker.fake::<SaleForRest>())
+        .json(&dummies::AliceToBobSale.fake::<SaleForRest>())
         .send()
         .await
         .expect("Failed to send request");
@@ -70,7 +71,7 @@ async fn requests_missing_authorization_are_rejected() {
 
     let response = reqwest::Client::new()
         .post(&format!("{}/sales", app.address))
-        .json(&Faker.fake::<SaleForRest>())
+        .json(&dummies::AliceToBobSale.fake::<SaleForRest>())
         .send()
         .await
         .expect("Failed to execute request");
@@ -89,8 +90,11 @@ async fn requests_missing_authorization_are_rejected() {
 #[tokio::test]
 async fn insert_valid_sale_success() -> anyhow::Result<()> {
     let app = spawn_app().await;
-    let sale: SaleForRest = Faker.fake();
-    let response = app.post_sale(&sale).await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sale: SaleForRest = dummies::AliceToBobSale.fake();
+    let response = app
+        .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+        .await;
 
     let status = response.status();
     let body = response
@@ -124,17 +128,71 @@ async fn insert_invalid_sale_rejects_and_returns_400_status() {
     let sale = json!({
         "wrong": "token json"
     });
-    let response = app.post_sale(&sale).await;
+    let response = app
+        .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+        .await;
     assert_eq!(response.status(), 400, "Response status is not `400`");
     assert_json_error(response).await;
 }
 
+#[tokio::test]
+async fn insert_sale_with_invalid_fields_rejects_and_returns_422_status() -> anyhow::Result<()> {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sale: SaleForRest = dummies::AliceToBobSale.fake();
+    let test_cases = [
+        (
+            json!({ "price": "-1" }),
+            "validation.price_not_positive",
+            "price",
+        ),
+        (
+            json!({ "price": "0.0000000000000000000000001" }),
+            "validation.price_scale_too_large",
+            "price",
+        ),
+        (
+            json!({ "token_id": "" }),
+            "validation.token_id_empty",
+            "token_id",
+        ),
+        (
+            json!({ "prev_owner": "Alice" }),
+            "validation.account_id_invalid_chars",
+            "prev_owner",
+        ),
+        (
+            json!({ "curr_owner": "alice.near" }),
+            "validation.curr_owner_is_prev_owner",
+            "curr_owner",
+        ),
+    ];
+
+    for (patch, code, field) in test_cases {
+        let mut body = serde_json::to_value(ContractScoped::new(NFT_CONTRACT_ID, &sale))?;
+        for (key, value) in patch.as_object().expect("The patch is an object") {
+            body[key] = value.clone();
+        }
+        let response = app.post_sale(&body).await;
+        assert_eq!(response.status(), 422, "`{}` isn't rejected", patch);
+        let error = assert_error_code(response, code).await;
+        assert_eq!(error.field(), Some(field));
+    }
+
+    let response: RowsJsonReport<SaleForDb> = app.get_sales("").await.json().await?;
+    assert!(response.rows.is_empty(), "The invalid sales are stored");
+    Ok(())
+}
+
 #[tokio::test]
 async fn insert_valid_two_sales_success() -> anyhow::Result<()> {
     let app = spawn_app().await;
-    let sale: SaleForRest = Faker.fake();
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sale: SaleForRest = dummies::AliceToBobSale.fake();
     for _ in 0..2 {
-        let response = app.post_sale(&sale).await;
+        let response = app
+            .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+            .await;
 
         let status = response.status();
         let body = response
@@ -173,12 +231,15 @@ async fn insert_valid_two_sales_success() -> anyhow::Result<()> {
 #[tokio::test]
 async fn insert_sale_fails_and_return_500_if_there_is_a_fatal_database_error() {
     let app = spawn_app().await;
-    let sale: SaleForRest = Faker.fake();
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sale: SaleForRest = dummies::AliceToBobSale.fake();
     sqlx::query!("ALTER TABLE sales DROP COLUMN prev_owner;",)
         .execute(&app.db_pool)
         .await
         .unwrap();
-    let response = app.post_sale(&sale).await;
+    let response = app
+        .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+        .await;
     let status = response.status();
     let body = response
         .text()
@@ -193,6 +254,8 @@ async fn insert_sale_fails_and_return_500_if_there_is_a_fatal_database_error() {
         status, body,
     );
 
-    let response = app.post_sale(&sale).await;
+    let response = app
+        .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+        .await;
     assert_json_error(response).await
 }
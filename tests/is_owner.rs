@@ -1,15 +1,15 @@
-use crate::dummies::{AliceNftToken, NftToken};
+use crate::dummies::{AliceNftToken, BobNftToken, NftToken, NFT_CONTRACT_ID};
+use battlemon_rest::routes::{IsOwnerReport, Ownership, TokenOwnership};
 use fake::Fake;
-use helpers::{assert_json_error, spawn_app};
-use serde_json::Value;
+use helpers::{assert_error_code, assert_json_error, spawn_app, spawn_app_with, TestApp};
+use serde_json::{json, Value};
 use sqlx::types::{chrono::Utc, Json};
 
 mod dummies;
 mod helpers;
 
-#[tokio::test]
-async fn is_owner_route_for_nft_tokens_success() {
-    let app = spawn_app().await;
+/// Stores the tokens `0` to `9` of alice.
+async fn store_alice_tokens(app: &TestApp) {
     let tokens = (0..10).map(|i| {
         let mut token: NftToken = AliceNftToken.fake();
         token.token_id = i.to_string();
@@ -19,9 +19,10 @@ async fn is_owner_route_for_nft_tokens_success() {
     for token in tokens {
         sqlx::query!(
             r#"
-            INSERT INTO nft_tokens (owner_id, token_id, media, model, db_created_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, media, model, db_created_at)
+            VALUES ('mainnet', $1, $2, $3, $4, $5, $6)
             "#,
+            token.contract_id,
             token.owner_id,
             token.token_id,
             token.media,
@@ -32,6 +33,19 @@ async fn is_owner_route_for_nft_tokens_success() {
         .await
         .unwrap();
     }
+}
+
+fn ownership(token_id: &str, ownership: Ownership) -> TokenOwnership {
+    TokenOwnership {
+        token_id: token_id.to_string(),
+        ownership,
+    }
+}
+
+#[tokio::test]
+async fn is_owner_route_for_nft_tokens_success() {
+    let app = spawn_app().await;
+    store_alice_tokens(&app).await;
 
     let valid_payloads_and_expected_results = [
         (vec!["1", "2"], true),
@@ -43,6 +57,8 @@ async fn is_owner_route_for_nft_tokens_success() {
         (vec!["one", "two"], false),
         (vec!["0", "9"], true),
         (vec!["0", "10"], false),
+        (vec!["1", "1"], true),
+        (vec!["1", " 1", "2"], true),
     ];
 
     for (payload, expected) in valid_payloads_and_expected_results {
@@ -66,3 +82,124 @@ async fn is_owner_route_for_nft_tokens_success() {
         assert_eq!(ret["result"], expected);
     }
 }
+
+#[tokio::test]
+async fn is_owner_rejects_invalid_user_and_token_ids_with_422() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post("users/Alice/is_owner", &serde_json::json!(["1", "", "2"]))
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.account_id_invalid_chars").await;
+    let fields: Vec<_> = error.errors().iter().map(|e| e.field()).collect();
+    assert_eq!(fields, ["user_id", "[1]"]);
+}
+
+#[tokio::test]
+async fn is_owner_details_the_ownership_of_every_distinct_token() {
+    let app = spawn_app().await;
+    store_alice_tokens(&app).await;
+
+    let response = app
+        .post(
+            "users/alice.near/is_owner?details=true",
+            &json!(["3", "11", "3"]),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: IsOwnerReport = response.json().await.unwrap();
+    assert!(!report.result);
+    assert_eq!(
+        report.tokens,
+        Some(vec![
+            ownership("3", Ownership::Owned),
+            ownership("11", Ownership::UnknownToken),
+        ])
+    );
+
+    let response = app
+        .post("users/bob.near/is_owner?details=true", &json!(["3"]))
+        .await;
+    let report: IsOwnerReport = response.json().await.unwrap();
+    assert!(!report.result);
+    assert_eq!(
+        report.tokens,
+        Some(vec![ownership("3", Ownership::NotOwned)])
+    );
+
+    let response = app.post("users/alice.near/is_owner", &json!(["3"])).await;
+    let report: IsOwnerReport = response.json().await.unwrap();
+    assert_eq!(report.tokens, None);
+}
+
+#[tokio::test]
+async fn is_owner_of_a_single_token() {
+    let app = spawn_app().await;
+    store_alice_tokens(&app).await;
+
+    for (user_id, token_id, expected) in [
+        ("alice.near", "1", Ownership::Owned),
+        ("bob.near", "1", Ownership::NotOwned),
+        ("alice.near", "11", Ownership::UnknownToken),
+    ] {
+        let response = app.get_is_owner(user_id, token_id, "").await;
+        assert_eq!(response.status().as_u16(), 200);
+        let report: IsOwnerReport = response.json().await.unwrap();
+        assert_eq!(report.result, expected == Ownership::Owned);
+        assert_eq!(report.tokens, Some(vec![ownership(token_id, expected)]));
+    }
+
+    let response = app.get_is_owner("Alice", "1", "").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.account_id_invalid_chars").await;
+    assert_eq!(error.field(), Some("user_id"));
+}
+
+#[tokio::test]
+async fn is_owner_of_a_token_id_shared_by_two_contracts_needs_the_contract() {
+    let items_contract_id = "items.battlemon.near";
+    let app = spawn_app_with(|config| {
+        config
+            .networks
+            .nft_contracts
+            .insert("mainnet".to_string(), vec![items_contract_id.to_string()]);
+    })
+    .await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let mut lemon: NftToken = AliceNftToken.fake();
+    lemon.token_id = "1".to_string();
+    let mut item: NftToken = BobNftToken.fake();
+    item.contract_id = items_contract_id.to_string();
+    item.token_id = "1".to_string();
+    let response = app.post_nft_token(&vec![&lemon, &item]).await;
+    assert_eq!(response.status(), 201, "Failed to insert the tokens");
+
+    let response = app.get_is_owner("alice.near", "1", "").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.contract_id_ambiguous").await;
+    assert_eq!(error.field(), Some("contract_id"));
+    let response = app.post("users/alice.near/is_owner", &json!(["1"])).await;
+    assert_eq!(response.status().as_u16(), 422);
+    assert_error_code(response, "validation.contract_id_ambiguous").await;
+
+    for (contract_id, expected) in [(NFT_CONTRACT_ID, true), (items_contract_id, false)] {
+        let query = format!("contract_id={contract_id}");
+        let response = app.get_is_owner("alice.near", "1", &query).await;
+        assert_eq!(response.status().as_u16(), 200);
+        let report: IsOwnerReport = response.json().await.unwrap();
+        assert_eq!(
+            report.result, expected,
+            "Unexpected ownership in `{contract_id}`"
+        );
+
+        let response = app
+            .post(&format!("users/alice.near/is_owner?{query}"), &json!(["1"]))
+            .await;
+        let report: IsOwnerReport = response.json().await.unwrap();
+        assert_eq!(
+            report.result, expected,
+            "Unexpected ownership in `{contract_id}`"
+        );
+    }
+}
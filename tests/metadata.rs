@@ -0,0 +1,71 @@
+use battlemon_models::nft::{FromTraitWeights, Lemon, ModelKind};
+use battlemon_rest::routes::{NftContractMetadata, TokenMetadata};
+use reqwest::header::ETAG;
+
+use crate::dummies::{NftToken, NFT_CONTRACT_ID};
+use crate::helpers::{assert_error_code, spawn_app, spawn_app_with};
+
+mod dummies;
+mod helpers;
+
+fn lemon() -> NftToken {
+    NftToken {
+        contract_id: NFT_CONTRACT_ID.to_string(),
+        owner_id: "alice.near".to_string(),
+        token_id: "1".to_string(),
+        media: "lemon.png".to_string(),
+        model: ModelKind::Lemon(Lemon::from_trait_weights(&"".to_string(), &[1, 1, 1, 1, 1])),
+    }
+}
+
+#[tokio::test]
+async fn token_metadata_has_the_model_in_extra() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let token = lemon();
+    let response = app.post_nft_token(&vec![token.clone()]).await;
+    assert_eq!(response.status(), 201);
+
+    let response = app.get_nft_token_metadata("1", "").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers().get(ETAG).map(|v| v.to_str().unwrap()),
+        Some("\"1\"")
+    );
+    let metadata: TokenMetadata = response.json().await.unwrap();
+    assert_eq!(metadata.media.as_deref(), Some("lemon.png"));
+    let extra: serde_json::Value = serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+    assert_eq!(
+        extra["model"],
+        serde_json::to_value(&token.model).unwrap(),
+        "The model isn't in the extra"
+    );
+
+    let response = app.get_nft_token_metadata("2", "").await;
+    assert_eq!(response.status(), 404);
+    assert_error_code(response, "nft_tokens.not_found").await;
+}
+
+#[tokio::test]
+async fn contract_metadata_is_read_from_the_config() {
+    let app = spawn_app_with(|cfg| {
+        let mut metadata = cfg.contract_metadata.default.clone();
+        metadata.symbol = "ITEM".to_string();
+        cfg.contract_metadata
+            .contracts
+            .insert(NFT_CONTRACT_ID.to_string(), metadata);
+    })
+    .await;
+
+    let response = app.get_contract_metadata().await;
+    assert_eq!(response.status(), 404);
+    assert_error_code(response, "contracts.not_found").await;
+
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let response = app.get_contract_metadata().await;
+    assert_eq!(response.status(), 200);
+    let metadata: NftContractMetadata = response.json().await.unwrap();
+    assert_eq!(metadata.spec, "nft-1.0.0");
+    assert_eq!(metadata.name, "Battlemon");
+    assert_eq!(metadata.symbol, "ITEM");
+}
@@ -0,0 +1,112 @@
+use crate::dummies::NFT_CONTRACT_ID;
+use crate::helpers::{assert_json_error, spawn_app};
+
+use battlemon_models::market::sale::{SaleForDb, SaleForRest};
+use battlemon_models::nft::NftTokenForRest;
+use battlemon_rest::routes::{ContractScoped, RowsJsonReport};
+use fake::Fake;
+
+mod dummies;
+mod helpers;
+
+#[tokio::test]
+async fn sale_stored_on_testnet_is_served_only_on_testnet() {
+    let app = spawn_app().await;
+    app.configure_contracts("testnet", NFT_CONTRACT_ID).await;
+    let sale = dummies::AliceToBobSale.fake::<SaleForRest>();
+
+    let response = app
+        .post_with_network_header(
+            "testnet",
+            "sales",
+            &ContractScoped::new(NFT_CONTRACT_ID, &sale),
+        )
+        .await;
+    assert!(
+        response.status().is_success(),
+        "Failed to insert sale, status is `{}`",
+        response.status()
+    );
+
+    let testnet_responses = [
+        app.get_on_network("testnet", "sales", "").await,
+        app.get_with_network_header("testnet", "sales", "").await,
+    ];
+    for response in testnet_responses {
+        let report = response
+            .json::<RowsJsonReport<SaleForDb>>()
+            .await
+            .expect("Failed to parse the sales report");
+        assert_eq!(report.rows.len(), 1, "The testnet sale must be served");
+        assert_eq!(report.rows[0].token_id, sale.token_id);
+    }
+
+    let mainnet_responses = [
+        app.get_sales("").await,
+        app.get_on_network("mainnet", "sales", "").await,
+    ];
+    for response in mainnet_responses {
+        let report = response
+            .json::<RowsJsonReport<SaleForDb>>()
+            .await
+            .expect("Failed to parse the sales report");
+        assert!(
+            report.rows.is_empty(),
+            "The testnet sale mustn't be served on mainnet"
+        );
+    }
+}
+
+#[tokio::test]
+async fn same_token_id_can_be_stored_on_each_network() {
+    let app = spawn_app().await;
+    let token = dummies::AliceNftToken.fake::<dummies::NftToken>();
+
+    for network in ["mainnet", "testnet"] {
+        app.configure_contracts(network, NFT_CONTRACT_ID).await;
+        let response = app
+            .post_with_network_header(network, "nft_tokens", &vec![token.clone()])
+            .await;
+        assert!(
+            response.status().is_success(),
+            "Failed to insert nft token on `{network}`, status is `{}`",
+            response.status()
+        );
+
+        let report = app
+            .get_on_network(
+                network,
+                "nft_tokens",
+                &format!("token_id={}", token.token_id),
+            )
+            .await
+            .json::<RowsJsonReport<NftTokenForRest>>()
+            .await
+            .expect("Failed to parse the nft tokens report");
+        assert_eq!(
+            report.rows.len(),
+            1,
+            "The nft token must be stored on `{network}`"
+        );
+    }
+}
+
+#[tokio::test]
+async fn unknown_network_returns_404() {
+    let app = spawn_app().await;
+
+    let responses = [
+        app.get_on_network("devnet", "sales", "").await,
+        app.get_with_network_header("devnet", "sales", "").await,
+    ];
+    for response in responses {
+        let actual_status = response.status();
+        assert_eq!(
+            actual_status,
+            reqwest::StatusCode::NOT_FOUND,
+            "The expected status code must be 404, actual is `{}`",
+            actual_status
+        );
+        assert_json_error(response).await;
+    }
+}
@@ -2,36 +2,34 @@ use battlemon_models::nft::NftTokenForRest;
 use fake::Fake;
 use sqlx::types::{chrono::Utc, Json};
 
-use crate::dummies::{AliceNftToken, BobNftToken, DannyNftToken};
-use battlemon_rest::routes::RowsJsonReport;
-use helpers::{assert_json_error, spawn_app};
+use crate::dummies::{AliceNftToken, BobNftToken, DannyNftToken, NftToken, NFT_CONTRACT_ID};
+use battlemon_rest::config::TokenIdCharset;
+use battlemon_rest::routes::{ContractScoped, IsOwnerReport, NftTokenRow, RowsJsonReport};
+use helpers::{assert_error_code, assert_json_error, spawn_app, spawn_app_with};
 
 mod dummies;
 mod helpers;
 
 #[tokio::test]
-async fn nft_tokens_return_400_with_invalid_queries() {
+async fn nft_tokens_return_400_with_malformed_queries() {
     let app = spawn_app().await;
-    let invalid_queries = [
+    let malformed_queries = [
         "owner_id=alice.near&owner_id=bob.near",
         "limit",
         "limit=",
-        "limit=-1",
         r#"limit="abc""#,
         r#"limit="10""#,
         "offset",
         "offset=",
-        "offset=-1",
         r#"offset="abc""#,
         r#"offset="10""#,
         "days",
         "days=",
-        "days=-1",
         r#"days="abc""#,
         r#"days="10""#,
     ];
 
-    for query in invalid_queries {
+    for query in malformed_queries {
         let response = app.get_nft_tokens(query).await;
         assert_eq!(
             response.status(),
@@ -43,6 +41,29 @@ async fn nft_tokens_return_400_with_invalid_queries() {
     }
 }
 
+#[tokio::test]
+async fn nft_tokens_return_422_with_invalid_queries() {
+    let app = spawn_app().await;
+    let invalid_queries = [
+        "limit=-1",
+        "offset=-1",
+        "days=-1",
+        "owner_id=a",
+        "token_id=lemon%07",
+    ];
+
+    for query in invalid_queries {
+        let response = app.get_nft_tokens(query).await;
+        assert_eq!(
+            response.status(),
+            422,
+            "Response status is not 422 for the query `{}`",
+            query
+        );
+        assert_json_error(response).await;
+    }
+}
+
 #[tokio::test]
 async fn nft_tokens_success_with_valid_queries() {
     let app = spawn_app().await;
@@ -54,9 +75,10 @@ async fn nft_tokens_success_with_valid_queries() {
     for token in tokens {
         sqlx::query!(
             r#"
-            INSERT INTO nft_tokens (owner_id, token_id, media, model, db_created_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, media, model, db_created_at)
+            VALUES ('mainnet', $1, $2, $3, $4, $5, $6)
             "#,
+            token.contract_id,
             token.owner_id,
             token.token_id,
             token.media,
@@ -110,7 +132,7 @@ async fn nft_tokens_success_with_valid_queries() {
 #[tokio::test]
 async fn nft_tokens_fails_and_return_500_if_there_is_a_fatal_database_error() {
     let app = spawn_app().await;
-    sqlx::query!("ALTER TABLE nft_tokens DROP COLUMN owner_id;",)
+    sqlx::query!("ALTER TABLE nft_tokens DROP COLUMN owner_id CASCADE;",)
         .execute(&app.db_pool)
         .await
         .unwrap();
@@ -132,9 +154,10 @@ async fn nft_tokens_for_valid_query_by_token_id_returns_200() {
     for token in &tokens {
         sqlx::query!(
             r#"
-            INSERT INTO nft_tokens (owner_id, token_id, media, model, db_created_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, media, model, db_created_at)
+            VALUES ('mainnet', $1, $2, $3, $4, $5, $6)
             "#,
+            token.contract_id,
             token.owner_id,
             token.token_id,
             token.media,
@@ -179,9 +202,10 @@ async fn nft_tokens_for_valid_query_by_nft_kind_returns_200() {
     for token in &tokens {
         sqlx::query!(
             r#"
-            INSERT INTO nft_tokens (owner_id, token_id, media, model, db_created_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, media, model, db_created_at)
+            VALUES ('mainnet', $1, $2, $3, $4, $5, $6)
             "#,
+            token.contract_id,
             token.owner_id,
             token.token_id,
             token.media,
@@ -208,3 +232,157 @@ async fn nft_tokens_for_valid_query_by_nft_kind_returns_200() {
         query
     );
 }
+
+#[tokio::test]
+async fn nft_tokens_with_nep171_token_id_are_queried_by_id() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let mut token: NftToken = AliceNftToken.fake();
+    token.token_id = "weapon:123".to_string();
+    let response = app.post_nft_token(&vec![&token]).await;
+    assert_eq!(response.status(), 201, "Failed to insert the token");
+
+    let response = app.get_nft_tokens("token_id=weapon%3A123").await;
+    assert_eq!(response.status(), 200);
+    let actual: RowsJsonReport<NftTokenForRest> = response.json().await.unwrap();
+    assert_eq!(actual.rows.len(), 1);
+    assert_eq!(actual.rows[0].token_id, "weapon:123");
+}
+
+#[tokio::test]
+async fn token_id_format_is_configurable() {
+    let app = spawn_app_with(|config| {
+        config.token_ids.default.charset = TokenIdCharset::Numeric;
+        config.token_ids.default.max_length = 4;
+    })
+    .await;
+    let test_cases = [
+        ("token_id=abc", "validation.token_id_not_numeric"),
+        ("token_id=12345", "validation.token_id_too_long"),
+    ];
+
+    for (query, code) in test_cases {
+        let response = app.get_nft_tokens(query).await;
+        assert_eq!(response.status().as_u16(), 422, "`{query}` isn't rejected");
+        assert_error_code(response, code).await;
+    }
+}
+
+#[tokio::test]
+async fn nft_tokens_are_filtered_by_contract() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let token: NftToken = AliceNftToken.fake();
+    let response = app.post_nft_token(&vec![&token]).await;
+    assert_eq!(response.status(), 201, "Failed to insert the token");
+
+    let query = format!("contract_id={NFT_CONTRACT_ID}&token_id={}", token.token_id);
+    let response = app.get_nft_tokens(&query).await;
+    assert_eq!(response.status(), 200);
+    let actual: RowsJsonReport<ContractScoped<NftTokenForRest>> = response.json().await.unwrap();
+    assert_eq!(actual.rows.len(), 1);
+    assert_eq!(actual.rows[0].contract_id, NFT_CONTRACT_ID);
+    assert_eq!(actual.rows[0].item.token_id, token.token_id);
+
+    let response = app.get_nft_tokens("contract_id=items.near").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let error = assert_error_code(response, "validation.contract_id_unknown").await;
+    assert_eq!(error.field(), Some("contract_id"));
+}
+
+#[tokio::test]
+async fn same_token_id_is_read_back_from_each_contract() {
+    let items_contract_id = "items.battlemon.near";
+    let app = spawn_app_with(|config| {
+        config
+            .networks
+            .nft_contracts
+            .insert("mainnet".to_string(), vec![items_contract_id.to_string()]);
+    })
+    .await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let mut lemon: NftToken = AliceNftToken.fake();
+    lemon.token_id = "1".to_string();
+    let mut item: NftToken = BobNftToken.fake();
+    item.contract_id = items_contract_id.to_string();
+    item.token_id = "1".to_string();
+    let response = app.post_nft_token(&vec![&lemon, &item]).await;
+    assert_eq!(response.status(), 201, "Failed to insert the tokens");
+    // the replaced contract stays valid
+    app.configure_contracts("mainnet", "nft2.battlemon.near")
+        .await;
+
+    for (token, other_owner) in [(&lemon, &item.owner_id), (&item, &lemon.owner_id)] {
+        let query = format!("contract_id={}", token.contract_id);
+        let response = app.get_nft_tokens(&query).await;
+        assert_eq!(response.status(), 200);
+        let actual: RowsJsonReport<ContractScoped<NftTokenForRest>> =
+            response.json().await.unwrap();
+        assert_eq!(actual.rows.len(), 1);
+        assert_eq!(actual.rows[0].contract_id, token.contract_id);
+        assert_eq!(actual.rows[0].item.owner_id, token.owner_id);
+
+        let response = app.get_nft_token("1", &query).await;
+        assert_eq!(response.status(), 200);
+        let actual: ContractScoped<NftTokenRow> = response.json().await.unwrap();
+        assert_eq!(actual.contract_id, token.contract_id);
+        assert_eq!(actual.item.nft_token.owner_id, token.owner_id);
+
+        for (user_id, expected) in [(&token.owner_id, true), (other_owner, false)] {
+            let response = app.get_is_owner(user_id, "1", &query).await;
+            assert_eq!(response.status(), 200);
+            let report: IsOwnerReport = response.json().await.unwrap();
+            assert_eq!(report.result, expected, "`{user_id}` under `{query}`");
+        }
+    }
+
+    let response = app.get_nft_token("1", "").await;
+    assert_eq!(response.status(), 422);
+    let error = assert_error_code(response, "validation.contract_id_ambiguous").await;
+    assert_eq!(error.field(), Some("contract_id"));
+    let response = app.get_nft_token_metadata("1", "").await;
+    assert_eq!(response.status(), 422);
+    assert_error_code(response, "validation.contract_id_ambiguous").await;
+}
+
+#[tokio::test]
+async fn nft_tokens_media_is_resolved_to_the_preferred_gateway() {
+    let app = spawn_app_with(|config| {
+        config.contract_metadata.default.base_uri = Some("https://media.battlemon.com/".to_string())
+    })
+    .await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+    let test_cases = [
+        (
+            format!("ipfs://{cid}/1.png"),
+            format!("https://ipfs.io/ipfs/{cid}/1.png"),
+        ),
+        (
+            format!("https://gateway.pinata.cloud/ipfs/{cid}"),
+            format!("https://ipfs.io/ipfs/{cid}"),
+        ),
+        (
+            "lemons/1.png".to_string(),
+            "https://media.battlemon.com/lemons/1.png".to_string(),
+        ),
+    ];
+
+    for (i, (media, expected)) in test_cases.into_iter().enumerate() {
+        let token = NftToken {
+            token_id: i.to_string(),
+            media: media.clone(),
+            ..AliceNftToken.fake()
+        };
+        let response = app.post_nft_token(&vec![&token]).await;
+        assert_eq!(response.status(), 201, "Failed to insert the token");
+
+        let response = app.get_nft_token(&token.token_id, "").await;
+        let actual: ContractScoped<NftTokenRow> = response.json().await.unwrap();
+        assert_eq!(
+            actual.item.nft_token.media, media,
+            "The raw media is changed"
+        );
+        assert_eq!(actual.item.media_url, Some(expected), "`{media}`");
+    }
+}
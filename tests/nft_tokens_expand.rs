@@ -0,0 +1,184 @@
+use chrono::Duration;
+use fake::Fake;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sqlx::types::{chrono::Utc, Json};
+
+use crate::dummies::{AliceNftToken, NftToken, NFT_CONTRACT_ID};
+use battlemon_rest::routes::{ContractScoped, NftTokenRow, RowsJsonReport};
+use helpers::{assert_error_code, spawn_app, TestApp};
+
+mod dummies;
+mod helpers;
+
+async fn store_token(app: &TestApp, token_id: &str) {
+    let token: NftToken = AliceNftToken.fake();
+    sqlx::query!(
+        r#"
+        INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, media, model, db_created_at)
+        VALUES ('mainnet', $1, $2, $3, $4, $5, $6)
+        "#,
+        NFT_CONTRACT_ID,
+        token.owner_id,
+        token_id,
+        token.media,
+        Json(token.model) as _,
+        Utc::now()
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the nft token");
+}
+
+async fn store_ask(app: &TestApp, id: &str, token_id: &str, price: Decimal) {
+    sqlx::query!(
+        r#"
+        INSERT INTO asks (network, contract_id, id, token_id, account_id, approval_id, price)
+        VALUES ('mainnet', $1, $2, $3, 'alice.near', 1, $4)
+        "#,
+        NFT_CONTRACT_ID,
+        id,
+        token_id,
+        price,
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the ask");
+}
+
+async fn store_bid(app: &TestApp, id: &str, token_id: &str, price: Decimal, expire_in: Duration) {
+    sqlx::query!(
+        r#"
+        INSERT INTO bids (network, contract_id, id, token_id, account_id, expire_at, create_at, price)
+        VALUES ('mainnet', $1, $2, $3, 'bob.near', $4, $5, $6)
+        "#,
+        NFT_CONTRACT_ID,
+        id,
+        token_id,
+        Utc::now() + expire_in,
+        Utc::now(),
+        price,
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the bid");
+}
+
+async fn store_sale(app: &TestApp, token_id: &str, price: Decimal, days_ago: i64) {
+    sqlx::query!(
+        r#"
+        INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+        VALUES ('mainnet', $1, 'bob.near', 'alice.near', $2, $3, $4)
+        "#,
+        NFT_CONTRACT_ID,
+        token_id,
+        price,
+        Utc::now() - Duration::days(days_ago),
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the sale");
+}
+
+/// Stores the token `1` with asks and sales, the token `2` with bids and the token `3` with
+/// an expired bid only.
+async fn store_market(app: &TestApp) {
+    for token_id in ["1", "2", "3"] {
+        store_token(app, token_id).await;
+    }
+    store_ask(app, "ask-1", "1", dec!(5)).await;
+    store_ask(app, "ask-2", "1", dec!(3)).await;
+    store_sale(app, "1", dec!(2), 2).await;
+    store_sale(app, "1", dec!(4), 1).await;
+    store_bid(app, "bid-1", "2", dec!(10), Duration::days(1)).await;
+    store_bid(app, "bid-2", "2", dec!(20), -Duration::days(1)).await;
+    store_bid(app, "bid-3", "3", dec!(30), -Duration::days(1)).await;
+}
+
+async fn nft_tokens(app: &TestApp, query: &str) -> Vec<NftTokenRow> {
+    let response = app.get_nft_tokens(query).await;
+    assert_eq!(response.status(), 200, "`{query}` failed");
+    let report: RowsJsonReport<ContractScoped<NftTokenRow>> = response
+        .json()
+        .await
+        .expect("Failed to parse the nft tokens");
+    report.rows.into_iter().map(|r| r.item).collect()
+}
+
+fn token_ids(rows: &[NftTokenRow]) -> Vec<&str> {
+    rows.iter().map(|r| r.nft_token.token_id.as_str()).collect()
+}
+
+#[tokio::test]
+async fn nft_tokens_embed_the_expanded_market_rows() {
+    let app = spawn_app().await;
+    store_market(&app).await;
+
+    let rows = nft_tokens(&app, "expand=ask,best_bid,last_sale").await;
+    assert_eq!(token_ids(&rows), ["1", "2", "3"]);
+
+    let ask = rows[0].ask.as_ref().expect("The ask isn't expanded");
+    assert_eq!((ask.id.as_str(), ask.price), ("ask-2", dec!(3)));
+    let last_sale = rows[0].last_sale.as_ref().expect("The sale isn't expanded");
+    assert_eq!(
+        (last_sale.token_id.as_str(), last_sale.price),
+        ("1", dec!(4))
+    );
+    assert_eq!(rows[0].best_bid, None);
+
+    let best_bid = rows[1].best_bid.as_ref().expect("The bid isn't expanded");
+    assert_eq!((best_bid.id.as_str(), best_bid.price), ("bid-1", dec!(10)));
+    assert_eq!((&rows[1].ask, &rows[1].last_sale), (&None, &None));
+
+    assert_eq!(rows[2].best_bid, None, "The expired bid is expanded");
+}
+
+#[tokio::test]
+async fn nft_tokens_omit_the_market_rows_unless_expanded() {
+    let app = spawn_app().await;
+    store_market(&app).await;
+
+    let response = app.get_nft_tokens("token_id=1&expand=ask").await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    let row = &body["rows"][0];
+    assert_eq!(row["ask"]["id"], "ask-2");
+    assert!(row.get("best_bid").is_none());
+    assert!(row.get("last_sale").is_none());
+
+    let rows = nft_tokens(&app, "token_id=1").await;
+    assert_eq!((&rows[0].ask, &rows[0].last_sale), (&None, &None));
+}
+
+#[tokio::test]
+async fn nft_tokens_are_filtered_by_asks_and_bids() {
+    let app = spawn_app().await;
+    store_market(&app).await;
+
+    let test_cases = [
+        ("listed=true", vec!["1"]),
+        ("listed=false", vec!["2", "3"]),
+        ("has_bids=true", vec!["2"]),
+        ("has_bids=false", vec!["1", "3"]),
+        ("listed=true&has_bids=true", vec![]),
+        ("listed=false&has_bids=false", vec!["3"]),
+    ];
+    for (query, expected) in test_cases {
+        let rows = nft_tokens(&app, query).await;
+        assert_eq!(token_ids(&rows), expected, "Unexpected tokens of `{query}`");
+    }
+}
+
+#[tokio::test]
+async fn nft_tokens_reject_unknown_expanded_fields() {
+    let app = spawn_app().await;
+
+    let response = app.get_nft_tokens("expand=ask,bids").await;
+
+    assert_eq!(response.status(), 422);
+    assert_error_code(response, "validation.expand_unknown").await;
+
+    let response = app.get_nft_tokens("listed=yes").await;
+
+    assert_eq!(response.status(), 400);
+    assert_error_code(response, "request.invalid_query").await;
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use crate::helpers::spawn_app;
+use serde_json::Value;
+
+mod helpers;
+
+const SPEC_PATH: &str = "openapi.json";
+
+#[tokio::test]
+async fn openapi_spec_matches_the_committed_one() {
+    let app = spawn_app().await;
+
+    let response = app.get_openapi().await;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let actual = response
+        .json::<Value>()
+        .await
+        .expect("Failed to parse the OpenAPI spec");
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(SPEC_PATH);
+    if std::env::var("UPDATE_OPENAPI").is_ok() {
+        let spec = serde_json::to_string_pretty(&actual).unwrap() + "\n";
+        std::fs::write(&path, spec).expect("Failed to update the committed OpenAPI spec");
+    }
+    let expected: Value = serde_json::from_str(
+        &std::fs::read_to_string(&path).expect("Failed to read the committed OpenAPI spec"),
+    )
+    .expect("Failed to parse the committed OpenAPI spec");
+
+    assert!(
+        actual == expected,
+        "The OpenAPI spec drifted from `{SPEC_PATH}`, review the changes and rerun the tests \
+        with `UPDATE_OPENAPI=1` to update it"
+    );
+}
+
+#[tokio::test]
+async fn every_documented_operation_is_routed() {
+    let app = spawn_app().await;
+    let spec = app
+        .get_openapi()
+        .await
+        .json::<Value>()
+        .await
+        .expect("Failed to parse the OpenAPI spec");
+
+    let server = spec["servers"][0]["url"]
+        .as_str()
+        .expect("The spec has no server");
+    let paths = spec["paths"].as_object().expect("The spec has no paths");
+    for (path, item) in paths {
+        let path = path.replace("{user_id}", "alice.near");
+        for method in item.as_object().unwrap().keys() {
+            if method == "parameters" {
+                continue;
+            }
+            let method = method.to_uppercase().parse::<reqwest::Method>().unwrap();
+            let response = reqwest::Client::new()
+                .request(method.clone(), format!("{}{server}{path}", app.address))
+                .send()
+                .await
+                .expect("Failed to execute request");
+            let status = response.status();
+            let body = response.text().await.unwrap();
+            assert!(
+                !(status == reqwest::StatusCode::NOT_FOUND && body.is_empty()),
+                "`{method} {server}{path}` is documented but isn't routed"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn every_schema_reference_resolves() {
+    let app = spawn_app().await;
+    let spec = app
+        .get_openapi()
+        .await
+        .json::<Value>()
+        .await
+        .expect("Failed to parse the OpenAPI spec");
+
+    let mut references = Vec::new();
+    collect_references(&spec, &mut references);
+    assert!(!references.is_empty(), "The spec has no schema references");
+    for reference in references {
+        let name = reference
+            .strip_prefix("#/components/schemas/")
+            .unwrap_or_else(|| panic!("`{reference}` isn't a schema reference"));
+        assert!(
+            spec["components"]["schemas"].get(name).is_some(),
+            "The schema `{name}` is referenced but isn't defined"
+        );
+    }
+}
+
+fn collect_references(value: &Value, references: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => references.push(reference.clone()),
+                    _ => collect_references(value, references),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| collect_references(value, references)),
+        _ => {}
+    }
+}
+
+#[tokio::test]
+async fn docs_page_is_served() {
+    let app = spawn_app().await;
+
+    let response = app.get_docs().await;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let content_type = response.headers()[reqwest::header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(
+        content_type.starts_with("text/html"),
+        "The docs page must be html, actual content type is `{content_type}`"
+    );
+    let page = response.text().await.unwrap();
+    assert!(page.contains("openapi.json"));
+    assert!(!page.contains("/latest/"), "The Redoc bundle isn't pinned");
+}
@@ -15,8 +15,8 @@ async fn paid_return_200_and_1_sale_in_history_stored_in_database() {
     let expected_sale: SaleForRest = Faker.fake();
     sqlx::query!(
         r#"
-        INSERT INTO sales (prev_owner, curr_owner, token_id, price, date)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+        VALUES ('mainnet', 'nft.battlemon.near', $1, $2, $3, $4, $5)
         "#,
         expected_sale.prev_owner,
         expected_sale.curr_owner,
@@ -55,8 +55,8 @@ async fn paid_success_and_returns_200_for_different_valid_queries() {
 
         sqlx::query!(
             r#"
-            INSERT INTO sales (prev_owner, curr_owner, token_id, price, date)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+            VALUES ('mainnet', 'nft.battlemon.near', $1, $2, $3, $4, $5)
             "#,
             sale.prev_owner,
             sale.curr_owner,
@@ -77,7 +77,6 @@ async fn paid_success_and_returns_200_for_different_valid_queries() {
         ("offset=110", 0),
         ("limit=50", 50),
         ("limit=250", 100),
-        ("limit=0", 0),
         ("days=0", 0),
         ("days=1", 100),
         ("days=2", 100),
@@ -86,11 +85,9 @@ async fn paid_success_and_returns_200_for_different_valid_queries() {
         ("days=1&limit=200", 100),
         ("days=2&limit=200", 200),
         ("days=2&limit=150", 150),
-        ("offset=0&limit=0", 0),
         ("offset=10&limit=25", 25),
         ("offset=100&limit=10", 0),
         ("offset=99&limit=11", 1),
-        ("days=1&offset=2&limit=0", 0),
         ("days=1&offset=5&limit=90", 90),
         ("days=1&limit=250&offset=10", 90),
         ("days=2&limit=200&offset=100", 100),
@@ -131,33 +128,48 @@ async fn paid_fails_and_return_500_if_there_is_a_fatal_database_error() {
 }
 
 #[tokio::test]
-async fn paid_fails_and_return_400_when_invalid_queries() {
+async fn paid_fails_and_return_400_when_malformed_queries() {
     let app = spawn_app().await;
 
-    let invalid_queries = [
+    let malformed_queries = [
         "limit",
         "limit=",
-        "limit=-1",
         r#"limit="abc""#,
         r#"limit="10""#,
         "offset",
         "offset=",
-        "offset=-1",
         r#"offset="abc""#,
         r#"offset="10""#,
         "days",
         "days=",
-        "days=-1",
         r#"days="abc""#,
         r#"days="10""#,
     ];
 
-    for invalid_query in invalid_queries {
-        let response = app.get_paid(invalid_query).await;
+    for malformed_query in malformed_queries {
+        let response = app.get_paid(malformed_query).await;
         let actual_status = response.status().as_u16();
         assert_eq!(
             actual_status, 400,
             "Actual: {}. Expected: 400. Wrong query is: {}",
+            actual_status, malformed_query
+        );
+        assert_json_error(response).await;
+    }
+}
+
+#[tokio::test]
+async fn paid_fails_and_return_422_when_invalid_queries() {
+    let app = spawn_app().await;
+
+    let invalid_queries = ["limit=-1", "limit=0", "limit=501", "offset=-1", "days=-1"];
+
+    for invalid_query in invalid_queries {
+        let response = app.get_paid(invalid_query).await;
+        let actual_status = response.status().as_u16();
+        assert_eq!(
+            actual_status, 422,
+            "Actual: {}. Expected: 422. Wrong query is: {}",
             actual_status, invalid_query
         );
         assert_json_error(response).await;
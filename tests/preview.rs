@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use battlemon_models::nft::{FromTraitWeights, Lemon, ModelKind};
+use reqwest::header::{CONTENT_TYPE, ETAG};
+use uuid::Uuid;
+
+use crate::dummies::{NftToken, NFT_CONTRACT_ID};
+use crate::helpers::{assert_error_code, spawn_app_with, TestApp};
+
+mod dummies;
+mod helpers;
+
+const SQUARE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><rect width="8" height="8" fill="red"/></svg>"#;
+
+/// Stores the assets of the traits `cap` and `eyes` of the first lemon.
+fn asset_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+    for (name, value) in [("cap", "cap1"), ("eyes", "eyes1")] {
+        let trait_dir = dir.join("lemon").join(name);
+        std::fs::create_dir_all(&trait_dir).expect("Failed to create the asset dir");
+        std::fs::write(trait_dir.join(format!("{value}.svg")), SQUARE)
+            .expect("Failed to store the asset");
+    }
+
+    dir
+}
+
+async fn spawn_app_with_assets(dir: &Path) -> TestApp {
+    let dir = dir.to_path_buf();
+    let app = spawn_app_with(|cfg| {
+        cfg.preview.asset_dir = dir;
+        cfg.preview.width = 8;
+        cfg.preview.height = 8;
+    })
+    .await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+
+    app
+}
+
+async fn store_lemon(app: &TestApp, token_id: &str, weight: u8) {
+    let token = NftToken {
+        contract_id: NFT_CONTRACT_ID.to_string(),
+        owner_id: "alice.near".to_string(),
+        token_id: token_id.to_string(),
+        media: "lemon.png".to_string(),
+        model: ModelKind::Lemon(Lemon::from_trait_weights(&"".to_string(), &[weight; 5])),
+    };
+    let response = app.post_nft_token(&vec![token]).await;
+    assert_eq!(response.status(), 201, "Failed to insert the token");
+}
+
+#[tokio::test]
+async fn preview_layers_the_assets_of_the_traits() {
+    let dir = asset_dir();
+    let app = spawn_app_with_assets(&dir).await;
+    store_lemon(&app, "1", 1).await;
+
+    let response = app.get_nft_token_preview("1", "svg").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()[CONTENT_TYPE], "image/svg+xml");
+    let etag = response.headers()[ETAG].to_str().unwrap().to_string();
+    let svg = response.text().await.unwrap();
+    assert_eq!(svg.matches("<image").count(), 2, "Unexpected layers: {svg}");
+
+    let response = app
+        .get_with_header("nft_tokens/1/preview.svg", "", ("If-None-Match", &etag))
+        .await;
+    assert_eq!(response.status(), 304);
+
+    let response = app.get_nft_token_preview("1", "png").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()[CONTENT_TYPE], "image/png");
+    let png = response.bytes().await.unwrap();
+    assert!(png.starts_with(b"\x89PNG"), "The preview isn't a PNG");
+}
+
+#[tokio::test]
+async fn preview_is_missing_without_assets() {
+    let dir = asset_dir();
+    let app = spawn_app_with_assets(&dir).await;
+    store_lemon(&app, "2", 2).await;
+
+    let test_cases = [
+        (app.get_nft_token_preview("1", "svg").await, 404),
+        (app.get_nft_token_preview("2", "svg").await, 404),
+        (app.get_nft_token_preview("2", "gif").await, 400),
+    ];
+    for (response, status) in test_cases {
+        assert_eq!(response.status(), status);
+    }
+
+    let response = app.get_nft_token_preview("2", "png").await;
+    assert_error_code(response, "nft_tokens.not_found").await;
+}
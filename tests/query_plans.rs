@@ -0,0 +1,367 @@
+//! The handler queries are explained against realistic volumes, each of them has to find its
+//! rows through an index rather than a sequential scan of its tables. The queries are read from
+//! the same files as the handlers' ones.
+use chrono::{Duration, Utc};
+use serde_json::Value;
+use sqlx::postgres::PgArguments;
+use sqlx::query::QueryScalar;
+use sqlx::{Executor, PgConnection, PgPool, Postgres};
+
+use crate::dummies::NFT_CONTRACT_ID;
+use crate::helpers::spawn_app;
+
+mod dummies;
+mod helpers;
+
+/// The search indexes only beat a sequential scan of tens of thousands of tokens.
+const TOKENS: i64 = 50_000;
+const OWNERS: i64 = 1_000;
+
+/// See `routes::nft_tokens_db`.
+const NFT_TOKENS_QUERY: &str = include_str!("../queries/nft_tokens.sql");
+/// See `routes::search_nft_tokens_db`.
+const SEARCH_QUERY: &str = include_str!("../queries/search_nft_tokens.sql");
+/// See `routes::get_trait_counts_db`.
+const TRAITS_QUERY: &str = include_str!("../queries/collection_traits.sql");
+/// See `routes::token_ownership_db`.
+const IS_OWNER_QUERY: &str = include_str!("../queries/token_ownership.sql");
+/// See `routes::sales_db`.
+const SALES_QUERY: &str = include_str!("../queries/sales.sql");
+/// See `routes::trades_db`.
+const PAID_QUERY: &str = include_str!("../queries/trades.sql");
+/// See `routes::asks_db`.
+const ASKS_QUERY: &str = include_str!("../queries/asks.sql");
+/// See `routes::bids_db`.
+const BIDS_QUERY: &str = include_str!("../queries/bids.sql");
+
+type Explain<'q> = QueryScalar<'q, Postgres, Value, PgArguments>;
+
+fn explain(query: &str) -> Explain<'_> {
+    sqlx::query_scalar(query)
+}
+
+/// Stores the tokens of the owners, 1% of them firearms, a sale per token over the last year and
+/// the asks and the bids of a quarter of the tokens.
+async fn seed(pool: &PgPool) {
+    let statements = [
+        r#"
+        INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, title, media, model, db_created_at, rarity_rank)
+        SELECT 'mainnet', $1, 'owner' || (i % $3) || '.near', i::text, 'Token ' || i, 'token.png',
+            CASE WHEN i % 100 = 0
+                THEN jsonb_build_object('kind', 'firearm', 'level', i % 3, 'type', 'type' || i % 7)
+                ELSE jsonb_build_object('kind', 'lemon', 'cap', 'cap' || i % 5, 'cloth', 'cloth' || i % 11,
+                    'exo', 'exo' || i % 13, 'eyes', 'eyes' || i % 17, 'head', 'head' || i % 19, 'teeth', 't')
+            END,
+            now(), i % 1000 + 1
+        FROM generate_series(1, $2) AS i
+        "#,
+        r#"
+        INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+        SELECT 'mainnet', $1, 'owner' || (i % $3) || '.near', 'owner' || ((i + 1) % $3) || '.near',
+            (i % $2)::text, i % 100 + 1, now() - make_interval(secs => i * 31536000.0 / $2)
+        FROM generate_series(1, $2) AS i
+        "#,
+        r#"
+        INSERT INTO asks (network, contract_id, id, token_id, account_id, approval_id, price)
+        SELECT 'mainnet', $1, 'ask' || i, (i * 4)::text, 'owner' || (i % $3) || '.near', i, i % 100 + 1
+        FROM generate_series(1, $2 / 4) AS i
+        "#,
+        r#"
+        INSERT INTO bids (network, contract_id, id, token_id, account_id, expire_at, create_at, price)
+        SELECT 'mainnet', $1, 'bid' || i, (i * 4 + 1)::text, 'owner' || (i % $3) || '.near', null, now(), i % 100 + 1
+        FROM generate_series(1, $2 / 4) AS i
+        "#,
+    ];
+    for statement in statements {
+        sqlx::query(statement)
+            .bind(NFT_CONTRACT_ID)
+            .bind(TOKENS)
+            .bind(OWNERS)
+            .execute(pool)
+            .await
+            .expect("Failed to seed the tables");
+    }
+    sqlx::query("ANALYZE nft_tokens, sales, asks, bids")
+        .execute(pool)
+        .await
+        .expect("Failed to analyze the tables");
+}
+
+/// The relations scanned sequentially and the indexes used by the plan's nodes, but the ones of
+/// the branches the bound values disable, which aren't run.
+fn scans(plan: &Value, seq_scans: &mut Vec<String>, indexes: &mut Vec<String>) {
+    if plan["One-Time Filter"] == "false" {
+        return;
+    }
+    if plan["Node Type"] == "Seq Scan" {
+        seq_scans.push(
+            plan["Relation Name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        );
+    }
+    if let Some(index) = plan["Index Name"].as_str() {
+        indexes.push(index.to_string());
+    }
+    for child in plan["Plans"].as_array().into_iter().flatten() {
+        scans(child, seq_scans, indexes);
+    }
+}
+
+/// Asserts that no table is scanned sequentially and that the indexes are used.
+async fn assert_indexed<'c>(
+    executor: impl Executor<'c, Database = Postgres>,
+    name: &str,
+    query: Explain<'_>,
+    expected: &[&str],
+) {
+    let plan = query
+        .fetch_one(executor)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to explain `{name}`: {e}"));
+    let (mut seq_scans, mut indexes) = (Vec::new(), Vec::new());
+    scans(&plan[0]["Plan"], &mut seq_scans, &mut indexes);
+
+    assert!(
+        seq_scans.is_empty(),
+        "`{name}` scans {seq_scans:?} sequentially:\n{plan:#}"
+    );
+    for index in expected {
+        assert!(
+            indexes.iter().any(|i| i == index),
+            "`{name}` doesn't use `{index}`:\n{plan:#}"
+        );
+    }
+}
+
+/// The statement explaining the custom plan of the query, which is made for the bound values.
+fn explained(query: &str) -> String {
+    format!("EXPLAIN (FORMAT JSON) {query}")
+}
+
+/// The statement explaining the generic plan of the query prepared by [`prepare_generic`] for
+/// the literal values, the plan is made regardless of them as the server settles on it for a
+/// reused statement.
+fn explained_generic(values: &[&str]) -> String {
+    format!(
+        "EXPLAIN (FORMAT JSON) EXECUTE generic({})",
+        values.join(", ")
+    )
+}
+
+async fn prepare_generic(conn: &mut PgConnection, query: &str) {
+    let statements = [
+        "SET plan_cache_mode = force_generic_plan".to_string(),
+        format!("PREPARE generic AS {query}"),
+    ];
+    for statement in statements {
+        conn.execute(statement.as_str())
+            .await
+            .expect("Failed to prepare the generic plan");
+    }
+}
+
+#[tokio::test]
+async fn nft_tokens_queries_use_indexes() {
+    let app = spawn_app().await;
+    seed(&app.db_pool).await;
+    let query = explained(NFT_TOKENS_QUERY);
+    let nft_tokens = |contract_id: Option<&'static str>,
+                      token_id: Option<&'static str>,
+                      owner_id: Option<&'static str>,
+                      nft_kind: Option<&'static str>,
+                      sort: &'static str,
+                      expand: bool,
+                      listed: Option<bool>| {
+        explain(&query)
+            .bind("mainnet")
+            .bind(contract_id)
+            .bind(token_id)
+            .bind(owner_id)
+            .bind(nft_kind)
+            .bind(21_i64)
+            .bind(0_i64)
+            .bind(sort)
+            .bind(expand)
+            .bind(expand)
+            .bind(expand)
+            .bind(listed)
+            .bind(listed)
+    };
+    let test_cases = [
+        (
+            "owner_id",
+            nft_tokens(None, None, Some("owner7.near"), None, "id", false, None),
+            vec!["nft_tokens_network_owner_id_idx"],
+        ),
+        (
+            "token_id",
+            nft_tokens(None, Some("42"), None, None, "id", false, None),
+            vec!["nft_tokens_network_token_id_idx"],
+        ),
+        (
+            "contract_id and token_id",
+            nft_tokens(
+                Some(NFT_CONTRACT_ID),
+                Some("42"),
+                None,
+                None,
+                "id",
+                false,
+                None,
+            ),
+            vec![],
+        ),
+        (
+            "nft_kind",
+            nft_tokens(None, None, None, Some("firearm"), "id", false, None),
+            vec![],
+        ),
+        (
+            "sort=rarity",
+            nft_tokens(None, None, None, None, "rarity", false, None),
+            vec!["nft_tokens_network_rarity_rank_id_idx"],
+        ),
+        (
+            "expand and listed",
+            nft_tokens(
+                None,
+                None,
+                Some("owner7.near"),
+                None,
+                "id",
+                true,
+                Some(true),
+            ),
+            vec!["nft_tokens_network_owner_id_idx"],
+        ),
+    ];
+
+    for (name, query, expected) in test_cases {
+        assert_indexed(&app.db_pool, name, query, &expected).await;
+    }
+
+    // a reused statement settles on the plan made before the sort is known
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    prepare_generic(&mut conn, NFT_TOKENS_QUERY).await;
+    for sort in ["rarity", "id"] {
+        let statement = explained_generic(&[
+            "'mainnet'",
+            "null",
+            "null",
+            "null",
+            "null",
+            "21",
+            "0",
+            &format!("'{sort}'"),
+            "true",
+            "true",
+            "true",
+            "null",
+            "null",
+        ]);
+        let expected = match sort {
+            "rarity" => vec!["nft_tokens_network_rarity_rank_id_idx"],
+            _ => vec![],
+        };
+        let name = format!("generic sort={sort}");
+        assert_indexed(&mut *conn, &name, explain(&statement), &expected).await;
+    }
+}
+
+#[tokio::test]
+async fn nft_tokens_lookups_use_indexes() {
+    let app = spawn_app().await;
+    seed(&app.db_pool).await;
+    let search = explained(SEARCH_QUERY);
+    let traits = explained(TRAITS_QUERY);
+    let is_owner = explained(IS_OWNER_QUERY);
+    let test_cases = [
+        (
+            "search",
+            explain(&search)
+                .bind("mainnet")
+                .bind("token1234:*")
+                .bind("token1234")
+                .bind(None::<&str>)
+                .bind(None::<&str>)
+                .bind(21_i64)
+                .bind(0_i64),
+            vec![
+                "nft_tokens_search_document_idx",
+                "nft_tokens_search_text_idx",
+            ],
+        ),
+        (
+            "collection traits",
+            explain(&traits)
+                .bind("mainnet")
+                .bind(None::<&str>)
+                .bind("firearm"),
+            vec!["nft_tokens_network_kind_idx"],
+        ),
+        (
+            "is_owner",
+            explain(&is_owner)
+                .bind("mainnet")
+                .bind("owner7.near")
+                .bind(None::<&str>)
+                .bind(vec!["7", "1007"]),
+            vec![],
+        ),
+    ];
+
+    for (name, query, expected) in test_cases {
+        assert_indexed(&app.db_pool, name, query, &expected).await;
+    }
+}
+
+#[tokio::test]
+async fn market_queries_use_indexes() {
+    let app = spawn_app().await;
+    seed(&app.db_pool).await;
+    let sales = explained(SALES_QUERY);
+    let paid = explained(PAID_QUERY);
+    let asks = explained(ASKS_QUERY);
+    let bids = explained(BIDS_QUERY);
+    let by_token_id = |query| {
+        explain(query)
+            .bind("mainnet")
+            .bind(None::<&str>)
+            .bind("42")
+            .bind(21_i64)
+            .bind(0_i64)
+    };
+    let test_cases = [
+        (
+            "sales",
+            by_token_id(&sales),
+            vec!["sales_network_token_id_idx"],
+        ),
+        (
+            "paid",
+            explain(&paid)
+                .bind("mainnet")
+                .bind(None::<&str>)
+                .bind(Utc::now() - Duration::days(1))
+                .bind(0_i64)
+                .bind(20_i64),
+            vec!["sales_network_date_idx"],
+        ),
+        (
+            "asks",
+            by_token_id(&asks),
+            vec!["asks_network_token_id_idx"],
+        ),
+        (
+            "bids",
+            by_token_id(&bids),
+            vec!["bids_network_token_id_idx"],
+        ),
+    ];
+
+    for (name, query, expected) in test_cases {
+        assert_indexed(&app.db_pool, name, query, &expected).await;
+    }
+}
@@ -0,0 +1,100 @@
+use battlemon_rest::config::Budget;
+use helpers::{assert_json_error, spawn_app_with, TestApp};
+
+mod helpers;
+
+const TINY_BUDGET: Budget = Budget {
+    capacity: 2,
+    refill_per_second: 0.001,
+};
+
+async fn spawn_app_with_tiny_sales_budget() -> TestApp {
+    spawn_app_with(|cfg| {
+        cfg.rate_limit.api_keys = vec!["indexer".to_string()];
+        cfg.rate_limit
+            .routes
+            .insert("sales".to_string(), TINY_BUDGET);
+    })
+    .await
+}
+
+#[tokio::test]
+async fn requests_within_budget_return_rate_limit_headers() {
+    let app = spawn_app_with_tiny_sales_budget().await;
+
+    for expected_remaining in ["1", "0"] {
+        let response = app.get_sales("").await;
+        assert_eq!(response.status().as_u16(), 200);
+        let headers = response.headers();
+        assert_eq!(headers["ratelimit-limit"], "2");
+        assert_eq!(headers["ratelimit-remaining"], expected_remaining);
+        assert!(headers.contains_key("ratelimit-reset"));
+    }
+}
+
+#[tokio::test]
+async fn requests_over_budget_are_rejected_with_429() {
+    let app = spawn_app_with_tiny_sales_budget().await;
+    for _ in 0..2 {
+        app.get_sales("").await;
+    }
+
+    let response = app.get_sales("").await;
+    assert_eq!(
+        response.status().as_u16(),
+        429,
+        "The API didn't return 429 Too Many Requests"
+    );
+    assert!(response.headers().contains_key("retry-after"));
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+    assert_json_error(response).await;
+}
+
+#[tokio::test]
+async fn budgets_are_counted_per_route() {
+    let app = spawn_app_with_tiny_sales_budget().await;
+    for _ in 0..3 {
+        app.get_sales("").await;
+    }
+
+    let response = app.get_nft_tokens("").await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn known_api_key_is_counted_separately_from_client_ip() {
+    let app = spawn_app_with_tiny_sales_budget().await;
+    for _ in 0..3 {
+        app.get_sales("").await;
+    }
+
+    let client = reqwest::Client::new();
+    let with_api_key = |key: &'static str| {
+        client
+            .get(format!("{}/sales", app.address))
+            .header("X-Api-Key", key)
+            .send()
+    };
+
+    let response = with_api_key("indexer").await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let response = with_api_key("unknown").await.unwrap();
+    assert_eq!(response.status().as_u16(), 429);
+}
+
+#[tokio::test]
+async fn disabled_rate_limiter_lets_all_requests_through() {
+    let app = spawn_app_with(|cfg| {
+        cfg.rate_limit.enabled = false;
+        cfg.rate_limit
+            .routes
+            .insert("sales".to_string(), TINY_BUDGET);
+    })
+    .await;
+
+    for _ in 0..5 {
+        let response = app.get_sales("").await;
+        assert_eq!(response.status().as_u16(), 200);
+        assert!(!response.headers().contains_key("ratelimit-limit"));
+    }
+}
@@ -1,9 +1,10 @@
 use battlemon_models::market::sale::{SaleForDb, SaleForRest};
+use battlemon_rest::config::OversizedLimit;
 use battlemon_rest::routes::RowsJsonReport;
 use chrono::Utc;
 use fake::{Fake, Faker};
 
-use helpers::{assert_json_error, spawn_app};
+use helpers::{assert_json_error, spawn_app, spawn_app_with};
 
 mod dummies;
 mod helpers;
@@ -25,8 +26,8 @@ async fn sales_return_200_and_one_stored_in_database_token() {
     let expected_sale: SaleForRest = Faker.fake();
     sqlx::query!(
         r#"
-        INSERT INTO sales (prev_owner, curr_owner, token_id, price, date)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+        VALUES ('mainnet', 'nft.battlemon.near', $1, $2, $3, $4, $5)
         "#,
         expected_sale.prev_owner,
         expected_sale.curr_owner,
@@ -53,8 +54,8 @@ async fn sales_success_and_returns_200_for_different_valid_queries() {
     for sale in sales {
         sqlx::query!(
             r#"
-            INSERT INTO sales (prev_owner, curr_owner, token_id, price, date)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+            VALUES ('mainnet', 'nft.battlemon.near', $1, $2, $3, $4, $5)
             "#,
             sale.prev_owner,
             sale.curr_owner,
@@ -75,8 +76,6 @@ async fn sales_success_and_returns_200_for_different_valid_queries() {
         ("offset=150", 50, true),
         ("limit=50", 50, false),
         ("limit=250", 200, true),
-        ("limit=0", 0, false),
-        ("offset=0&limit=0", 0, false),
         ("offset=10&limit=25", 25, false),
         ("offset=200&limit=10", 0, true),
         ("offset=199&limit=11", 1, true),
@@ -126,8 +125,8 @@ async fn sales_success_with_valid_queries_for_token_id() {
     for sale in sales {
         sqlx::query!(
             r#"
-            INSERT INTO sales (prev_owner, curr_owner, token_id, price, date)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+            VALUES ('mainnet', 'nft.battlemon.near', $1, $2, $3, $4, $5)
             "#,
             sale.prev_owner,
             sale.curr_owner,
@@ -187,40 +186,85 @@ async fn sale_fails_and_return_500_if_there_is_a_fatal_database_error() {
 }
 
 #[tokio::test]
-async fn sale_fails_and_return_400_when_invalid_queries() {
+async fn sale_fails_and_return_400_when_malformed_queries() {
     let app = spawn_app().await;
 
-    let invalid_queries = [
+    let malformed_queries = [
         "limit",
         "limit=",
-        "limit=-1",
         r#"limit="abc""#,
         r#"limit="10""#,
         "offset",
         "offset=",
-        "offset=-1",
-        "token_id",
-        "token_id=",
-        r#"token_id="abc""#,
-        r#"token_id="10""#,
-        "token_id=-1",
         r#"offset="abc""#,
         r#"offset="10""#,
         "days",
         "days=",
-        "days=-1",
         r#"days="abc""#,
         r#"days="10""#,
     ];
 
-    for invalid_query in invalid_queries {
-        let response = app.get_sales(invalid_query).await;
+    for malformed_query in malformed_queries {
+        let response = app.get_sales(malformed_query).await;
         let actual_status = response.status().as_u16();
         assert_eq!(
             actual_status, 400,
             "Actual: {}. Expected: 400. Wrong query is: {}",
+            actual_status, malformed_query
+        );
+        assert_json_error(response).await;
+    }
+}
+
+#[tokio::test]
+async fn sale_fails_and_return_422_when_invalid_queries() {
+    let app = spawn_app().await;
+
+    let invalid_queries = [
+        "limit=-1",
+        "limit=0",
+        "limit=501",
+        "offset=-1",
+        "token_id",
+        "token_id=",
+        "token_id=lemon%07",
+        "days=-1",
+    ];
+
+    for invalid_query in invalid_queries {
+        let response = app.get_sales(invalid_query).await;
+        let actual_status = response.status().as_u16();
+        assert_eq!(
+            actual_status, 422,
+            "Actual: {}. Expected: 422. Wrong query is: {}",
             actual_status, invalid_query
         );
         assert_json_error(response).await;
     }
 }
+
+#[tokio::test]
+async fn sales_report_contains_effective_limit() {
+    let app = spawn_app().await;
+
+    for (query, expected_limit) in [("", 100), ("limit=10", 10), ("limit=500", 500)] {
+        let response = app.get_sales(query).await;
+        assert_eq!(response.status().as_u16(), 200);
+        let actual_sales = response.json::<RowsJsonReport<SaleForDb>>().await.unwrap();
+        assert_eq!(
+            actual_sales.limit, expected_limit,
+            "The effective limit is wrong for query `{}`",
+            query
+        );
+    }
+}
+
+#[tokio::test]
+async fn sales_oversized_limit_is_clamped_when_configured() {
+    let app = spawn_app_with(|cfg| cfg.pagination.oversized_limit = OversizedLimit::Clamp).await;
+
+    let response = app.get_sales("limit=100000000").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let actual_sales = response.json::<RowsJsonReport<SaleForDb>>().await.unwrap();
+    assert_eq!(actual_sales.limit, 500);
+}
@@ -0,0 +1,136 @@
+use battlemon_models::nft::{FromTraitWeights, Lemon, ModelKind};
+use battlemon_rest::routes::{KindFacet, SearchReport, TraitFacet};
+use sqlx::types::{chrono::Utc, Json};
+
+use crate::dummies::NFT_CONTRACT_ID;
+use crate::helpers::{assert_error_code, spawn_app, TestApp};
+
+mod dummies;
+mod helpers;
+
+async fn store_lemon(app: &TestApp, token_id: &str, title: &str, owner_id: &str, weights: [u8; 5]) {
+    let model = ModelKind::Lemon(Lemon::from_trait_weights(&"".to_string(), &weights));
+    sqlx::query!(
+        r#"
+        INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, title, media, model, db_created_at)
+        VALUES ('mainnet', $1, $2, $3, $4, 'lemon.png', $5, $6)
+        "#,
+        NFT_CONTRACT_ID,
+        owner_id,
+        token_id,
+        title,
+        Json(model) as _,
+        Utc::now()
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the lemon");
+}
+
+async fn search(app: &TestApp, query: &str) -> SearchReport {
+    let response = app.get_search(query).await;
+    assert_eq!(response.status(), 200, "The search `{query}` failed");
+    response
+        .json()
+        .await
+        .expect("Failed to parse the search report")
+}
+
+fn token_ids(report: &SearchReport) -> Vec<&str> {
+    report
+        .rows
+        .iter()
+        .map(|r| r.item.nft_token.token_id.as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn search_matches_partial_words_traits_and_typos() {
+    let app = spawn_app().await;
+    store_lemon(&app, "1", "Sour Lemon", "alice.near", [1, 1, 1, 1, 1]).await;
+    store_lemon(&app, "2", "Sweet Lemon", "bob.near", [2, 2, 2, 2, 2]).await;
+    store_lemon(&app, "3", "Lemonade", "alice.near", [1, 2, 3, 4, 5]).await;
+
+    let test_cases = [
+        ("q=sou", "1"),
+        ("q=lemon%20swe", "2"),
+        ("q=bob", "2"),
+        ("q=head5", "3"),
+        ("q=lemmonade", "3"),
+    ];
+    for (query, expected) in test_cases {
+        let report = search(&app, query).await;
+        assert_eq!(
+            token_ids(&report).first(),
+            Some(&expected),
+            "Unexpected best match of `{query}`"
+        );
+    }
+}
+
+#[tokio::test]
+async fn search_ranks_full_text_matches_above_typos() {
+    let app = spawn_app().await;
+    store_lemon(&app, "1", "Lemmon", "alice.near", [1, 1, 1, 1, 1]).await;
+    store_lemon(&app, "2", "Lemon", "alice.near", [1, 1, 1, 1, 1]).await;
+
+    let report = search(&app, "q=lemon").await;
+    assert_eq!(token_ids(&report), ["2", "1"]);
+}
+
+#[tokio::test]
+async fn search_counts_the_facets_of_all_matches() {
+    let app = spawn_app().await;
+    store_lemon(&app, "1", "Lemon", "alice.near", [1, 1, 1, 1, 1]).await;
+    store_lemon(&app, "2", "Lemon", "alice.near", [1, 2, 2, 2, 2]).await;
+    store_lemon(&app, "3", "Orange", "alice.near", [1, 1, 1, 1, 1]).await;
+
+    let report = search(&app, "q=lemon&limit=1").await;
+    assert_eq!(report.rows.len(), 1);
+    assert!(!report.end);
+    assert_eq!(
+        report.facets.kinds,
+        [KindFacet {
+            kind: "lemon".to_string(),
+            count: 2
+        }]
+    );
+    let caps: Vec<_> = report
+        .facets
+        .traits
+        .iter()
+        .filter(|t| t.name == "cap")
+        .collect();
+    assert_eq!(
+        caps,
+        [&TraitFacet {
+            name: "cap".to_string(),
+            value: "cap1".to_string(),
+            count: 2
+        }]
+    );
+
+    let report = search(&app, "q=lemon&offset=1").await;
+    assert_eq!(report.rows.len(), 1);
+    assert!(report.end);
+}
+
+#[tokio::test]
+async fn search_rejects_invalid_queries_with_422() {
+    let app = spawn_app().await;
+    let test_cases = [
+        ("", "validation.search_query_empty"),
+        ("q=%26%7C%21", "validation.search_query_empty"),
+        ("q=lemon&limit=-1", "validation.limit_negative"),
+        (
+            "q=lemon&contract_id=items.near",
+            "validation.contract_id_unknown",
+        ),
+    ];
+
+    for (query, code) in test_cases {
+        let response = app.get_search(query).await;
+        assert_eq!(response.status(), 422, "`{query}` isn't rejected");
+        assert_error_code(response, code).await;
+    }
+}
@@ -0,0 +1,156 @@
+use battlemon_models::market::{ask::AskForDb, bid::BidForDb, sale::SaleForDb};
+use chrono::{Duration, Utc};
+use fake::Fake;
+use reqwest::header::ETAG;
+use rust_decimal_macros::dec;
+use sqlx::types::Json;
+
+use crate::dummies::{AliceNftToken, NftToken, NFT_CONTRACT_ID};
+use battlemon_rest::routes::{ContractScoped, NftTokenRow};
+use helpers::{assert_error_code, spawn_app, TestApp};
+
+mod dummies;
+mod helpers;
+
+async fn store_token(app: &TestApp, token_id: &str) {
+    let token: NftToken = AliceNftToken.fake();
+    sqlx::query!(
+        r#"
+        INSERT INTO nft_tokens (network, contract_id, owner_id, token_id, media, model, db_created_at)
+        VALUES ('mainnet', $1, $2, $3, $4, $5, $6)
+        "#,
+        NFT_CONTRACT_ID,
+        token.owner_id,
+        token_id,
+        token.media,
+        Json(token.model) as _,
+        Utc::now()
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the nft token");
+}
+
+/// Stores the ask `ask-1`, the bid `bid-1` and a sale of the token `1`, returns the sale id.
+async fn store_market(app: &TestApp) -> i64 {
+    sqlx::query!(
+        r#"
+        INSERT INTO asks (network, contract_id, id, token_id, account_id, approval_id, price)
+        VALUES ('mainnet', $1, 'ask-1', '1', 'alice.near', 1, 5)
+        "#,
+        NFT_CONTRACT_ID,
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the ask");
+    sqlx::query!(
+        r#"
+        INSERT INTO bids (network, contract_id, id, token_id, account_id, expire_at, create_at, price)
+        VALUES ('mainnet', $1, 'bid-1', '1', 'bob.near', $2, $3, 7)
+        "#,
+        NFT_CONTRACT_ID,
+        Utc::now() + Duration::days(1),
+        Utc::now(),
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the bid");
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO sales (network, contract_id, prev_owner, curr_owner, token_id, price, date)
+        VALUES ('mainnet', $1, 'bob.near', 'alice.near', '1', 3, $2)
+        RETURNING id
+        "#,
+        NFT_CONTRACT_ID,
+        Utc::now(),
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to store the sale")
+}
+
+fn etag(response: &reqwest::Response) -> Option<&str> {
+    response.headers().get(ETAG).map(|v| v.to_str().unwrap())
+}
+
+#[tokio::test]
+async fn nft_token_is_returned_with_its_version() {
+    let app = spawn_app().await;
+    store_token(&app, "1").await;
+
+    let response = app.get_nft_token("1", "").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(etag(&response), Some("\"1\""));
+    let token: ContractScoped<NftTokenRow> = response.json().await.unwrap();
+    assert_eq!(token.contract_id, NFT_CONTRACT_ID);
+    assert_eq!(token.item.nft_token.token_id, "1");
+
+    let response = app
+        .get_with_header("nft_tokens/1", "", ("If-None-Match", "\"1\""))
+        .await;
+    assert_eq!(response.status(), 304);
+
+    store_market(&app).await;
+    let response = app.get_nft_token("1", "expand=ask").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(etag(&response), None, "The expanded token is tagged");
+    let token: ContractScoped<NftTokenRow> = response.json().await.unwrap();
+    assert_eq!(token.item.ask.map(|a| a.id).as_deref(), Some("ask-1"));
+}
+
+#[tokio::test]
+async fn market_rows_are_returned_by_id() {
+    let app = spawn_app().await;
+    let sale_id = store_market(&app).await;
+
+    let response = app.get_ask("ask-1").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(etag(&response), Some("\"1\""));
+    let ask: ContractScoped<AskForDb> = response.json().await.unwrap();
+    assert_eq!((ask.item.token_id.as_str(), ask.item.price), ("1", dec!(5)));
+
+    let response = app.get_bid("bid-1").await;
+    assert_eq!(response.status(), 200);
+    let bid: ContractScoped<BidForDb> = response.json().await.unwrap();
+    assert_eq!(
+        (bid.item.account_id.as_str(), bid.item.price),
+        ("bob.near", dec!(7))
+    );
+
+    let response = app.get_sale(&sale_id.to_string()).await;
+    assert_eq!(response.status(), 200);
+    let sale: ContractScoped<SaleForDb> = response.json().await.unwrap();
+    assert_eq!((sale.item.id, sale.item.price), (sale_id, dec!(3)));
+}
+
+#[tokio::test]
+async fn missing_resources_return_404() {
+    let app = spawn_app().await;
+    let test_cases = [
+        (app.get_nft_token("1", "").await, "nft_tokens.not_found"),
+        (app.get_ask("ask-1").await, "asks.not_found"),
+        (app.get_bid("bid-1").await, "bids.not_found"),
+        (app.get_sale("1").await, "sales.not_found"),
+    ];
+
+    for (response, code) in test_cases {
+        assert_eq!(response.status(), 404, "Unexpected status for `{code}`");
+        assert_error_code(response, code).await;
+    }
+}
+
+#[tokio::test]
+async fn malformed_ids_are_rejected() {
+    let app = spawn_app().await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+
+    let response = app.get_sale("first").await;
+    assert_eq!(response.status(), 400);
+    assert_error_code(response, "request.invalid_path").await;
+
+    let response = app
+        .get_nft_token("1", "contract_id=items.battlemon.near")
+        .await;
+    assert_eq!(response.status(), 422);
+    assert_error_code(response, "validation.contract_id_unknown").await;
+}
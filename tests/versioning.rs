@@ -0,0 +1,125 @@
+use crate::dummies::NFT_CONTRACT_ID;
+use crate::helpers::spawn_app_with;
+
+use battlemon_models::market::sale::{SaleForDb, SaleForRest};
+use battlemon_rest::config::DeprecationSettings;
+use battlemon_rest::routes::{ContractScoped, RowsJsonReport};
+use fake::Fake;
+use reqwest::Response;
+
+mod dummies;
+mod helpers;
+
+fn header(response: &Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn legacy_aliases_carry_the_configured_deprecation_headers() {
+    let app = spawn_app_with(|cfg| {
+        cfg.api_versions.deprecated.insert(
+            "legacy".to_string(),
+            DeprecationSettings {
+                since: Some("2022-09-12T00:00:00Z".parse().unwrap()),
+                sunset: Some("2023-03-01T00:00:00Z".parse().unwrap()),
+                link: Some("https://example.com/migrating-to-v1".to_string()),
+            },
+        );
+    })
+    .await;
+
+    let response = app.get_sales("").await;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(
+        header(&response, "Deprecation").as_deref(),
+        Some("Mon, 12 Sep 2022 00:00:00 GMT")
+    );
+    assert_eq!(
+        header(&response, "Sunset").as_deref(),
+        Some("Wed, 01 Mar 2023 00:00:00 GMT")
+    );
+    assert_eq!(
+        header(&response, "Link").as_deref(),
+        Some("<https://example.com/migrating-to-v1>; rel=\"deprecation\"")
+    );
+
+    let response = app.get_on_version("v1", "sales", "").await;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(header(&response, "Deprecation"), None);
+    assert_eq!(header(&response, "Sunset"), None);
+}
+
+#[tokio::test]
+async fn deprecated_version_without_date_is_flagged() {
+    let app = spawn_app_with(|cfg| {
+        cfg.api_versions.deprecated.clear();
+        cfg.api_versions.deprecated.insert(
+            "v1".to_string(),
+            DeprecationSettings {
+                since: None,
+                sunset: None,
+                link: None,
+            },
+        );
+    })
+    .await;
+
+    let response = app.get_on_version("v1", "sales", "").await;
+    assert_eq!(header(&response, "Deprecation").as_deref(), Some("true"));
+    assert_eq!(header(&response, "Sunset"), None);
+
+    let response = app.get_sales("").await;
+    assert_eq!(header(&response, "Deprecation"), None);
+}
+
+#[tokio::test]
+async fn health_check_at_the_root_is_not_deprecated() {
+    let app = spawn_app_with(|_| {}).await;
+
+    let response = reqwest::get(format!("{}/health_check", app.address))
+        .await
+        .expect("Failed to execute request");
+    assert!(response.status().is_success());
+    assert_eq!(header(&response, "Deprecation"), None);
+}
+
+#[tokio::test]
+async fn legacy_and_v1_routes_serve_the_same_data() {
+    let app = spawn_app_with(|_| {}).await;
+    app.configure_contracts("mainnet", NFT_CONTRACT_ID).await;
+    let sale = dummies::AliceToBobSale.fake::<SaleForRest>();
+
+    let response = app
+        .post_sale(&ContractScoped::new(NFT_CONTRACT_ID, &sale))
+        .await;
+    assert!(
+        response.status().is_success(),
+        "Failed to insert sale, status is `{}`",
+        response.status()
+    );
+
+    let responses = [
+        app.get_on_version("v1", "sales", "").await,
+        app.get_on_version("v1", "networks/mainnet/sales", "").await,
+    ];
+    for response in responses {
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let report = response
+            .json::<RowsJsonReport<SaleForDb>>()
+            .await
+            .expect("Failed to parse the sales report");
+        assert_eq!(report.rows.len(), 1, "The sale must be served on `v1`");
+        assert_eq!(report.rows[0].token_id, sale.token_id);
+    }
+}
+
+#[tokio::test]
+async fn unknown_version_is_not_found() {
+    let app = spawn_app_with(|_| {}).await;
+
+    let response = app.get_on_version("v9", "sales", "").await;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}